@@ -0,0 +1,16 @@
+-- Create tags table
+CREATE TABLE IF NOT EXISTS tags (
+    id BLOB PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+-- Create join table between playlists and tags
+CREATE TABLE IF NOT EXISTS playlist_tags (
+    playlist_id BLOB NOT NULL,
+    tag_id BLOB NOT NULL,
+    PRIMARY KEY (playlist_id, tag_id),
+    FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_playlist_tags_tag_id ON playlist_tags(tag_id);
@@ -0,0 +1,2 @@
+-- Add optional custom text printed on the back of each card
+ALTER TABLE playlists ADD COLUMN back_text TEXT;
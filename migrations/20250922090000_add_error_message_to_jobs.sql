@@ -0,0 +1,2 @@
+-- Store why a job failed
+ALTER TABLE jobs ADD COLUMN error_message TEXT;
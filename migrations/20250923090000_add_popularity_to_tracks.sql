@@ -0,0 +1,2 @@
+-- Add Spotify popularity (0-100) to tracks, used to judge how hard a card is to guess
+ALTER TABLE tracks ADD COLUMN popularity TINYINT;
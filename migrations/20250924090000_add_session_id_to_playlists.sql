@@ -0,0 +1,4 @@
+-- Scope playlists to the anonymous session that created them
+ALTER TABLE playlists ADD COLUMN session_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_playlists_session_id ON playlists(session_id);
@@ -0,0 +1,2 @@
+-- Add optional game master notes (hints, fun facts, point values) to tracks
+ALTER TABLE tracks ADD COLUMN notes TEXT;
@@ -0,0 +1,17 @@
+-- Each position holds a single track of a playlist.
+-- Playlists that already have two tracks at one position are renumbered first, keeping
+-- their order and otherwise the order the tracks were stored in.
+CREATE TEMP TABLE renumbered_tracks AS
+SELECT id, ROW_NUMBER() OVER (PARTITION BY playlist_id ORDER BY position, rowid) - 1 AS position
+FROM tracks
+WHERE playlist_id IN (
+    SELECT playlist_id FROM tracks GROUP BY playlist_id, position HAVING COUNT(*) > 1
+);
+
+UPDATE tracks
+SET position = (SELECT position FROM renumbered_tracks WHERE renumbered_tracks.id = tracks.id)
+WHERE id IN (SELECT id FROM renumbered_tracks);
+
+DROP TABLE renumbered_tracks;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tracks_playlist_id_position ON tracks(playlist_id, position);
@@ -0,0 +1,11 @@
+-- Keep only the oldest copy of Spotify playlists a session imported more than once
+DELETE FROM playlists
+WHERE spotify_id IS NOT NULL
+  AND rowid NOT IN (
+    SELECT MIN(rowid) FROM playlists WHERE spotify_id IS NOT NULL GROUP BY session_id, spotify_id
+  );
+DELETE FROM tracks WHERE playlist_id NOT IN (SELECT id FROM playlists);
+DELETE FROM playlist_tags WHERE playlist_id NOT IN (SELECT id FROM playlists);
+
+-- A session imports each Spotify playlist once, even when the import form is submitted twice
+CREATE UNIQUE INDEX IF NOT EXISTS idx_playlists_session_id_spotify_id ON playlists(session_id, spotify_id);
@@ -0,0 +1,20 @@
+-- Failed jobs wait in 'retrying' until they are run again, SQLite cannot alter a CHECK constraint in place
+CREATE TABLE jobs_new (
+    id BLOB PRIMARY KEY,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'processing', 'retrying', 'completed', 'failed')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at DATETIME,
+    payload TEXT NOT NULL DEFAULT '{}',
+    result TEXT,
+    error_message TEXT
+);
+
+INSERT INTO jobs_new (id, status, created_at, completed_at, payload, result, error_message)
+SELECT id, status, created_at, completed_at, payload, result, error_message FROM jobs;
+
+DROP TABLE jobs;
+ALTER TABLE jobs_new RENAME TO jobs;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status);
+CREATE INDEX IF NOT EXISTS idx_jobs_created_at ON jobs(created_at);
+CREATE INDEX IF NOT EXISTS idx_jobs_payload_playlist_id ON jobs(json_extract(payload, '$.playlist_id'));
@@ -0,0 +1,9 @@
+-- Generated PDFs are stored with the job that generated them instead of as files on disk
+CREATE TABLE IF NOT EXISTS pdfs (
+    id BLOB PRIMARY KEY,
+    job_id BLOB NOT NULL,
+    side TEXT NOT NULL CHECK (side IN ('front', 'back')),
+    data BLOB NOT NULL,
+    UNIQUE (job_id, side),
+    FOREIGN KEY (job_id) REFERENCES jobs(id) ON DELETE CASCADE
+);
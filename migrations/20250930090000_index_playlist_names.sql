@@ -0,0 +1,2 @@
+-- Speeds up searching playlists by name
+CREATE INDEX IF NOT EXISTS idx_playlists_name ON playlists(name COLLATE NOCASE);
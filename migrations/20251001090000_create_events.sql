@@ -0,0 +1,10 @@
+-- Audit trail of what happened to each playlist
+CREATE TABLE IF NOT EXISTS events (
+    id BLOB PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    playlist_id TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_playlist_id ON events(playlist_id);
@@ -0,0 +1,2 @@
+-- Add the Spotify track length, printed on the front cards
+ALTER TABLE tracks ADD COLUMN duration_ms INTEGER;
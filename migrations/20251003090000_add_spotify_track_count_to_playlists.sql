@@ -0,0 +1,2 @@
+-- Track count Spotify reported, so refetches can show how many tracks are still missing
+ALTER TABLE playlists ADD COLUMN spotify_track_count INTEGER;
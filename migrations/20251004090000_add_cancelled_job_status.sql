@@ -0,0 +1,26 @@
+-- Users can cancel jobs that have not finished, SQLite cannot alter a CHECK constraint in place
+-- Dropping jobs cascades to the PDFs generated by them, so they are set aside meanwhile
+CREATE TEMP TABLE pdfs_backup AS SELECT * FROM pdfs;
+
+CREATE TABLE jobs_new (
+    id BLOB PRIMARY KEY,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'processing', 'retrying', 'completed', 'failed', 'cancelled')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at DATETIME,
+    payload TEXT NOT NULL DEFAULT '{}',
+    result TEXT,
+    error_message TEXT
+);
+
+INSERT INTO jobs_new (id, status, created_at, completed_at, payload, result, error_message)
+SELECT id, status, created_at, completed_at, payload, result, error_message FROM jobs;
+
+DROP TABLE jobs;
+ALTER TABLE jobs_new RENAME TO jobs;
+
+INSERT INTO pdfs SELECT * FROM pdfs_backup;
+DROP TABLE pdfs_backup;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status);
+CREATE INDEX IF NOT EXISTS idx_jobs_created_at ON jobs(created_at);
+CREATE INDEX IF NOT EXISTS idx_jobs_payload_playlist_id ON jobs(json_extract(payload, '$.playlist_id'));
@@ -0,0 +1,2 @@
+-- When a stale job was last queued again, so the next check does not queue it once more
+ALTER TABLE jobs ADD COLUMN requeued_at DATETIME;
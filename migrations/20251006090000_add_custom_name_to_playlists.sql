@@ -0,0 +1,2 @@
+-- Name the user gave the playlist, shown in place of the Spotify name so refetches can update it
+ALTER TABLE playlists ADD COLUMN custom_name TEXT;
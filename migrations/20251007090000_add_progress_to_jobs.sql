@@ -0,0 +1,2 @@
+-- Progress running jobs report, as JSON with the units done and the total
+ALTER TABLE jobs ADD COLUMN progress TEXT;
@@ -0,0 +1,5 @@
+-- Spotify IDs are stored as URIs, which keep whether they are playlists, albums or tracks.
+-- Only playlists were stored before.
+UPDATE playlists
+SET spotify_id = 'spotify:playlist:' || spotify_id
+WHERE spotify_id IS NOT NULL AND spotify_id NOT LIKE 'spotify:%';
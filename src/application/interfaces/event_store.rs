@@ -0,0 +1,18 @@
+use crate::domain::DomainEvent;
+use std::future::Future;
+use tracing::warn;
+
+/// Append-only log of [`DomainEvent`]s
+pub trait IEventStore: Clone + Send + Sync + 'static {
+    fn append(&self, event: DomainEvent) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    /// Appends the event after the change it describes, a failure is logged instead of undoing the change
+    fn record(&self, event: DomainEvent) -> impl Future<Output = ()> + Send {
+        async move {
+            let event_type = event.event_type();
+            if let Err(e) = self.append(event).await {
+                warn!("Failed to record {} event: {:#}", event_type, e);
+            }
+        }
+    }
+}
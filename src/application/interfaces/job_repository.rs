@@ -1,12 +1,165 @@
-use crate::domain::{Job, JobId};
+use crate::domain::{Job, JobId, JobProgress, JobStatus, JobType, PlaylistId, SessionId};
+use chrono::{DateTime, Utc};
 use std::future::Future;
 
+/// Criteria for listing jobs, unset fields match every job
+#[derive(Debug, Clone, Default)]
+pub struct JobFilter {
+    /// Only jobs created at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only jobs created at or before this time
+    pub to: Option<DateTime<Utc>>,
+    pub status: Option<JobStatus>,
+    /// Only jobs whose payload `type` equals this discriminant
+    pub filter_by_type: Option<String>,
+    /// Only jobs enqueued by this session
+    pub session_id: Option<SessionId>,
+}
+
+/// 1-based page of a listing
+#[derive(Debug, Clone, Copy)]
+pub struct Pagination {
+    pub page: u32,
+    pub per_page: u32,
+}
+
+#[derive(Debug, displaydoc::Display, thiserror::Error)]
+/// Page {page} is out of range
+pub struct PageOutOfRangeError {
+    pub page: u32,
+}
+
+impl Pagination {
+    /// Fails for page 0 and for pages whose offset does not fit in a `u32`
+    pub fn new(page: u32, per_page: u32) -> Result<Self, PageOutOfRangeError> {
+        if page == 0 || (page - 1).checked_mul(per_page).is_none() {
+            return Err(PageOutOfRangeError { page });
+        }
+        Ok(Self { page, per_page })
+    }
+
+    pub fn offset(&self) -> u32 {
+        self.page.saturating_sub(1).saturating_mul(self.per_page)
+    }
+
+    /// The page after this one, `None` if it would be out of range
+    pub fn next(&self) -> Option<u32> {
+        let next = self.page.checked_add(1)?;
+        Self::new(next, self.per_page).ok().map(|_| next)
+    }
+}
+
+/// One page of jobs and the number of jobs matching the filter
+#[derive(Debug, Clone)]
+pub struct JobsPage {
+    pub jobs: Vec<Job>,
+    pub total: u64,
+}
+
+/// One page of jobs with the name of their playlist, `None` once it was deleted
+#[derive(Debug, Clone)]
+pub struct NamedJobsPage {
+    pub jobs: Vec<(Job, Option<String>)>,
+    pub total: u64,
+}
+
 pub trait IJobsRepository: Clone + Send + Sync + 'static {
     fn create(&self, job: Job) -> impl Future<Output = anyhow::Result<Job>> + Send;
     fn get(&self, id: &JobId) -> impl Future<Output = anyhow::Result<Option<Job>>> + Send;
     fn update(&self, job: Job) -> impl Future<Output = anyhow::Result<Job>> + Send;
+    /// Stores `job` only while its stored status is one of `expected`, returning whether
+    /// it did.
+    ///
+    /// A job cancelled in the meantime keeps its status, so its outcome is discarded.
+    fn update_if_status(
+        &self,
+        job: Job,
+        expected: &[JobStatus],
+    ) -> impl Future<Output = anyhow::Result<bool>> + Send;
+    /// Marks the job as cancelled unless it already finished, returning whether it did
+    fn cancel(&self, job_id: &JobId) -> impl Future<Output = anyhow::Result<bool>> + Send;
     fn get_by_playlist_id(
         &self,
         playlist_id: &crate::domain::PlaylistId,
     ) -> impl Future<Output = anyhow::Result<Vec<Job>>> + Send;
+    /// Jobs of the playlist created at or after `since`, newest first
+    fn get_by_playlist_id_since(
+        &self,
+        playlist_id: &crate::domain::PlaylistId,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = anyhow::Result<Vec<Job>>> + Send;
+    /// Most recently created job of `job_type` for the playlist
+    fn get_latest_by_type(
+        &self,
+        playlist_id: &PlaylistId,
+        job_type: JobType,
+    ) -> impl Future<Output = anyhow::Result<Option<Job>>> + Send;
+    /// Newest jobs first
+    fn list(
+        &self,
+        filter: &JobFilter,
+        pagination: Pagination,
+    ) -> impl Future<Output = anyhow::Result<JobsPage>> + Send;
+    /// Like [`Self::list`], loading the playlist names in the same query
+    fn list_with_playlist_names(
+        &self,
+        filter: &JobFilter,
+        pagination: Pagination,
+    ) -> impl Future<Output = anyhow::Result<NamedJobsPage>> + Send;
+    /// Number of jobs waiting for a worker
+    fn get_pending_count(&self) -> impl Future<Output = anyhow::Result<u64>> + Send;
+    /// Number of jobs with `status`
+    fn count_by_status(
+        &self,
+        status: &JobStatus,
+    ) -> impl Future<Output = anyhow::Result<u64>> + Send;
+    /// Fails unless the database answers a trivial query
+    fn ping(&self) -> impl Future<Output = anyhow::Result<()>> + Send;
+    /// Pending jobs of `job_type`, oldest first
+    fn get_pending_by_type(
+        &self,
+        job_type: JobType,
+    ) -> impl Future<Output = anyhow::Result<Vec<Job>>> + Send;
+    /// Marks jobs a previous run left processing or waiting for a retry as pending,
+    /// returning how many were reset
+    fn reset_processing(&self) -> impl Future<Output = anyhow::Result<u64>> + Send;
+    /// Deletes jobs completed before `cutoff` unless their status is in `keep_statuses`,
+    /// returning how many were deleted
+    fn delete_older_than(
+        &self,
+        cutoff: DateTime<Utc>,
+        keep_statuses: &[JobStatus],
+    ) -> impl Future<Output = anyhow::Result<u64>> + Send;
+    /// Records that the job is queued again if it is still pending or processing and was
+    /// neither enqueued nor queued again since `stale_before`.
+    ///
+    /// Only one caller can claim a job this way, the others get `false`.
+    fn claim_stale(
+        &self,
+        job_id: &JobId,
+        stale_before: DateTime<Utc>,
+    ) -> impl Future<Output = anyhow::Result<bool>> + Send;
+    /// Stores how far the running job got, see [`Job::progress`]
+    fn set_progress(
+        &self,
+        job_id: &JobId,
+        progress: JobProgress,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pagination_rejects_pages_out_of_range() {
+        assert!(Pagination::new(0, 20).is_err());
+        assert!(Pagination::new(u32::MAX, 20).is_err());
+
+        let last = u32::MAX / 20 + 1;
+        let pagination = Pagination::new(last, 20).unwrap();
+        assert_eq!(pagination.offset(), (last - 1) * 20);
+        assert_eq!(pagination.next(), None);
+        assert_eq!(Pagination::new(1, 20).unwrap().next(), Some(2));
+    }
 }
@@ -1,7 +1,9 @@
+pub mod event_store;
 pub mod job_repository;
 pub mod playlist_repository;
 pub mod spotify_client;
 
+pub use event_store::*;
 pub use job_repository::*;
 pub use playlist_repository::*;
 pub use spotify_client::*;
@@ -1,17 +1,123 @@
-use crate::domain::{Job, Playlist, PlaylistId, SpotifyId};
+use crate::application::Pagination;
+use crate::domain::{Job, JobId, Pdf, Playlist, PlaylistId, SessionId, SpotifyId, Tag};
 use std::future::Future;
 
+/// One page of playlists and the number of playlists matching the query
+#[derive(Debug, Clone)]
+pub struct PlaylistsPage {
+    pub playlists: Vec<Playlist>,
+    pub total: u64,
+}
+
+/// Timestamp playlist listings are sorted by, newest first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlaylistOrder {
+    #[default]
+    Created,
+    /// Playlists that were never updated count as updated when they were created
+    Updated,
+}
+
+/// Playlists are stored per session, lookups only see playlists of the given session
 pub trait IPlaylistRepository: Clone + Send + Sync + 'static {
-    fn create(&self, playlist: &Playlist) -> impl Future<Output = anyhow::Result<Playlist>> + Send;
-    fn get(&self, id: &PlaylistId)
-    -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    /// Returns the stored playlist instead when the session already has one with the same Spotify ID
+    fn create(
+        &self,
+        session_id: &SessionId,
+        playlist: &Playlist,
+    ) -> impl Future<Output = anyhow::Result<Playlist>> + Send;
+    /// Like [`Self::create`] in a single statement, with `true` when the playlist was stored
+    /// and `false` when the session's existing copy of the Spotify playlist was returned
+    fn upsert(
+        &self,
+        session_id: &SessionId,
+        playlist: &Playlist,
+    ) -> impl Future<Output = anyhow::Result<(Playlist, bool)>> + Send;
+    fn get(
+        &self,
+        session_id: &SessionId,
+        id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
     fn get_by_spotify_id(
         &self,
+        session_id: &SessionId,
         spotify_id: &SpotifyId,
     ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    fn list(
+        &self,
+        session_id: &SessionId,
+        pagination: Pagination,
+        order: PlaylistOrder,
+    ) -> impl Future<Output = anyhow::Result<PlaylistsPage>> + Send;
+    /// Up to `limit` playlists created before the playlist `after`, newest first.
+    ///
+    /// Unlike page numbers, the cursor keeps its place when playlists are created in
+    /// the meantime. No playlists are returned when `after` is not in the session.
+    fn list_after(
+        &self,
+        session_id: &SessionId,
+        after: Option<&PlaylistId>,
+        limit: u32,
+    ) -> impl Future<Output = anyhow::Result<Vec<Playlist>>> + Send;
+    /// Playlists whose name contains `query`, ignoring ASCII case, newest first
+    fn search_by_name(
+        &self,
+        session_id: &SessionId,
+        query: &str,
+    ) -> impl Future<Output = anyhow::Result<Vec<Playlist>>> + Send;
+    /// Deletes the playlist with its tracks, tags and jobs, `false` if the session has no such playlist
+    fn delete(
+        &self,
+        session_id: &SessionId,
+        id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<bool>> + Send;
     fn get_jobs(
         &self,
         playlist_id: &PlaylistId,
     ) -> impl Future<Output = anyhow::Result<Option<Vec<Job>>>> + Send;
+    /// Playlists with jobs still pending or processing that were enqueued, or last queued again,
+    /// more than `older_than` ago
+    fn get_playlists_with_stale_jobs(
+        &self,
+        older_than: chrono::Duration,
+    ) -> impl Future<Output = anyhow::Result<Vec<PlaylistId>>> + Send;
+    /// Stores the front and back PDFs generated by the job
+    fn save_pdfs(
+        &self,
+        job_id: &JobId,
+        pdfs: &[Pdf; 2],
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+    /// Front and back PDFs generated by the job, `None` if it stored none
+    fn get_pdfs(
+        &self,
+        job_id: &JobId,
+    ) -> impl Future<Output = anyhow::Result<Option<[Pdf; 2]>>> + Send;
+    /// Replaces the stored playlist and its tracks, stamping `updated_at` with the current time
     fn update(&self, playlist: &Playlist) -> impl Future<Output = anyhow::Result<Playlist>> + Send;
+    fn add_tag(
+        &self,
+        playlist_id: &PlaylistId,
+        tag: &Tag,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+    fn remove_tag(
+        &self,
+        playlist_id: &PlaylistId,
+        tag: &Tag,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+    fn get_tags(
+        &self,
+        session_id: &SessionId,
+        playlist_id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<Vec<Tag>>> + Send;
+    /// Newest playlists tagged with `tag` first
+    fn list_by_tag(
+        &self,
+        session_id: &SessionId,
+        tag: &Tag,
+        pagination: Pagination,
+    ) -> impl Future<Output = anyhow::Result<PlaylistsPage>> + Send;
+    /// Number of playlists stored across all sessions
+    fn count_playlists(&self) -> impl Future<Output = anyhow::Result<u64>> + Send;
+    /// Number of tracks stored across all playlists and sessions
+    fn count_tracks(&self) -> impl Future<Output = anyhow::Result<u64>> + Send;
 }
@@ -1,13 +1,115 @@
-use crate::domain::{Playlist, SpotifyId};
+use crate::domain::{JobProgress, Playlist, SpotifyId, Track};
+use std::collections::HashMap;
 use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of tracks Spotify returns from a single batch tracks request
+pub const MAX_TRACKS_PER_REQUEST: usize = 50;
+
+/// A playlist fetched from Spotify together with what was left out of it
+#[derive(Debug, Clone)]
+pub struct PlaylistFetchResult {
+    pub playlist: Playlist,
+    /// Number of podcast episodes skipped because cards can only be made for music tracks
+    pub skipped_episodes: u32,
+}
+
+/// What is known about a Spotify playlist before its tracks are fetched
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistMetadata {
+    pub name: String,
+    pub track_count: u32,
+}
+
+/// A playlist found by [`ISpotifyClient::search_playlists`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpotifyPlaylistSummary {
+    pub id: SpotifyId,
+    pub name: String,
+    /// Display name of the owner, their user ID if they have none
+    pub owner: String,
+    pub track_count: u32,
+    pub image_url: Option<String>,
+}
+
+/// Tracks fetched so far while a playlist is fetched, shared between clones so it can be
+/// read while the fetch runs
+#[derive(Debug, Clone, Default)]
+pub struct FetchProgress(Arc<Mutex<Option<JobProgress>>>);
+
+impl FetchProgress {
+    /// Starts counting towards the `total` tracks Spotify reported
+    pub fn start(&self, total: u32) {
+        *self.0.lock().unwrap() = Some(JobProgress { done: 0, total });
+    }
+
+    /// Counts `tracks` more tracks as fetched
+    pub fn add(&self, tracks: u32) {
+        if let Some(progress) = self.0.lock().unwrap().as_mut() {
+            progress.done = progress.done.saturating_add(tracks);
+        }
+    }
+
+    /// Progress so far, `None` until the total is known
+    pub fn get(&self) -> Option<JobProgress> {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[derive(Debug, displaydoc::Display, thiserror::Error)]
+/// Playlist fetch failed after {fetched_tracks} tracks
+pub struct PartialFetchError {
+    pub fetched_tracks: usize,
+}
+
+/// Spotify failures callers handle differently, attached as context to the errors of
+/// [`ISpotifyClient`] so they can be found with `downcast_ref`
+#[derive(Debug, displaydoc::Display, thiserror::Error, PartialEq)]
+pub enum SpotifyError {
+    /// Spotify is rate limiting requests, try again later
+    RateLimited {
+        /// Seconds Spotify asked to wait in its last response, if it said
+        retry_after_seconds: Option<u64>,
+    },
+    /// Spotify rejected the client credentials
+    Unauthorized,
+}
 
 pub trait ISpotifyClient: Clone + Send + Sync + 'static {
-    fn get_playlist(
+    /// Name and track count of the playlist, requested without any of its tracks
+    fn get_playlist_metadata(
         &self,
         id: &SpotifyId,
-    ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    ) -> impl Future<Output = anyhow::Result<Option<PlaylistMetadata>>> + Send;
+    /// Number of tracks in the playlist, read from its metadata without fetching the tracks
+    fn get_playlist_track_count(
+        &self,
+        id: &SpotifyId,
+    ) -> impl Future<Output = anyhow::Result<u32>> + Send;
+    /// Fetches the playlist with all of its tracks, counting them in `progress` as they arrive
     fn get_playlist_with_tracks(
         &self,
         id: &SpotifyId,
+        progress: &FetchProgress,
+    ) -> impl Future<Output = anyhow::Result<Option<PlaylistFetchResult>>> + Send;
+    /// Fetches an album as a playlist named after the album, with one track per album track
+    fn get_album_tracks(
+        &self,
+        album_id: &SpotifyId,
     ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    /// Fetches fresh metadata for the given Spotify track URLs, keyed by the requested URL
+    fn refresh_track_metadata(
+        &self,
+        spotify_urls: Vec<String>,
+    ) -> impl Future<Output = anyhow::Result<HashMap<String, Track>>> + Send;
+    /// Requests a new access token before the current one expires
+    fn refresh_token(&self) -> impl Future<Output = anyhow::Result<()>> + Send;
+    /// Up to `limit` playlists matching `query`, best match first
+    fn search_playlists(
+        &self,
+        query: &str,
+        limit: u32,
+    ) -> impl Future<Output = anyhow::Result<Vec<SpotifyPlaylistSummary>>> + Send;
+    /// Whether the client holds an access token, an expired one is renewed by the next request
+    fn has_token(&self) -> impl Future<Output = bool> + Send;
 }
@@ -6,5 +6,8 @@ pub mod playlist_service;
 pub mod worker;
 
 pub use interfaces::*;
-pub use pdf_generator::{IPdfGenerator, PdfGenerator};
+pub use pdf_generator::{
+    CardBorderStyle, CardFonts, CardLayout, IPdfGenerator, PdfGenerator, PdfGeneratorConfig,
+    PdfGeneratorOptions, QrCodeFormat,
+};
 pub use playlist_service::PlaylistService;
@@ -1,23 +1,410 @@
 use crate::domain::Playlist;
-use anyhow::Result;
-use oxidize_pdf::{Color, Document, Font, Page};
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use oxidize_pdf::graphics::LineDashPattern;
+use oxidize_pdf::{Color, Document, Font, Image, Page};
 use rayon::iter::IntoParallelRefIterator;
 use rayon::prelude::*;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::future::Future;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tracing::warn;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Width of the A4 pages cards are printed on, in points
+const PAGE_WIDTH: f64 = 595.0;
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+
+/// Unicode font artist names, titles and notes are printed in, as Helvetica only covers
+/// Latin-1. DejaVu Sans covers Cyrillic, Greek, Arabic and Hebrew but has no CJK glyphs,
+/// those are printed in the fallback font of [`CardFonts`].
+const CARD_FONT: &str = "DejaVuSans";
+const CARD_FONT_DATA: &[u8] = include_bytes!("../../../assets/fonts/DejaVuSans.ttf");
+/// Name the fallback font of [`CardFonts`] is embedded under
+const FALLBACK_FONT: &str = "CardFallback";
+
+/// Glyph widths of [`CARD_FONT`] for wrapping text
+static CARD_FONT_METRICS: LazyLock<oxidize_pdf::fonts::Font> = LazyLock::new(|| {
+    oxidize_pdf::fonts::Font::from_bytes(CARD_FONT, CARD_FONT_DATA.to_vec())
+        .expect("bundled card font is a valid TrueType font")
+});
+
+/// Fonts card text is printed in: [`CARD_FONT`], and optionally a TrueType font for the
+/// characters it has no glyphs for, such as Chinese, Japanese and Korean.
+///
+/// Without a fallback font such characters are printed as empty boxes.
+#[derive(Clone, Default)]
+pub struct CardFonts {
+    fallback: Option<Arc<FallbackFont>>,
+}
+
+struct FallbackFont {
+    data: Vec<u8>,
+    metrics: oxidize_pdf::fonts::Font,
+}
+
+impl std::fmt::Debug for CardFonts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CardFonts")
+            .field("has_fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl CardFonts {
+    /// Prints the characters [`CARD_FONT`] lacks in the TrueType font `data`
+    pub fn with_fallback(data: Vec<u8>) -> Result<Self> {
+        let metrics = oxidize_pdf::fonts::Font::from_bytes(FALLBACK_FONT, data.clone())?;
+        Ok(Self {
+            fallback: Some(Arc::new(FallbackFont { data, metrics })),
+        })
+    }
+
+    /// Prints the characters [`CARD_FONT`] lacks in the TrueType font at `path`
+    pub fn load_fallback(path: &Path) -> Result<Self> {
+        std::fs::read(path)
+            .map_err(anyhow::Error::from)
+            .and_then(Self::with_fallback)
+            .with_context(|| format!("while loading fallback font {}", path.display()))
+    }
+
+    /// Embeds the fonts in `doc`, so that pages of it can draw with them
+    fn add_to(&self, doc: &mut Document) -> Result<()> {
+        doc.add_font_from_bytes(CARD_FONT, CARD_FONT_DATA.to_vec())?;
+        if let Some(fallback) = &self.fallback {
+            doc.add_font_from_bytes(FALLBACK_FONT, fallback.data.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Name and metrics of the font `grapheme` is printed in, the fallback font only when
+    /// it has every glyph [`CARD_FONT`] is missing
+    fn font_for(&self, grapheme: &str) -> (&'static str, &oxidize_pdf::fonts::Font) {
+        match &self.fallback {
+            Some(fallback)
+                if grapheme.chars().any(|c| !CARD_FONT_METRICS.has_glyph(c))
+                    && grapheme.chars().all(|c| fallback.metrics.has_glyph(c)) =>
+            {
+                (FALLBACK_FONT, &fallback.metrics)
+            }
+            _ => (CARD_FONT, &CARD_FONT_METRICS),
+        }
+    }
+
+    /// Splits `text` into runs printed in the same font, with that font's name and metrics
+    fn runs<'a>(
+        &'a self,
+        text: &'a str,
+    ) -> Vec<(&'static str, &'a oxidize_pdf::fonts::Font, &'a str)> {
+        let mut runs: Vec<(&'static str, &oxidize_pdf::fonts::Font, usize, usize)> = Vec::new();
+        for (index, grapheme) in text.grapheme_indices(true) {
+            let (name, metrics) = self.font_for(grapheme);
+            let end = index + grapheme.len();
+            match runs.last_mut() {
+                Some((run_font, _, _, run_end)) if *run_font == name => *run_end = end,
+                _ => runs.push((name, metrics, index, end)),
+            }
+        }
+        runs.into_iter()
+            .map(|(name, metrics, start, end)| (name, metrics, &text[start..end]))
+            .collect()
+    }
+
+    /// Width of `text` in points when printed in these fonts at `font_size`
+    fn text_width(&self, text: &str, font_size: f64) -> f64 {
+        self.runs(text)
+            .into_iter()
+            .map(|(_, metrics, run)| f64::from(metrics.measure_text(run, font_size as f32).width))
+            .sum()
+    }
+
+    /// Draws `text` at `x`, `y`, switching fonts wherever [`CARD_FONT`] lacks glyphs
+    fn draw(&self, page: &mut Page, text: &str, font_size: f64, x: f64, y: f64) -> Result<()> {
+        let mut x = x;
+        for (name, metrics, run) in self.runs(text) {
+            page.graphics()
+                .set_custom_font(name, font_size)
+                .draw_text(run, x, y)?;
+            x += f64::from(metrics.measure_text(run, font_size as f32).width);
+        }
+        Ok(())
+    }
+}
+
+/// Font size and reserved height for the text below the QR code on card backs
+const BACK_TEXT_SIZE: f64 = 8.0;
+const BACK_TEXT_AREA: f64 = 16.0;
+/// Height added to the reserved area for every further line of wrapped back text
+const BACK_TEXT_LINE_HEIGHT: f64 = 10.0;
+
+/// Side length of the album cover in the top right corner of card fronts
+const ALBUM_COVER_SIZE: f64 = 48.0;
+/// Album covers are scaled down to at most this many pixels per side before embedding
+const ALBUM_COVER_PIXELS: u32 = 200;
+const ALBUM_COVER_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of album covers downloaded at the same time
+const ALBUM_COVER_DOWNLOADS: usize = 8;
+/// Bytes of downloaded album covers kept for later generations, enough for the covers of
+/// the largest playlists while bounding the memory of a long running server
+const ALBUM_COVER_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Side length of the album cover thumbnail in the top left corner of card backs, 15 mm
+const ALBUM_THUMBNAIL_SIZE: f64 = 15.0 / 25.4 * 72.0;
+
+/// Pixels per side of QR codes rendered as PNG images
+const QR_CODE_PIXELS: u32 = 200;
+/// Empty modules around the QR code that scanners need to find it, as in the SVG render
+const QR_CODE_QUIET_ZONE: usize = 4;
+
+/// Font size and line height of the track notes on card fronts
+const NOTES_TEXT_SIZE: f64 = 6.0;
+const NOTES_LINE_HEIGHT: f64 = 7.5;
+/// Size of the track length below the year
+const DURATION_TEXT_SIZE: f64 = 8.0;
 
 pub trait IPdfGenerator: Clone + Send + Sync + 'static {
     fn generate_front_cards(
         &self,
         playlist: &Playlist,
+        config: &PdfGeneratorConfig,
     ) -> impl Future<Output = anyhow::Result<Vec<u8>>> + Send;
     fn generate_back_cards(
         &self,
         playlist: &Playlist,
+        config: &PdfGeneratorConfig,
     ) -> impl Future<Output = anyhow::Result<Vec<u8>>> + Send;
 }
 
+/// Card geometry and font sizes, passed with every generation so callers can tune them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdfGeneratorConfig {
+    /// Space between the card border and the text on card fronts
+    pub card_margin_mm: f64,
+    pub artist_font_size: f64,
+    pub title_font_size: f64,
+    pub year_font_size: f64,
+    /// Grid of cards printed on each page
+    pub layout: CardLayout,
+    /// Space between the edges of a card back and the QR code or thumbnail
+    pub qr_padding_mm: f64,
+}
+
+impl Default for PdfGeneratorConfig {
+    fn default() -> Self {
+        Self {
+            card_margin_mm: 18.0 / POINTS_PER_MM,
+            artist_font_size: 16.0,
+            title_font_size: 12.0,
+            year_font_size: 32.0,
+            layout: CardLayout::default(),
+            qr_padding_mm: 5.0 / POINTS_PER_MM,
+        }
+    }
+}
+
+impl PdfGeneratorConfig {
+    fn card_margin(&self) -> f64 {
+        self.card_margin_mm * POINTS_PER_MM
+    }
+
+    fn qr_padding(&self) -> f64 {
+        self.qr_padding_mm * POINTS_PER_MM
+    }
+
+    /// Width available to a line of text on card fronts
+    fn text_width(&self) -> f64 {
+        PAGE_WIDTH / self.layout.columns() as f64 - 2.0 * self.card_margin()
+    }
+}
+
+/// Grid of cards printed on each page, named columns by rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum CardLayout {
+    /// 12 cards per page. Pages used to be cut every 24 cards, which left half of
+    /// them off the printed sheet, so a playlist now takes twice as many pages.
+    #[default]
+    #[serde(rename = "3x4")]
+    ThreeByFour,
+    #[serde(rename = "2x3")]
+    TwoByThree,
+    #[serde(rename = "4x6")]
+    FourBySix,
+}
+
+impl CardLayout {
+    pub const fn columns(self) -> usize {
+        match self {
+            CardLayout::ThreeByFour => 3,
+            CardLayout::TwoByThree => 2,
+            CardLayout::FourBySix => 4,
+        }
+    }
+
+    pub const fn rows(self) -> usize {
+        match self {
+            CardLayout::ThreeByFour => 4,
+            CardLayout::TwoByThree => 3,
+            CardLayout::FourBySix => 6,
+        }
+    }
+
+    /// Number of cards that fit on a single page
+    pub const fn cards_per_page(self) -> usize {
+        self.columns() * self.rows()
+    }
+}
+
+impl std::fmt::Display for CardLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.columns(), self.rows())
+    }
+}
+
+/// Unsupported card layout '{0}', expected 3x4, 2x3 or 4x6
+#[derive(Debug, displaydoc::Display, thiserror::Error)]
+pub struct UnsupportedCardLayout(String);
+
+impl FromStr for CardLayout {
+    type Err = UnsupportedCardLayout;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            CardLayout::ThreeByFour,
+            CardLayout::TwoByThree,
+            CardLayout::FourBySix,
+        ]
+        .into_iter()
+        .find(|layout| layout.to_string() == s)
+        .ok_or_else(|| UnsupportedCardLayout(s.to_string()))
+    }
+}
+
+/// Border drawn around each card, used as a cutting guide
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CardBorderStyle {
+    #[default]
+    Solid,
+    Dashed {
+        dash_length: f64,
+        gap: f64,
+    },
+    Dotted,
+    None,
+}
+
+impl CardBorderStyle {
+    /// Dashes of 6pt with 3pt gaps
+    pub const DASHED: Self = CardBorderStyle::Dashed {
+        dash_length: 6.0,
+        gap: 3.0,
+    };
+}
+
+/// How QR codes are embedded on card backs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QrCodeFormat {
+    /// Raster image of `size` pixels per side, pixelates when printed at high DPI
+    Png { size: u32 },
+    /// Vector graphics matching `qrcode::render::svg`, sharp at any print resolution.
+    ///
+    /// The PDF library cannot embed SVG images, so the modules are drawn as PDF paths.
+    Svg,
+}
+
+impl Default for QrCodeFormat {
+    fn default() -> Self {
+        QrCodeFormat::Png {
+            size: QR_CODE_PIXELS,
+        }
+    }
+}
+
+/// Options controlling how cards are rendered
+#[derive(Debug, Clone, Default)]
+pub struct PdfGeneratorOptions {
+    pub card_border: CardBorderStyle,
+    pub qr_code_format: QrCodeFormat,
+    /// Print track notes in small text at the bottom of the card fronts
+    pub show_notes: bool,
+    pub fonts: CardFonts,
+}
+
+/// Renders card PDFs, downloading album covers as needed.
+///
+/// Cloning is cheap: the HTTP client shares its connection pool between clones, and so
+/// does the album cover cache, so every worker can hold its own clone.
 #[derive(Clone)]
-pub struct PdfGenerator;
+pub struct PdfGenerator {
+    options: PdfGeneratorOptions,
+    http: reqwest::Client,
+    /// Downloaded album covers, shared between clones
+    album_covers: Arc<Mutex<AlbumCoverCache>>,
+}
+
+/// Downloaded album covers by URL, evicting the least recently used ones once they take
+/// up more than the capacity in bytes
+#[derive(Debug)]
+struct AlbumCoverCache {
+    capacity: usize,
+    size: usize,
+    covers: HashMap<String, Bytes>,
+    /// URLs of the cached covers, least recently used first
+    recency: VecDeque<String>,
+}
+
+impl Default for AlbumCoverCache {
+    fn default() -> Self {
+        Self::new(ALBUM_COVER_CACHE_BYTES)
+    }
+}
+
+impl AlbumCoverCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            size: 0,
+            covers: HashMap::new(),
+            recency: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, url: &str) -> Option<Bytes> {
+        let bytes = self.covers.get(url)?.clone();
+        self.touch(url);
+        Some(bytes)
+    }
+
+    fn insert(&mut self, url: String, bytes: Bytes) {
+        if let Some(replaced) = self.covers.insert(url.clone(), bytes.clone()) {
+            self.size -= replaced.len();
+            self.touch(&url);
+        } else {
+            self.recency.push_back(url);
+        }
+        self.size += bytes.len();
+
+        while self.size > self.capacity
+            && let Some(oldest) = self.recency.pop_front()
+        {
+            if let Some(evicted) = self.covers.remove(&oldest) {
+                self.size -= evicted.len();
+            }
+        }
+    }
+
+    /// Marks `url` as the most recently used cover
+    fn touch(&mut self, url: &str) {
+        if let Some(index) = self.recency.iter().position(|cached| cached == url) {
+            let url = self.recency.remove(index).expect("index is in bounds");
+            self.recency.push_back(url);
+        }
+    }
+}
 
 impl Default for PdfGenerator {
     fn default() -> Self {
@@ -27,28 +414,93 @@ impl Default for PdfGenerator {
 
 impl PdfGenerator {
     pub fn new() -> Self {
-        Self
+        Self::with_options(PdfGeneratorOptions::default())
+    }
+
+    pub fn with_options(options: PdfGeneratorOptions) -> Self {
+        Self {
+            options,
+            http: reqwest::Client::new(),
+            album_covers: Arc::default(),
+        }
+    }
+
+    /// Album covers of the tracks by URL, leaving out covers that cannot be downloaded or decoded
+    async fn album_covers(&self, playlist: &Playlist) -> HashMap<String, Image> {
+        let urls: HashSet<String> = playlist
+            .tracks
+            .iter()
+            .filter_map(|track| track.album_cover_url.clone())
+            .collect();
+
+        futures_util::stream::iter(urls)
+            .map(|url| async move {
+                let cover = match self.album_cover(&url).await {
+                    Ok(bytes) => decode_album_cover(&bytes),
+                    Err(err) => Err(err),
+                };
+                match cover {
+                    Ok(image) => Some((url, image)),
+                    Err(err) => {
+                        warn!("Skipping album cover {}: {:#}", url, err);
+                        None
+                    }
+                }
+            })
+            .buffer_unordered(ALBUM_COVER_DOWNLOADS)
+            .filter_map(std::future::ready)
+            .collect()
+            .await
+    }
+
+    /// Downloads the album cover at `url` unless it is already cached
+    async fn album_cover(&self, url: &str) -> Result<Bytes> {
+        let cached = self.album_covers.lock().unwrap().get(url);
+        if let Some(bytes) = cached {
+            return Ok(bytes);
+        }
+
+        let bytes = self
+            .http
+            .get(url)
+            .timeout(ALBUM_COVER_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        self.album_covers
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), bytes.clone());
+        Ok(bytes)
     }
 }
 
 impl IPdfGenerator for PdfGenerator {
-    async fn generate_front_cards(&self, playlist: &Playlist) -> Result<Vec<u8>> {
+    async fn generate_front_cards(
+        &self,
+        playlist: &Playlist,
+        config: &PdfGeneratorConfig,
+    ) -> Result<Vec<u8>> {
+        let layout = config.layout;
         let mut doc = Document::new();
-        doc.set_title(format!("{} - Front", playlist.name));
+        doc.set_title(format!("{} - Front", playlist.display_name()));
+        let fonts = &self.options.fonts;
+        fonts.add_to(&mut doc)?;
+        let album_covers = self.album_covers(playlist).await;
 
-        // 4x6 grid = 24 cards per page
-        let pages: Vec<_> = playlist
+        let pages = playlist
             .tracks
-            .par_chunks(24)
+            .par_chunks(layout.cards_per_page())
             .map(|tracks_on_page| {
                 let mut page = Page::a4();
 
                 let page_width = page.width();
                 let page_height = page.height();
 
-                // 3 columns, 4 rows
-                let cols = 3;
-                let rows = 4;
+                let cols = layout.columns();
+                let rows = layout.rows();
 
                 let card_width = page_width / cols as f64;
                 let card_height = page_height / rows as f64;
@@ -60,46 +512,60 @@ impl IPdfGenerator for PdfGenerator {
                     let pos_x = col as f64 * card_width;
                     let pos_y = page.height() - row as f64 * card_height;
 
-                    // Draw rectangle border
-                    page.graphics()
-                        .set_stroke_color(Color::black())
-                        .rectangle(pos_x, pos_y, card_width, card_height)
-                        .stroke();
+                    draw_card_border(
+                        &mut page,
+                        &self.options.card_border,
+                        pos_x,
+                        pos_y,
+                        card_width,
+                        card_height,
+                    );
+
+                    // Album cover in the top right corner, the text wraps before reaching it
+                    let album_cover = track
+                        .album_cover_url
+                        .as_ref()
+                        .and_then(|url| Some((url, album_covers.get(url)?)));
+                    let padding = config.card_margin();
+                    let text_width = match album_cover {
+                        Some((url, image)) => {
+                            page.add_image(url, image.clone());
+                            page.draw_image(
+                                url,
+                                pos_x + card_width - padding - ALBUM_COVER_SIZE,
+                                pos_y + card_height - padding - ALBUM_COVER_SIZE,
+                                ALBUM_COVER_SIZE,
+                                ALBUM_COVER_SIZE,
+                            )?;
+                            config.text_width() - ALBUM_COVER_SIZE - padding / 2.0
+                        }
+                        None => config.text_width(),
+                    };
 
                     // Add text content
-                    let padding = 18.0;
                     let line_height = 16.0;
                     // Gap between artist and title
                     let gap = 4.0;
 
                     // Handle artist name with smart wrapping
-                    let max_artist_chars = 20; // Approximate character limit for artist lines
-                    let artist_lines = wrap_text(&track.artist, max_artist_chars);
                     let mut current_line = 0;
 
-                    for (idx, artist_line) in artist_lines.iter().enumerate() {
-                        let artist_string = if idx == artist_lines.len() - 1 {
-                            artist_line.clone()
-                        } else {
-                            // Add comma for all but the last line when we split by commas
-                            if track.artist.contains(',') && idx < artist_lines.len() - 1 {
-                                format!("{},", artist_line)
-                            } else {
-                                artist_line.clone()
-                            }
-                        };
-
-                        let _ = page
-                            .text()
-                            .set_font(Font::Helvetica, 16.0)
-                            .at(
-                                pos_x + padding,
-                                pos_y + card_height
-                                    - line_height
-                                    - padding
-                                    - (current_line as f64 * line_height),
-                            )
-                            .write(&artist_string);
+                    for artist_string in artist_lines_within(
+                        fonts,
+                        &track.artist,
+                        config.artist_font_size,
+                        text_width,
+                    ) {
+                        fonts.draw(
+                            &mut page,
+                            &artist_string,
+                            config.artist_font_size,
+                            pos_x + padding,
+                            pos_y + card_height
+                                - line_height
+                                - padding
+                                - (current_line as f64 * line_height),
+                        )?;
                         current_line += 1;
                     }
 
@@ -107,35 +573,63 @@ impl IPdfGenerator for PdfGenerator {
                     current_line += 1;
 
                     // Handle title with smart wrapping
-                    let max_title_chars = 30; // Approximate character limit for title lines
-                    let title_lines = wrap_text(&track.title, max_title_chars);
-
-                    for title_line in title_lines.iter() {
-                        let _ = page
-                            .text()
-                            .set_font(Font::Helvetica, 12.0)
-                            .at(
-                                pos_x + padding,
-                                pos_y + card_height
-                                    - padding
-                                    - gap
-                                    - (current_line as f64 * line_height),
-                            )
-                            .write(title_line);
+                    for title_line in
+                        title_lines_within(fonts, &track.title, config.title_font_size, text_width)
+                    {
+                        fonts.draw(
+                            &mut page,
+                            &title_line,
+                            config.title_font_size,
+                            pos_x + padding,
+                            pos_y + card_height
+                                - padding
+                                - gap
+                                - (current_line as f64 * line_height),
+                        )?;
                         current_line += 1;
                     }
 
+                    // Notes at the very bottom, pushing the year up
+                    let notes = match &track.notes {
+                        Some(notes) if self.options.show_notes => {
+                            wrap_text_at_width(fonts, notes, NOTES_TEXT_SIZE, config.text_width())
+                        }
+                        _ => Vec::new(),
+                    };
+                    for (index, notes_line) in notes.iter().rev().enumerate() {
+                        fonts.draw(
+                            &mut page,
+                            notes_line,
+                            NOTES_TEXT_SIZE,
+                            pos_x + padding,
+                            pos_y + padding / 2.0 + index as f64 * NOTES_LINE_HEIGHT,
+                        )?;
+                    }
+                    let notes_height = notes.len() as f64 * NOTES_LINE_HEIGHT;
+
                     // Year at bottom
                     let _ = page
                         .text()
-                        .set_font(Font::Helvetica, 32.0)
-                        .at(pos_x + padding, pos_y + line_height + padding)
+                        .set_font(Font::Helvetica, config.year_font_size)
+                        .at(
+                            pos_x + padding,
+                            pos_y + line_height + padding + notes_height,
+                        )
                         .write(&track.year.to_string());
+                    if let Some(duration) = track.formatted_duration() {
+                        fonts.draw(
+                            &mut page,
+                            &duration,
+                            DURATION_TEXT_SIZE,
+                            pos_x + padding,
+                            pos_y + padding / 2.0 + notes_height + 2.0,
+                        )?;
+                    }
                 }
 
-                page
+                Ok::<Page, anyhow::Error>(page)
             })
-            .collect();
+            .collect::<Result<Vec<_>>>()?;
 
         for page in pages {
             doc.add_page(page);
@@ -145,35 +639,50 @@ impl IPdfGenerator for PdfGenerator {
         Ok(bytes)
     }
 
-    async fn generate_back_cards(&self, playlist: &Playlist) -> Result<Vec<u8>> {
+    async fn generate_back_cards(
+        &self,
+        playlist: &Playlist,
+        config: &PdfGeneratorConfig,
+    ) -> Result<Vec<u8>> {
+        let layout = config.layout;
+        let margin = config.qr_padding();
         let mut doc = Document::new();
-        doc.set_title(format!("{} - Back", playlist.name));
+        doc.set_title(format!("{} - Back", playlist.display_name()));
+        let fonts = &self.options.fonts;
+        fonts.add_to(&mut doc)?;
+        let back_text = playlist.card_back_text();
+        let album_covers = self.album_covers(playlist).await;
 
-        // 4x6 grid = 24 cards per page (same as front)
         let pages = playlist
             .tracks
-            .par_chunks(24)
+            .par_chunks(layout.cards_per_page())
             .map(|tracks_on_page| {
                 let mut page = Page::a4();
 
                 let page_width = page.width();
                 let page_height = page.height();
 
-                // 3 columns, 4 rows
-                let cols = 3;
-                let rows = 4;
+                let cols = layout.columns();
+                let rows = layout.rows();
 
                 let card_width = page_width / cols as f64;
                 let card_height = page_height / rows as f64;
 
+                // Long back text wraps onto more lines, pushing the QR code up
+                let back_text_lines =
+                    wrap_text_at_width(fonts, back_text, BACK_TEXT_SIZE, card_width - 2.0 * margin);
+                let back_text_area = back_text_area(back_text_lines.len());
+
                 // Pre-generate all QR codes in parallel for this page
-                let qr_images: Vec<_> = tracks_on_page
+                let qr_codes: Vec<_> = tracks_on_page
                     .par_iter()
-                    .map(|track| generate_qr_code_image(&track.spotify_url))
+                    .map(|track| {
+                        QrCodeGraphic::new(&track.spotify_url, self.options.qr_code_format)
+                    })
                     .collect::<Result<Vec<_>>>()?;
 
-                for (index, (track, qr_image)) in
-                    tracks_on_page.iter().zip(qr_images.iter()).enumerate()
+                for (index, (track, qr_code)) in
+                    tracks_on_page.iter().zip(qr_codes.iter()).enumerate()
                 {
                     let row = index / cols + 1;
                     let col = index % cols;
@@ -181,21 +690,61 @@ impl IPdfGenerator for PdfGenerator {
                     let pos_x = col as f64 * card_width;
                     let pos_y = page.height() - row as f64 * card_height;
 
-                    // Draw rectangle border
-                    page.graphics()
-                        .set_stroke_color(Color::black())
-                        .rectangle(pos_x, pos_y, card_width, card_height)
-                        .stroke();
+                    draw_card_border(
+                        &mut page,
+                        &self.options.card_border,
+                        pos_x,
+                        pos_y,
+                        card_width,
+                        card_height,
+                    );
 
-                    // Add QR code image
-                    page.add_image(&track.spotify_url, qr_image.clone());
-                    page.draw_image(
+                    // Album cover thumbnail in the top left corner to match fronts and backs
+                    let album_cover = track
+                        .album_cover_url
+                        .as_ref()
+                        .and_then(|url| Some((url, album_covers.get(url)?)));
+                    if let Some((url, image)) = album_cover {
+                        page.add_image(url, image.clone());
+                        page.draw_image(
+                            url,
+                            pos_x + margin,
+                            pos_y + card_height - margin - ALBUM_THUMBNAIL_SIZE,
+                            ALBUM_THUMBNAIL_SIZE,
+                            ALBUM_THUMBNAIL_SIZE,
+                        )?;
+                    }
+
+                    // Add QR code image, leaving room for the back text below it
+                    let (qr_offset, qr_size) = qr_code_area(
+                        card_width,
+                        card_height,
+                        margin,
+                        back_text_area,
+                        album_cover.is_some(),
+                    );
+                    qr_code.draw(
+                        &mut page,
                         &track.spotify_url,
-                        pos_x + 5.0,
-                        pos_y + 5.0,
-                        card_width - 10.0,
-                        card_height - 10.0,
+                        pos_x + qr_offset,
+                        pos_y + margin + back_text_area,
+                        qr_size,
                     )?;
+
+                    // Small centered text below the QR code, every line centered on its own
+                    for (index, line) in back_text_lines.iter().rev().enumerate() {
+                        let text_width = fonts.text_width(line, BACK_TEXT_SIZE);
+                        fonts.draw(
+                            &mut page,
+                            line,
+                            BACK_TEXT_SIZE,
+                            pos_x + (card_width - text_width).max(0.0) / 2.0,
+                            pos_y
+                                + margin
+                                + (BACK_TEXT_AREA - BACK_TEXT_SIZE) / 2.0
+                                + index as f64 * BACK_TEXT_LINE_HEIGHT,
+                        )?;
+                    }
                 }
 
                 Ok::<Page, anyhow::Error>(page)
@@ -211,11 +760,92 @@ impl IPdfGenerator for PdfGenerator {
     }
 }
 
-fn generate_qr_code_image(url: &str) -> Result<oxidize_pdf::Image> {
-    let code = qrcode::QrCode::new(url)?;
+fn draw_card_border(
+    page: &mut Page,
+    style: &CardBorderStyle,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) {
+    let dash_pattern = match *style {
+        CardBorderStyle::None => return,
+        CardBorderStyle::Solid => LineDashPattern::solid(),
+        CardBorderStyle::Dashed { dash_length, gap } => LineDashPattern::dashed(dash_length, gap),
+        CardBorderStyle::Dotted => LineDashPattern::dotted(1.0, 2.0),
+    };
+
+    page.graphics()
+        .save_state()
+        .set_stroke_color(Color::black())
+        .set_line_dash_pattern(dash_pattern)
+        .rectangle(x, y, width, height)
+        .stroke()
+        .restore_state();
+}
+
+/// Height reserved below the QR code for `lines` lines of back text
+fn back_text_area(lines: usize) -> f64 {
+    BACK_TEXT_AREA + lines.saturating_sub(1) as f64 * BACK_TEXT_LINE_HEIGHT
+}
+
+/// Horizontal offset and side length of the QR code on a card back, above the
+/// `text_area` reserved for the back text.
+///
+/// With a thumbnail the QR code is centered in the space right of it, as covering
+/// part of the code with the thumbnail would break scanning.
+fn qr_code_area(
+    card_width: f64,
+    card_height: f64,
+    margin: f64,
+    text_area: f64,
+    with_thumbnail: bool,
+) -> (f64, f64) {
+    let left = if with_thumbnail {
+        2.0 * margin + ALBUM_THUMBNAIL_SIZE
+    } else {
+        margin
+    };
+    let width = card_width - left - margin;
+    let size = width.min(card_height - 2.0 * margin - text_area);
+
+    (left + (width - size) / 2.0, size)
+}
+
+/// QR code of a track, rendered in the [`QrCodeFormat`] it is drawn in
+enum QrCodeGraphic {
+    Image(Image),
+    Vector(qrcode::QrCode),
+}
+
+impl QrCodeGraphic {
+    fn new(url: &str, format: QrCodeFormat) -> Result<Self> {
+        let code = qrcode::QrCode::new(url)?;
+        Ok(match format {
+            QrCodeFormat::Png { size } => {
+                QrCodeGraphic::Image(generate_qr_code_image(&code, size)?)
+            }
+            QrCodeFormat::Svg => QrCodeGraphic::Vector(code),
+        })
+    }
+
+    /// Draws the QR code as a square of `size` with its bottom left corner at `x`, `y`
+    fn draw(&self, page: &mut Page, name: &str, x: f64, y: f64, size: f64) -> Result<()> {
+        match self {
+            QrCodeGraphic::Image(image) => {
+                page.add_image(name, image.clone());
+                page.draw_image(name, x, y, size, size)?;
+            }
+            QrCodeGraphic::Vector(code) => draw_qr_code_modules(page, code, x, y, size),
+        }
+        Ok(())
+    }
+}
+
+fn generate_qr_code_image(code: &qrcode::QrCode, size: u32) -> Result<oxidize_pdf::Image> {
     let image = code
         .render::<image::Rgba<u8>>()
-        .min_dimensions(200, 200)
+        .min_dimensions(size, size)
         .build();
 
     let image_w = image.width();
@@ -225,7 +855,95 @@ fn generate_qr_code_image(url: &str) -> Result<oxidize_pdf::Image> {
 
     Ok(pdf_image)
 }
-fn wrap_text(text: &str, max_chars_per_line: usize) -> Vec<String> {
+
+/// Fills a square for every dark module, the same shapes `qrcode::render::svg` would draw
+fn draw_qr_code_modules(page: &mut Page, code: &qrcode::QrCode, x: f64, y: f64, size: f64) {
+    let width = code.width();
+    let module_size = size / (width + 2 * QR_CODE_QUIET_ZONE) as f64;
+
+    let graphics = page.graphics();
+    graphics.save_state().set_fill_color(Color::black());
+    for (index, color) in code.to_colors().into_iter().enumerate() {
+        if color != qrcode::Color::Dark {
+            continue;
+        }
+        let column = index % width + QR_CODE_QUIET_ZONE;
+        // Rows count down from the top while PDF coordinates count up from the bottom
+        let row = index / width + QR_CODE_QUIET_ZONE + 1;
+        graphics.rect(
+            x + column as f64 * module_size,
+            y + size - row as f64 * module_size,
+            module_size,
+            module_size,
+        );
+    }
+    graphics.fill().restore_state();
+}
+/// Decodes a downloaded album cover, scaling it down to the size it is printed at
+fn decode_album_cover(bytes: &[u8]) -> Result<Image> {
+    let cover = image::load_from_memory(bytes)?
+        .thumbnail(ALBUM_COVER_PIXELS, ALBUM_COVER_PIXELS)
+        .to_rgba8();
+    let (width, height) = cover.dimensions();
+
+    Ok(Image::from_rgba_data(cover.into_raw(), width, height)?)
+}
+
+/// Splits an artist name into the lines printed on the front of a card
+pub(crate) fn artist_lines(artist: &str, config: &PdfGeneratorConfig) -> Vec<String> {
+    artist_lines_within(
+        &CardFonts::default(),
+        artist,
+        config.artist_font_size,
+        config.text_width(),
+    )
+}
+
+fn artist_lines_within(
+    fonts: &CardFonts,
+    artist: &str,
+    font_size: f64,
+    max_width: f64,
+) -> Vec<String> {
+    let lines = wrap_text(fonts, artist, font_size, max_width);
+    let last = lines.len().saturating_sub(1);
+
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            // Add comma for all but the last line when we split by commas
+            if artist.contains(',') && idx < last {
+                format!("{},", line)
+            } else {
+                line
+            }
+        })
+        .collect()
+}
+
+/// Splits a track title into the lines printed on the front of a card
+pub(crate) fn title_lines(title: &str, config: &PdfGeneratorConfig) -> Vec<String> {
+    title_lines_within(
+        &CardFonts::default(),
+        title,
+        config.title_font_size,
+        config.text_width(),
+    )
+}
+
+fn title_lines_within(
+    fonts: &CardFonts,
+    title: &str,
+    font_size: f64,
+    max_width: f64,
+) -> Vec<String> {
+    wrap_text(fonts, title, font_size, max_width)
+}
+
+/// Wraps `text` in `fonts` of `font_size` to `max_width`, starting a new line for every
+/// comma separated part
+fn wrap_text(fonts: &CardFonts, text: &str, font_size: f64, max_width: f64) -> Vec<String> {
     let mut lines = Vec::new();
 
     // First split by commas for multiple artists
@@ -236,36 +954,545 @@ fn wrap_text(text: &str, max_chars_per_line: usize) -> Vec<String> {
             continue;
         }
 
-        // If this part is short enough, add it as a single line
-        if part.len() <= max_chars_per_line {
-            lines.push(part.to_string());
-        } else {
-            // Split long parts by spaces for word wrapping
-            let words: Vec<&str> = part.split_whitespace().collect();
-            let mut current_line = String::new();
-
-            for word in words {
-                // If adding this word would exceed the line length
-                if current_line.len() + word.len() + 1 > max_chars_per_line
-                    && !current_line.is_empty()
-                {
-                    lines.push(current_line.trim().to_string());
-                    current_line = String::new();
-                }
+        lines.extend(wrap_text_at_width(fonts, part, font_size, max_width));
+    }
 
-                if current_line.is_empty() {
-                    current_line = word.to_string();
-                } else {
-                    current_line.push(' ');
-                    current_line.push_str(word);
-                }
+    lines
+}
+
+/// Word wraps `text` into lines at most `max_width` points wide when rendered in `fonts`,
+/// hyphenating words that do not fit on a line of their own
+pub(crate) fn wrap_text_at_width(
+    fonts: &CardFonts,
+    text: &str,
+    font_size: f64,
+    max_width: f64,
+) -> Vec<String> {
+    let width = |text: &str| fonts.text_width(text, font_size);
+    if width(text) <= max_width {
+        return vec![text.to_string()];
+    }
+
+    let mut lines = Vec::new();
+    let mut current_line = String::new();
+    for mut word in text.split_whitespace() {
+        if !current_line.is_empty() && width(&format!("{current_line} {word}")) > max_width {
+            lines.push(std::mem::take(&mut current_line));
+        }
+
+        while width(word) > max_width {
+            // A line keeps at least one grapheme even if it overflows, otherwise the
+            // wrapping could never make progress
+            let Some((first, _)) = word.grapheme_indices(true).nth(1) else {
+                break;
+            };
+            let split = word
+                .grapheme_indices(true)
+                .map(|(index, _)| index)
+                .skip(2)
+                .take_while(|&index| width(&format!("{}-", &word[..index])) <= max_width)
+                .last()
+                .unwrap_or(first);
+            lines.push(format!("{}-", &word[..split]));
+            word = &word[split..];
+        }
+
+        if !current_line.is_empty() {
+            current_line.push(' ');
+        }
+        current_line.push_str(word);
+    }
+
+    if !current_line.is_empty() {
+        lines.push(current_line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[tokio::test]
+    async fn test_back_cards_include_back_text() {
+        let mut playlist = fixtures::playlist(
+            "Tournament",
+            vec![crate::domain::Track {
+                title: "Song".to_string(),
+                artist: "Artist".to_string(),
+                year: 1999,
+                spotify_url: "https://open.spotify.com/track/1".to_string(),
+                album_cover_url: None,
+                popularity: None,
+                duration_ms: None,
+                notes: None,
+            }],
+        );
+        playlist.set_back_text(Some("Game Night 2025")).unwrap();
+
+        let bytes = PdfGenerator::new()
+            .generate_back_cards(&playlist, &PdfGeneratorConfig::default())
+            .await
+            .unwrap();
+
+        let document = oxidize_pdf::parser::PdfReader::new(std::io::Cursor::new(bytes))
+            .unwrap()
+            .into_document();
+        let text: String = document
+            .extract_text()
+            .unwrap()
+            .into_iter()
+            .map(|page| page.text.replace('\0', ""))
+            .collect();
+        assert!(text.contains("Game Night 2025"));
+    }
+
+    #[tokio::test]
+    async fn test_front_cards_include_notes_when_enabled() {
+        let mut track = crate::domain::Track {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            year: 1999,
+            spotify_url: "https://open.spotify.com/track/1".to_string(),
+            album_cover_url: None,
+            popularity: None,
+            duration_ms: Some(222_500),
+            notes: None,
+        };
+        track.set_notes(Some("Number 1 for 10 weeks")).unwrap();
+        let playlist = fixtures::playlist("Annotated", vec![track]);
+
+        let front_text = |options: PdfGeneratorOptions| {
+            let playlist = playlist.clone();
+            async move {
+                let bytes = PdfGenerator::with_options(options)
+                    .generate_front_cards(&playlist, &PdfGeneratorConfig::default())
+                    .await
+                    .unwrap();
+                let document = oxidize_pdf::parser::PdfReader::new(std::io::Cursor::new(bytes))
+                    .unwrap()
+                    .into_document();
+                // The parser does not decode the two byte codes of the embedded font, so
+                // ASCII text comes out interleaved with NULs
+                document
+                    .extract_text()
+                    .unwrap()
+                    .into_iter()
+                    .map(|page| page.text.replace('\0', ""))
+                    .collect::<String>()
             }
+        };
+
+        let shown = front_text(PdfGeneratorOptions {
+            show_notes: true,
+            ..Default::default()
+        })
+        .await;
+        assert!(shown.contains("Number 1 for 10 weeks"));
+        assert!(shown.contains("3:42"));
+
+        let hidden = front_text(PdfGeneratorOptions::default()).await;
+        assert!(!hidden.contains("Number 1 for 10 weeks"));
+    }
+
+    #[tokio::test]
+    async fn test_front_cards_embed_unicode_font() {
+        let playlist = fixtures::playlist(
+            "Unicode",
+            vec![crate::domain::Track {
+                title: "告白予行練習".to_string(),
+                artist: "Кино".to_string(),
+                year: 2011,
+                spotify_url: "https://open.spotify.com/track/1".to_string(),
+                album_cover_url: None,
+                popularity: None,
+                duration_ms: None,
+                notes: None,
+            }],
+        );
+
+        let bytes = PdfGenerator::new()
+            .generate_front_cards(&playlist, &PdfGeneratorConfig::default())
+            .await
+            .unwrap();
+
+        let contains = |needle: &[u8]| bytes.windows(needle.len()).any(|window| window == needle);
+        assert!(contains(b"/BaseFont /DejaVuSans"));
+        assert!(contains(b"/FontFile2"));
+    }
+
+    #[tokio::test]
+    async fn test_front_cards_print_cjk_in_the_fallback_font() {
+        // Has a glyph for each character of the title and nothing else
+        let fonts = CardFonts::with_fallback(
+            include_bytes!("../../../tests/fixtures/cjk_test_font.ttf").to_vec(),
+        )
+        .unwrap();
+        let title = "告白予行練習";
+        assert!(title.chars().all(|c| !CARD_FONT_METRICS.has_glyph(c)));
+
+        let runs = fonts.runs("Kokuhaku 告白予行練習");
+        let fonts_used: Vec<_> = runs.iter().map(|(name, _, run)| (*name, *run)).collect();
+        assert_eq!(
+            fonts_used,
+            [(CARD_FONT, "Kokuhaku "), (FALLBACK_FONT, title)]
+        );
+        assert_eq!(
+            runs[1].1.measure_text(title, 12.0).glyph_count,
+            title.chars().count()
+        );
+
+        let playlist = fixtures::playlist(
+            "CJK",
+            vec![crate::domain::Track {
+                title: title.to_string(),
+                artist: "HoneyWorks".to_string(),
+                year: 2014,
+                spotify_url: "https://open.spotify.com/track/1".to_string(),
+                album_cover_url: None,
+                popularity: None,
+                duration_ms: None,
+                notes: None,
+            }],
+        );
+        let bytes = PdfGenerator::with_options(PdfGeneratorOptions {
+            fonts,
+            ..Default::default()
+        })
+        .generate_front_cards(&playlist, &PdfGeneratorConfig::default())
+        .await
+        .unwrap();
+
+        let contains = |needle: &[u8]| bytes.windows(needle.len()).any(|window| window == needle);
+        assert!(contains(b"/BaseFont /DejaVuSans"));
+        assert!(contains(b"/BaseFont /CardFallback"));
+    }
+
+    #[tokio::test]
+    async fn test_back_cards_print_cjk_in_the_fallback_font() {
+        let fonts = CardFonts::with_fallback(
+            include_bytes!("../../../tests/fixtures/cjk_test_font.ttf").to_vec(),
+        )
+        .unwrap();
+        // Without back text the playlist name is printed on the backs
+        let playlist = fixtures::playlist("告白予行練習", fixtures::tracks(1));
+        assert_eq!(playlist.card_back_text(), "告白予行練習");
 
-            if !current_line.is_empty() {
-                lines.push(current_line.trim().to_string());
+        let bytes = PdfGenerator::with_options(PdfGeneratorOptions {
+            fonts,
+            ..Default::default()
+        })
+        .generate_back_cards(&playlist, &PdfGeneratorConfig::default())
+        .await
+        .unwrap();
+
+        let contains = |needle: &[u8]| bytes.windows(needle.len()).any(|window| window == needle);
+        assert!(contains(b"/BaseFont /DejaVuSans"));
+        assert!(contains(b"/BaseFont /CardFallback"));
+    }
+
+    #[tokio::test]
+    async fn test_album_covers_are_downloaded_once() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let mut png = Vec::new();
+        image::RgbaImage::new(4, 4)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        Mock::given(method("GET"))
+            .and(path("/cover.png"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(png))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let track = |album_cover_url: String| crate::domain::Track {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            year: 1999,
+            spotify_url: "https://open.spotify.com/track/1".to_string(),
+            album_cover_url: Some(album_cover_url),
+            popularity: None,
+            duration_ms: None,
+            notes: None,
+        };
+        let cover_url = format!("{}/cover.png", server.uri());
+        let missing_url = format!("{}/missing.png", server.uri());
+        let playlist = fixtures::playlist(
+            "Album",
+            vec![
+                track(cover_url.clone()),
+                track(cover_url.clone()),
+                track(missing_url.clone()),
+            ],
+        );
+
+        let generator = PdfGenerator::new();
+        let covers = generator.album_covers(&playlist).await;
+        assert!(covers.contains_key(&cover_url));
+        assert!(!covers.contains_key(&missing_url));
+
+        // Served from the cache, the missing cover does not fail the generation
+        generator
+            .generate_front_cards(&playlist, &PdfGeneratorConfig::default())
+            .await
+            .unwrap();
+        generator
+            .generate_back_cards(&playlist, &PdfGeneratorConfig::default())
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_album_cover_cache_evicts_least_recently_used() {
+        let cover = |byte: u8| Bytes::from(vec![byte; 10]);
+        let mut cache = AlbumCoverCache::new(25);
+        cache.insert("a".to_string(), cover(1));
+        cache.insert("b".to_string(), cover(2));
+        assert_eq!(cache.get("a"), Some(cover(1)));
+
+        cache.insert("c".to_string(), cover(3));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(cover(1)));
+        assert_eq!(cache.get("c"), Some(cover(3)));
+        assert_eq!(cache.size, 20);
+
+        // A cover larger than the whole cache is not kept
+        cache.insert("d".to_string(), Bytes::from(vec![4; 30]));
+        assert_eq!(cache.get("d"), None);
+        assert_eq!(cache.size, 0);
+    }
+
+    #[test]
+    fn test_qr_code_keeps_clear_of_thumbnail() {
+        for layout in [
+            CardLayout::ThreeByFour,
+            CardLayout::TwoByThree,
+            CardLayout::FourBySix,
+        ] {
+            let card_width = PAGE_WIDTH / layout.columns() as f64;
+            let card_height = 842.0 / layout.rows() as f64;
+
+            let margin = PdfGeneratorConfig::default().qr_padding();
+
+            let (offset, size) =
+                qr_code_area(card_width, card_height, margin, BACK_TEXT_AREA, true);
+            assert!(offset >= margin + ALBUM_THUMBNAIL_SIZE);
+            assert!(offset + size <= card_width - margin);
+
+            let (offset, size) =
+                qr_code_area(card_width, card_height, margin, BACK_TEXT_AREA, false);
+            assert_eq!(offset, (card_width - size) / 2.0);
+        }
+    }
+
+    #[test]
+    fn test_long_back_text_wraps_below_the_qr_code() {
+        let fonts = CardFonts::default();
+        let back_text = "Summer Party ".repeat(8);
+        let back_text = back_text.trim();
+        for layout in [
+            CardLayout::ThreeByFour,
+            CardLayout::TwoByThree,
+            CardLayout::FourBySix,
+        ] {
+            let card_width = PAGE_WIDTH / layout.columns() as f64;
+            let card_height = 842.0 / layout.rows() as f64;
+            let margin = PdfGeneratorConfig::default().qr_padding();
+
+            let lines =
+                wrap_text_at_width(&fonts, back_text, BACK_TEXT_SIZE, card_width - 2.0 * margin);
+            assert!(lines.len() > 1);
+            for line in &lines {
+                assert!(fonts.text_width(line, BACK_TEXT_SIZE) <= card_width - 2.0 * margin);
             }
+
+            let text_area = back_text_area(lines.len());
+            let (_, size) = qr_code_area(card_width, card_height, margin, text_area, false);
+            assert!(margin + text_area + size <= card_height - margin);
         }
     }
 
-    lines
+    #[test]
+    fn test_card_layout_parses_columns_by_rows() {
+        for layout in [
+            CardLayout::ThreeByFour,
+            CardLayout::TwoByThree,
+            CardLayout::FourBySix,
+        ] {
+            assert_eq!(layout.to_string().parse::<CardLayout>().unwrap(), layout);
+        }
+        assert_eq!(CardLayout::FourBySix.to_string(), "4x6");
+        assert_eq!(CardLayout::FourBySix.cards_per_page(), 24);
+        assert!("5x5".parse::<CardLayout>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_default_layout_prints_twelve_cards_per_page() {
+        let generator = PdfGenerator::new();
+        let config = PdfGeneratorConfig::default();
+        for (track_count, pages) in [(12, 1), (24, 2), (25, 3)] {
+            let playlist = fixtures::playlist("Pages", fixtures::tracks(track_count));
+            let bytes = generator
+                .generate_front_cards(&playlist, &config)
+                .await
+                .unwrap();
+
+            let document = oxidize_pdf::parser::PdfReader::new(std::io::Cursor::new(bytes))
+                .unwrap()
+                .into_document();
+            assert_eq!(
+                document.page_count().unwrap(),
+                pages,
+                "{track_count} tracks"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pages_follow_card_layout() {
+        let playlist = fixtures::playlist(
+            "Grid",
+            (0..12)
+                .map(|i| crate::domain::Track {
+                    title: format!("Song {i}"),
+                    artist: "Artist".to_string(),
+                    year: 1999,
+                    spotify_url: format!("https://open.spotify.com/track/{i}"),
+                    album_cover_url: None,
+                    popularity: None,
+                    duration_ms: None,
+                    notes: None,
+                })
+                .collect(),
+        );
+
+        let generator = PdfGenerator::new();
+        for (layout, pages) in [
+            (CardLayout::ThreeByFour, 1),
+            (CardLayout::TwoByThree, 2),
+            (CardLayout::FourBySix, 1),
+        ] {
+            let config = PdfGeneratorConfig {
+                layout,
+                ..Default::default()
+            };
+            for bytes in [
+                generator
+                    .generate_front_cards(&playlist, &config)
+                    .await
+                    .unwrap(),
+                generator
+                    .generate_back_cards(&playlist, &config)
+                    .await
+                    .unwrap(),
+            ] {
+                let document = oxidize_pdf::parser::PdfReader::new(std::io::Cursor::new(bytes))
+                    .unwrap()
+                    .into_document();
+                assert_eq!(document.page_count().unwrap(), pages, "{layout}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_svg_qr_codes_are_drawn_as_vector_paths() {
+        let url = "https://open.spotify.com/track/1";
+        let mut page = Page::a4();
+        QrCodeGraphic::new(url, QrCodeFormat::Svg)
+            .unwrap()
+            .draw(&mut page, url, 0.0, 0.0, 100.0)
+            .unwrap();
+
+        let operations = page.graphics().operations();
+        assert!(operations.contains(" re\n"));
+        assert!(operations.contains("f\n"));
+        // No image is placed with `Do`
+        assert!(!operations.contains(" Do\n"));
+    }
+
+    #[test]
+    fn test_border_none_does_not_stroke() {
+        let mut page = Page::a4();
+        draw_card_border(&mut page, &CardBorderStyle::None, 0.0, 0.0, 100.0, 100.0);
+        assert!(!page.graphics().operations().contains("S\n"));
+    }
+
+    #[test]
+    fn test_border_dashed_sets_dash_pattern() {
+        let mut page = Page::a4();
+        let style = CardBorderStyle::Dashed {
+            dash_length: 4.0,
+            gap: 2.0,
+        };
+        draw_card_border(&mut page, &style, 0.0, 0.0, 100.0, 100.0);
+        let operations = page.graphics().operations();
+        assert!(operations.contains("[4.00 2.00] 0.00 d"));
+        assert!(operations.contains("S\n"));
+    }
+
+    #[test]
+    fn test_wrap_text_at_width_measures_rendered_width() {
+        let wrap = |text: &str, max_width: f64| {
+            wrap_text_at_width(&CardFonts::default(), text, 12.0, max_width)
+        };
+        let max_width = CardFonts::default().text_width("WWWWW", 12.0);
+
+        // Same number of characters, but narrow letters fit on a single line
+        assert_eq!(
+            wrap("iiiii iiiii iiiii", max_width),
+            vec!["iiiii iiiii iiiii".to_string()]
+        );
+        assert_eq!(
+            wrap("WWW WWW", max_width),
+            vec!["WWW".to_string(), "WWW".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_wrap_text_at_width_keeps_words_together() {
+        let max_width = CardFonts::default().text_width("Winding Road", 12.0);
+        assert_eq!(
+            wrap_text_at_width(
+                &CardFonts::default(),
+                "The Long and Winding Road",
+                12.0,
+                max_width
+            ),
+            vec!["The Long and".to_string(), "Winding Road".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_wrap_text_at_width_hyphenates_long_words() {
+        let max_width = CardFonts::default().text_width("Superca-", 12.0);
+        let lines = wrap_text_at_width(
+            &CardFonts::default(),
+            "Supercalifragilistic",
+            12.0,
+            max_width,
+        );
+
+        assert_eq!(lines[0], "Superca-");
+        assert!(lines.len() > 2);
+        assert!(
+            lines
+                .iter()
+                .all(|line| CardFonts::default().text_width(line, 12.0) <= max_width)
+        );
+        assert_eq!(lines.concat().replace('-', ""), "Supercalifragilistic");
+    }
+
+    #[test]
+    fn test_wrap_text_at_width_never_splits_graphemes() {
+        // Narrower than any letter, every grapheme gets a line of its own
+        assert_eq!(
+            wrap_text_at_width(&CardFonts::default(), "ðe\u{301}", 12.0, 1.0),
+            vec!["ð-".to_string(), "e\u{301}".to_string()]
+        );
+    }
 }
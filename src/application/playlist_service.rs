@@ -1,43 +1,254 @@
 use crate::application::worker::{GeneratePlaylistPdfsResult, IWorker};
 use crate::application::{
-    IJobsRepository, IPdfGenerator, IPlaylistRepository, ISpotifyClient, worker,
+    FetchProgress, IEventStore, IJobsRepository, IPdfGenerator, IPlaylistRepository,
+    ISpotifyClient, JobFilter, JobsPage, MAX_TRACKS_PER_REQUEST, NamedJobsPage, Pagination,
+    PlaylistOrder, PlaylistsPage, SpotifyPlaylistSummary, worker,
+};
+use crate::domain::{
+    DomainEvent, Job, JobId, JobStatus, JobType, Pdf, Playlist, PlaylistDomainService, PlaylistId,
+    SortOrder, SpotifyId, Tag, Track, UserContext,
 };
-use crate::domain::{Job, JobId, JobStatus, Pdf, Playlist, PlaylistId, SpotifyId};
 use std::future::Future;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, instrument, warn};
+
+#[derive(Debug, displaydoc::Display, thiserror::Error)]
+/// Playlist has {track_count} tracks, more than the {max_tracks} allowed
+pub struct PlaylistTooLargeError {
+    pub track_count: u32,
+    pub max_tracks: u32,
+}
+
+#[derive(Debug, displaydoc::Display, thiserror::Error)]
+/// Job {job_id} is already {status} and can no longer be cancelled
+pub struct JobAlreadyDoneError {
+    pub job_id: JobId,
+    pub status: JobStatus,
+}
 
 pub trait IPlaylistService: Clone + Send + Sync + 'static {
     fn create_from_spotify(
         &self,
+        user: &UserContext,
         id: &SpotifyId,
     ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
     fn create_partial_playlist_from_spotify(
         &self,
+        user: &UserContext,
         id: &SpotifyId,
     ) -> impl Future<Output = anyhow::Result<(Option<Playlist>, Option<Job>)>> + Send;
+    /// Creates a playlist with the tracks of a Spotify album, named after the album
+    fn create_from_spotify_album(
+        &self,
+        user: &UserContext,
+        album_id: &SpotifyId,
+    ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
     fn get_playlist(
         &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    fn get_by_spotify_id(
+        &self,
+        user: &UserContext,
+        spotify_id: &SpotifyId,
+    ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    fn get_missing_year_tracks(
+        &self,
+        user: &UserContext,
         id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<Option<Vec<(usize, Track)>>>> + Send;
+    fn update_back_text(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        back_text: Option<String>,
+    ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    /// Gives the playlist a local display name, kept when it is refetched from Spotify
+    fn rename_playlist(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        new_name: &str,
+    ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    /// Sets the game master notes of the track at `position`, `None` if there is no such track
+    fn update_track_notes(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        position: usize,
+        notes: Option<String>,
+    ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    /// Reorders and stores the tracks of a playlist
+    fn sort_tracks(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        sort_order: SortOrder,
     ) -> impl Future<Output = anyhow::Result<Option<Playlist>>> + Send;
+    fn add_tag(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        tag: &Tag,
+    ) -> impl Future<Output = anyhow::Result<Vec<Tag>>> + Send;
+    fn remove_tag(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        tag: &Tag,
+    ) -> impl Future<Output = anyhow::Result<Vec<Tag>>> + Send;
+    fn get_tags(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<Vec<Tag>>> + Send;
+    /// Deletes the playlist, its jobs and generated PDFs, `false` if the user has no such playlist
+    fn delete_playlist(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<bool>> + Send;
+    /// Lists the playlists of the user's session, newest first
+    fn list_playlists(
+        &self,
+        user: &UserContext,
+        pagination: Pagination,
+        order: PlaylistOrder,
+    ) -> impl Future<Output = anyhow::Result<PlaylistsPage>> + Send;
+    /// Cursor based alternative to [`Self::list_playlists`], see [`IPlaylistRepository::list_after`]
+    fn list_playlists_after(
+        &self,
+        user: &UserContext,
+        after: Option<&PlaylistId>,
+        limit: u32,
+    ) -> impl Future<Output = anyhow::Result<Vec<Playlist>>> + Send;
+    /// Playlists of the user whose name contains `query`, see [`IPlaylistRepository::search_by_name`]
+    fn search_playlists_by_name(
+        &self,
+        user: &UserContext,
+        query: &str,
+    ) -> impl Future<Output = anyhow::Result<Vec<Playlist>>> + Send;
+    fn get_playlists_by_tag(
+        &self,
+        user: &UserContext,
+        tag: &Tag,
+        pagination: Pagination,
+    ) -> impl Future<Output = anyhow::Result<PlaylistsPage>> + Send;
+    fn refresh_album_covers(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
     fn generate_playlist_pdfs(
         &self,
+        user: &UserContext,
         id: &PlaylistId,
     ) -> impl Future<Output = anyhow::Result<Job>> + Send;
     fn get_playlist_pdfs(
         &self,
+        user: &UserContext,
         id: &PlaylistId,
     ) -> impl Future<Output = anyhow::Result<[Pdf; 2]>> + Send;
-    fn refetch_playlist(&self, id: &PlaylistId)
-    -> impl Future<Output = anyhow::Result<Job>> + Send;
+    fn refetch_playlist(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<Job>> + Send;
     fn get_latest_job(
         &self,
         playlist_id: &PlaylistId,
     ) -> impl Future<Output = anyhow::Result<Option<Job>>> + Send;
+    /// Every job of the playlist, newest first, `None` if the user has no such playlist
+    fn get_jobs_for_playlist(
+        &self,
+        user: &UserContext,
+        playlist_id: &PlaylistId,
+    ) -> impl Future<Output = anyhow::Result<Option<Vec<Job>>>> + Send;
+    fn get_latest_job_by_type(
+        &self,
+        playlist_id: &PlaylistId,
+        job_type: JobType,
+    ) -> impl Future<Output = anyhow::Result<Option<Job>>> + Send;
+    /// The job, `None` if it does not exist or another session enqueued it
     fn get_job_by_id(
         &self,
+        user: &UserContext,
         job_id: &JobId,
     ) -> impl Future<Output = anyhow::Result<Option<Job>>> + Send;
+    /// Jobs of the playlist the session enqueued at or after `since`, newest first
+    fn get_jobs_for_playlist_since(
+        &self,
+        user: &UserContext,
+        playlist_id: &PlaylistId,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = anyhow::Result<Vec<Job>>> + Send;
+    /// Marks a job of the session that has not finished as cancelled, so workers skip it,
+    /// returning `false` if it does not exist or another session enqueued it.
+    ///
+    /// A job that is already running is not interrupted, but its outcome is discarded.
+    /// Fails with [`JobAlreadyDoneError`] for jobs that already finished.
+    fn cancel_job(
+        &self,
+        user: &UserContext,
+        job_id: &JobId,
+    ) -> impl Future<Output = anyhow::Result<bool>> + Send;
+    fn list_jobs(
+        &self,
+        filter: &JobFilter,
+        pagination: Pagination,
+    ) -> impl Future<Output = anyhow::Result<JobsPage>> + Send;
+    /// Jobs of every session with the names of their playlists, for the admin dashboard
+    fn list_jobs_with_playlist_names(
+        &self,
+        filter: &JobFilter,
+        pagination: Pagination,
+    ) -> impl Future<Output = anyhow::Result<NamedJobsPage>> + Send;
+    /// Number of jobs waiting for a worker
+    fn pending_job_count(&self) -> impl Future<Output = anyhow::Result<u64>> + Send;
+    /// Searches Spotify for playlists to import, see [`ISpotifyClient::search_playlists`]
+    fn search_spotify_playlists(
+        &self,
+        query: &str,
+        limit: u32,
+    ) -> impl Future<Output = anyhow::Result<Vec<SpotifyPlaylistSummary>>> + Send;
+    /// Checks that the database answers and Spotify can be called
+    fn check_health(&self) -> impl Future<Output = HealthReport> + Send;
+    /// Number of jobs in each status
+    fn job_stats(&self) -> impl Future<Output = anyhow::Result<JobStats>> + Send;
+    /// Number of playlists and tracks stored across all sessions
+    fn library_stats(&self) -> impl Future<Output = anyhow::Result<LibraryStats>> + Send;
+}
+
+/// Outcome of [`IPlaylistService::check_health`], describing what failed in each `Err`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    pub database: Result<(), String>,
+    pub spotify: Result<(), String>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.database.is_ok() && self.spotify.is_ok()
+    }
+}
+
+/// Number of jobs in each status, see [`IPlaylistService::job_stats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JobStats {
+    pub pending: u64,
+    pub processing: u64,
+    pub retrying: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub cancelled: u64,
+}
+
+/// Size of the playlist library, see [`IPlaylistService::library_stats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LibraryStats {
+    pub playlists: u64,
+    pub tracks: u64,
 }
 
 #[derive(Clone)]
@@ -46,151 +257,1448 @@ pub struct PlaylistService<
     PR: IPlaylistRepository,
     JR: IJobsRepository,
     PG: IPdfGenerator,
+    ES: IEventStore,
 > {
     spotify_client: Arc<SC>,
     playlist_repository: Arc<PR>,
     jobs_repository: Arc<JR>,
-    pdf_worker: Arc<worker::Worker<JR, worker::GeneratePlaylistPdfsTask<PR, PG>>>,
-    refetch_worker: Arc<worker::Worker<JR, worker::RefetchPlaylistTask<PR, SC>>>,
+    event_store: Arc<ES>,
+    pdf_worker: Arc<worker::Worker<JR, worker::GeneratePlaylistPdfsTask<PR, PG, ES>>>,
+    refetch_worker: Arc<worker::Worker<JR, worker::RefetchPlaylistTask<PR, SC, ES, JR>>>,
+    /// Drop tracks added to a Spotify playlist more than once when importing it
+    dedup_on_import: bool,
+    /// Spotify playlists with more tracks are rejected before their tracks are fetched
+    max_playlist_tracks: u32,
 }
 
-impl<SC: ISpotifyClient, PR: IPlaylistRepository, JR: IJobsRepository, PG: IPdfGenerator>
-    PlaylistService<SC, PR, JR, PG>
+impl<
+    SC: ISpotifyClient,
+    PR: IPlaylistRepository,
+    JR: IJobsRepository,
+    PG: IPdfGenerator,
+    ES: IEventStore,
+> PlaylistService<SC, PR, JR, PG, ES>
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         playlist_repository: Arc<PR>,
         spotify_client: Arc<SC>,
         jobs_repository: Arc<JR>,
-        pdf_worker: Arc<worker::Worker<JR, worker::GeneratePlaylistPdfsTask<PR, PG>>>,
-        refetch_worker: Arc<worker::Worker<JR, worker::RefetchPlaylistTask<PR, SC>>>,
+        event_store: Arc<ES>,
+        pdf_worker: Arc<worker::Worker<JR, worker::GeneratePlaylistPdfsTask<PR, PG, ES>>>,
+        refetch_worker: Arc<worker::Worker<JR, worker::RefetchPlaylistTask<PR, SC, ES, JR>>>,
+        dedup_on_import: bool,
+        max_playlist_tracks: u32,
     ) -> Self {
         Self {
             spotify_client,
             playlist_repository,
             jobs_repository,
+            event_store,
             pdf_worker,
             refetch_worker,
+            dedup_on_import,
+            max_playlist_tracks,
         }
     }
-}
 
-impl<SC: ISpotifyClient, PR: IPlaylistRepository, JR: IJobsRepository, PG: IPdfGenerator>
-    IPlaylistService for PlaylistService<SC, PR, JR, PG>
-{
-    async fn create_from_spotify(&self, id: &SpotifyId) -> anyhow::Result<Option<Playlist>> {
-        if let Some(existing) = self.playlist_repository.get_by_spotify_id(id).await? {
+    /// The session's copy of the Spotify playlist, stored from `fetch` when there is none yet.
+    ///
+    /// `true` when this call stored the playlist. `fetch` only runs when the session has no
+    /// copy, and the [`IPlaylistRepository::upsert`] settles concurrent imports of the same
+    /// playlist. `None` when Spotify does not know the playlist.
+    async fn get_or_create_from_spotify(
+        &self,
+        user: &UserContext,
+        id: &SpotifyId,
+        fetch: impl Future<Output = anyhow::Result<Option<Playlist>>>,
+    ) -> anyhow::Result<Option<(Playlist, bool)>> {
+        if let Some(existing) = self
+            .playlist_repository
+            .get_by_spotify_id(&user.session_id, id)
+            .await?
+        {
             info!(
                 "Playlist with Spotify ID {} already exists with ID {}",
                 id, existing.id
             );
-            return Ok(Some(existing));
+            return Ok(Some((existing, false)));
         }
 
-        let playlist = match self.spotify_client.get_playlist_with_tracks(id).await? {
-            Some(p) => p,
-            None => {
-                info!("Playlist with Spotify ID {} not found", id);
+        let Some(playlist) = fetch.await? else {
+            info!("Playlist with Spotify ID {} not found", id);
+            return Ok(None);
+        };
+
+        let (stored, was_created) = self
+            .playlist_repository
+            .upsert(&user.session_id, &playlist)
+            .await?;
+        if was_created {
+            self.record_created(&stored).await;
+            info!(
+                "Created new playlist with ID {} from Spotify ID {}",
+                stored.id, id
+            );
+        } else {
+            info!(
+                "Playlist with Spotify ID {} was imported concurrently with ID {}",
+                id, stored.id
+            );
+        }
+        Ok(Some((stored, was_created)))
+    }
+
+    /// Records a [`DomainEvent::PlaylistCreated`] for the newly stored playlist
+    async fn record_created(&self, created: &Playlist) {
+        self.event_store
+            .record(DomainEvent::PlaylistCreated {
+                playlist_id: created.id.clone(),
+                spotify_id: created.spotify_id.clone(),
+                track_count: created.track_count(),
+            })
+            .await;
+    }
+
+    /// Fails with [`PlaylistTooLargeError`] when the Spotify playlist has more tracks than allowed
+    fn check_track_limit(&self, track_count: u32) -> anyhow::Result<()> {
+        if track_count > self.max_playlist_tracks {
+            return Err(PlaylistTooLargeError {
+                track_count,
+                max_tracks: self.max_playlist_tracks,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+impl<
+    SC: ISpotifyClient,
+    PR: IPlaylistRepository,
+    JR: IJobsRepository,
+    PG: IPdfGenerator,
+    ES: IEventStore,
+> IPlaylistService for PlaylistService<SC, PR, JR, PG, ES>
+{
+    #[instrument(skip_all, fields(spotify_id = %id))]
+    async fn create_from_spotify(
+        &self,
+        user: &UserContext,
+        id: &SpotifyId,
+    ) -> anyhow::Result<Option<Playlist>> {
+        let fetch = async {
+            let track_count = self.spotify_client.get_playlist_track_count(id).await?;
+            self.check_track_limit(track_count)?;
+            let Some(fetched) = self
+                .spotify_client
+                .get_playlist_with_tracks(id, &FetchProgress::default())
+                .await?
+            else {
                 return Ok(None);
+            };
+            let mut playlist = fetched.playlist;
+            if self.dedup_on_import {
+                let removed = playlist.dedup_tracks();
+                if removed > 0 {
+                    warn!(
+                        "Removed {} duplicate tracks from Spotify playlist {}",
+                        removed, id
+                    );
+                }
             }
+            Ok(Some(playlist))
         };
 
-        let created = self.playlist_repository.create(&playlist).await?;
-        info!(
-            "Created new playlist with ID {} from Spotify ID {}",
-            created.id, id
-        );
-        Ok(Some(created))
+        Ok(self
+            .get_or_create_from_spotify(user, id, fetch)
+            .await?
+            .map(|(playlist, _)| playlist))
     }
 
+    #[instrument(skip_all, fields(spotify_id = %id))]
     async fn create_partial_playlist_from_spotify(
         &self,
+        user: &UserContext,
         id: &SpotifyId,
     ) -> anyhow::Result<(Option<Playlist>, Option<Job>)> {
-        if let Some(existing) = self.playlist_repository.get_by_spotify_id(id).await? {
+        // The refetch job fetches the tracks, the playlist is stored from its metadata alone
+        let fetch = async {
+            let Some(metadata) = self.spotify_client.get_playlist_metadata(id).await? else {
+                return Ok(None);
+            };
+            self.check_track_limit(metadata.track_count)?;
+            Ok(Some(Playlist {
+                id: PlaylistId::new(),
+                spotify_id: Some(id.clone()),
+                name: metadata.name,
+                tracks: vec![],
+                created_at: None,
+                updated_at: None,
+                back_text: None,
+                spotify_track_count: Some(metadata.track_count),
+                custom_name: None,
+            }))
+        };
+        let Some((playlist, was_created)) =
+            self.get_or_create_from_spotify(user, id, fetch).await?
+        else {
+            return Ok((None, None));
+        };
+        if !was_created {
+            return Ok((Some(playlist), None));
+        }
+
+        let job = IPlaylistService::refetch_playlist(self, user, &playlist.id).await?;
+
+        Ok((Some(playlist), Some(job)))
+    }
+
+    #[instrument(skip_all, fields(spotify_id = %album_id))]
+    async fn create_from_spotify_album(
+        &self,
+        user: &UserContext,
+        album_id: &SpotifyId,
+    ) -> anyhow::Result<Option<Playlist>> {
+        let Some(playlist) = self.spotify_client.get_album_tracks(album_id).await? else {
+            info!("Album with Spotify ID {} not found", album_id);
+            return Ok(None);
+        };
+
+        let (created, was_created) = self
+            .playlist_repository
+            .upsert(&user.session_id, &playlist)
+            .await?;
+        if was_created {
+            self.record_created(&created).await;
             info!(
-                "Playlist with Spotify ID {} already exists with ID {}",
-                id, existing.id
+                "Created new playlist with ID {} from Spotify album {}",
+                created.id, album_id
+            );
+        } else {
+            info!(
+                "Playlist of Spotify album {} already exists with ID {}",
+                album_id, created.id
             );
-            return Ok((Some(existing), None));
         }
+        Ok(Some(created))
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn get_playlist(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> anyhow::Result<Option<Playlist>> {
+        self.playlist_repository.get(&user.session_id, id).await
+    }
+
+    #[instrument(skip_all, fields(spotify_id = %spotify_id))]
+    async fn get_by_spotify_id(
+        &self,
+        user: &UserContext,
+        spotify_id: &SpotifyId,
+    ) -> anyhow::Result<Option<Playlist>> {
+        self.playlist_repository
+            .get_by_spotify_id(&user.session_id, spotify_id)
+            .await
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn get_missing_year_tracks(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> anyhow::Result<Option<Vec<(usize, Track)>>> {
+        let Some(playlist) = self.playlist_repository.get(&user.session_id, id).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            playlist
+                .tracks
+                .into_iter()
+                .enumerate()
+                .filter(|(_, track)| track.has_missing_year())
+                .collect(),
+        ))
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn update_back_text(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        back_text: Option<String>,
+    ) -> anyhow::Result<Option<Playlist>> {
+        let mut playlist = match self.playlist_repository.get(&user.session_id, id).await? {
+            Some(playlist) => playlist,
+            None => return Ok(None),
+        };
+
+        playlist.set_back_text(back_text.as_deref())?;
+
+        let updated = self.playlist_repository.update(&playlist).await?;
+        info!("Updated back text of playlist {}", id);
+        Ok(Some(updated))
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn rename_playlist(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        new_name: &str,
+    ) -> anyhow::Result<Option<Playlist>> {
+        let Some(mut playlist) = self.playlist_repository.get(&user.session_id, id).await? else {
+            return Ok(None);
+        };
+
+        playlist.rename(new_name)?;
+
+        let updated = self.playlist_repository.update(&playlist).await?;
+        info!("Renamed playlist {}", id);
+        Ok(Some(updated))
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id, position = position))]
+    async fn update_track_notes(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        position: usize,
+        notes: Option<String>,
+    ) -> anyhow::Result<Option<Playlist>> {
+        let mut playlist = match self.playlist_repository.get(&user.session_id, id).await? {
+            Some(playlist) => playlist,
+            None => return Ok(None),
+        };
+        let Some(track) = playlist.tracks.get_mut(position) else {
+            return Ok(None);
+        };
+
+        track.set_notes(notes.as_deref())?;
+
+        let updated = self.playlist_repository.update(&playlist).await?;
+        info!("Updated notes of track {} in playlist {}", position, id);
+        Ok(Some(updated))
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn sort_tracks(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        sort_order: SortOrder,
+    ) -> anyhow::Result<Option<Playlist>> {
+        let mut playlist = match self.playlist_repository.get(&user.session_id, id).await? {
+            Some(playlist) => playlist,
+            None => return Ok(None),
+        };
+
+        playlist.tracks = match sort_order {
+            SortOrder::Difficulty => PlaylistDomainService::sort_by_difficulty(&playlist)
+                .into_iter()
+                .cloned()
+                .collect(),
+        };
+
+        let updated = self.playlist_repository.update(&playlist).await?;
+        info!("Sorted tracks of playlist {} by {:?}", id, sort_order);
+        Ok(Some(updated))
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id, tag = %tag))]
+    async fn add_tag(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        tag: &Tag,
+    ) -> anyhow::Result<Vec<Tag>> {
+        if self
+            .playlist_repository
+            .get(&user.session_id, id)
+            .await?
+            .is_none()
+        {
+            anyhow::bail!("Playlist with ID {} not found", id);
+        }
+
+        self.playlist_repository.add_tag(id, tag).await?;
+        info!("Tagged playlist {} with '{}'", id, tag);
+        self.playlist_repository
+            .get_tags(&user.session_id, id)
+            .await
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id, tag = %tag))]
+    async fn remove_tag(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+        tag: &Tag,
+    ) -> anyhow::Result<Vec<Tag>> {
+        if self
+            .playlist_repository
+            .get(&user.session_id, id)
+            .await?
+            .is_none()
+        {
+            anyhow::bail!("Playlist with ID {} not found", id);
+        }
+
+        self.playlist_repository.remove_tag(id, tag).await?;
+        self.playlist_repository
+            .get_tags(&user.session_id, id)
+            .await
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn delete_playlist(&self, user: &UserContext, id: &PlaylistId) -> anyhow::Result<bool> {
+        if !self
+            .playlist_repository
+            .delete(&user.session_id, id)
+            .await?
+        {
+            return Ok(false);
+        }
+        info!("Deleted playlist {}", id);
+        Ok(true)
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn get_tags(&self, user: &UserContext, id: &PlaylistId) -> anyhow::Result<Vec<Tag>> {
+        self.playlist_repository
+            .get_tags(&user.session_id, id)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn list_playlists(
+        &self,
+        user: &UserContext,
+        pagination: Pagination,
+        order: PlaylistOrder,
+    ) -> anyhow::Result<PlaylistsPage> {
+        self.playlist_repository
+            .list(&user.session_id, pagination, order)
+            .await
+    }
+
+    #[instrument(skip_all, fields(limit = limit))]
+    async fn list_playlists_after(
+        &self,
+        user: &UserContext,
+        after: Option<&PlaylistId>,
+        limit: u32,
+    ) -> anyhow::Result<Vec<Playlist>> {
+        self.playlist_repository
+            .list_after(&user.session_id, after, limit)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn search_playlists_by_name(
+        &self,
+        user: &UserContext,
+        query: &str,
+    ) -> anyhow::Result<Vec<Playlist>> {
+        self.playlist_repository
+            .search_by_name(&user.session_id, query)
+            .await
+    }
 
-        let playlist = match self.spotify_client.get_playlist(id).await? {
-            Some(p) => p,
+    #[instrument(skip_all, fields(tag = %tag))]
+    async fn get_playlists_by_tag(
+        &self,
+        user: &UserContext,
+        tag: &Tag,
+        pagination: Pagination,
+    ) -> anyhow::Result<PlaylistsPage> {
+        self.playlist_repository
+            .list_by_tag(&user.session_id, tag, pagination)
+            .await
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn refresh_album_covers(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> anyhow::Result<()> {
+        let mut playlist = match self.playlist_repository.get(&user.session_id, id).await? {
+            Some(playlist) => playlist,
             None => {
-                info!("Playlist with Spotify ID {} not found", id);
-                return Ok((None, None));
+                anyhow::bail!("Playlist with ID {} not found", id);
             }
         };
-        let created = self.playlist_repository.create(&playlist).await?;
 
-        let job = IPlaylistService::refetch_playlist(self, &playlist.id).await?;
+        let mut refreshed_count = 0;
+        for tracks in playlist.tracks.chunks_mut(MAX_TRACKS_PER_REQUEST) {
+            let urls = tracks.iter().map(|t| t.spotify_url.clone()).collect();
+            let refreshed = self.spotify_client.refresh_track_metadata(urls).await?;
 
-        Ok((Some(created), Some(job)))
-    }
+            for track in tracks.iter_mut() {
+                if let Some(fresh) = refreshed.get(&track.spotify_url)
+                    && fresh.album_cover_url != track.album_cover_url
+                {
+                    track.album_cover_url = fresh.album_cover_url.clone();
+                    refreshed_count += 1;
+                }
+            }
+        }
+
+        if refreshed_count > 0 {
+            self.playlist_repository.update(&playlist).await?;
+        }
+        info!(
+            "Refreshed {} album covers of playlist {}",
+            refreshed_count, id
+        );
 
-    async fn get_playlist(&self, id: &PlaylistId) -> anyhow::Result<Option<Playlist>> {
-        self.playlist_repository.get(id).await
+        Ok(())
     }
 
-    async fn generate_playlist_pdfs(&self, id: &PlaylistId) -> anyhow::Result<Job> {
-        let playlist = match self.playlist_repository.get(id).await? {
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn generate_playlist_pdfs(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> anyhow::Result<Job> {
+        let playlist = match self.playlist_repository.get(&user.session_id, id).await? {
             Some(playlist) => playlist,
             None => {
                 anyhow::bail!("Playlist with ID {} not found", id);
             }
         };
 
-        let task = worker::GeneratePlaylistPdfsTask::new(playlist.id);
+        let task = worker::GeneratePlaylistPdfsTask::new(playlist.id, user.session_id.clone());
 
         let job = self.pdf_worker.enqueue(task).await?;
 
         Ok(job)
     }
 
-    async fn get_playlist_pdfs(&self, id: &PlaylistId) -> anyhow::Result<[Pdf; 2]> {
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn get_playlist_pdfs(
+        &self,
+        user: &UserContext,
+        id: &PlaylistId,
+    ) -> anyhow::Result<[Pdf; 2]> {
+        if self
+            .playlist_repository
+            .get(&user.session_id, id)
+            .await?
+            .is_none()
+        {
+            anyhow::bail!("Playlist with ID {} not found", id);
+        }
+
         // Look for completed PDF generation jobs for this playlist
         let jobs = self.jobs_repository.get_by_playlist_id(id).await?;
 
-        let (_, pdfs) = jobs
+        let job = jobs
             .iter()
             .filter(|j| j.status == JobStatus::Completed)
-            .filter(|j| j.result.is_some())
-            .filter_map(|j| {
-                let result: serde_json::Value = j.result.clone()?;
-                let pdfs: GeneratePlaylistPdfsResult = serde_json::from_value(result).ok()?;
-                Some((j, pdfs))
+            .filter(|j| {
+                j.result.clone().is_some_and(|result| {
+                    serde_json::from_value::<GeneratePlaylistPdfsResult>(result).is_ok()
+                })
             })
-            .max_by_key(|(j, _)| j.completed_at)
+            .max_by_key(|j| j.completed_at)
             .ok_or(anyhow::anyhow!("No generation job found"))?;
 
-        let front: Pdf = tokio::fs::read(pdfs.front).await?.into();
-        let back: Pdf = tokio::fs::read(pdfs.back).await?.into();
-
-        Ok([front, back])
+        self.playlist_repository
+            .get_pdfs(&job.id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("PDFs of job {} not found", job.id))
     }
 
-    async fn refetch_playlist(&self, id: &PlaylistId) -> anyhow::Result<Job> {
-        let playlist = match self.playlist_repository.get(id).await? {
+    #[instrument(skip_all, fields(playlist_id = %id))]
+    async fn refetch_playlist(&self, user: &UserContext, id: &PlaylistId) -> anyhow::Result<Job> {
+        let playlist = match self.playlist_repository.get(&user.session_id, id).await? {
             Some(playlist) => playlist,
             None => {
                 anyhow::bail!("Playlist with ID {} not found", id);
             }
         };
 
-        let task = worker::RefetchPlaylistTask::new(playlist.id);
+        let task = worker::RefetchPlaylistTask::new(playlist.id, user.session_id.clone());
         let job = self.refetch_worker.enqueue(task).await?;
 
         Ok(job)
     }
 
+    #[instrument(skip_all, fields(playlist_id = %playlist_id))]
     async fn get_latest_job(&self, playlist_id: &PlaylistId) -> anyhow::Result<Option<Job>> {
         let jobs = self.jobs_repository.get_by_playlist_id(playlist_id).await?;
         Ok(jobs.into_iter().max_by_key(|j| j.created_at))
     }
 
-    async fn get_job_by_id(&self, job_id: &JobId) -> anyhow::Result<Option<Job>> {
+    #[instrument(skip_all, fields(playlist_id = %playlist_id))]
+    async fn get_jobs_for_playlist(
+        &self,
+        user: &UserContext,
+        playlist_id: &PlaylistId,
+    ) -> anyhow::Result<Option<Vec<Job>>> {
+        if self
+            .playlist_repository
+            .get(&user.session_id, playlist_id)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let jobs = self.playlist_repository.get_jobs(playlist_id).await?;
+        Ok(Some(jobs.unwrap_or_default()))
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %playlist_id))]
+    async fn get_latest_job_by_type(
+        &self,
+        playlist_id: &PlaylistId,
+        job_type: JobType,
+    ) -> anyhow::Result<Option<Job>> {
+        self.jobs_repository
+            .get_latest_by_type(playlist_id, job_type)
+            .await
+    }
+
+    #[instrument(skip_all, fields(job_id = %job_id))]
+    async fn get_job_by_id(
+        &self,
+        user: &UserContext,
+        job_id: &JobId,
+    ) -> anyhow::Result<Option<Job>> {
         let job = self.jobs_repository.get(job_id).await?;
 
-        Ok(job)
+        Ok(job.filter(|job| job.session_id().as_ref() == Some(&user.session_id)))
+    }
+
+    #[instrument(skip_all, fields(playlist_id = %playlist_id))]
+    async fn get_jobs_for_playlist_since(
+        &self,
+        user: &UserContext,
+        playlist_id: &PlaylistId,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<Job>> {
+        let mut jobs = self
+            .jobs_repository
+            .get_by_playlist_id_since(playlist_id, since)
+            .await?;
+        jobs.retain(|job| job.session_id().as_ref() == Some(&user.session_id));
+
+        Ok(jobs)
+    }
+
+    #[instrument(skip_all, fields(job_id = %job_id))]
+    async fn cancel_job(&self, user: &UserContext, job_id: &JobId) -> anyhow::Result<bool> {
+        let Some(job) = self.get_job_by_id(user, job_id).await? else {
+            return Ok(false);
+        };
+
+        // The status is only changed if the job has not finished by now, a worker may have
+        // finished it since it was loaded
+        if !self.jobs_repository.cancel(job_id).await? {
+            let status = self
+                .jobs_repository
+                .get(job_id)
+                .await?
+                .map_or(job.status, |job| job.status);
+            return Err(JobAlreadyDoneError {
+                job_id: job.id,
+                status,
+            }
+            .into());
+        }
+
+        info!("Cancelled job {}", job_id);
+        Ok(true)
+    }
+
+    #[instrument(skip_all)]
+    async fn list_jobs(
+        &self,
+        filter: &JobFilter,
+        pagination: Pagination,
+    ) -> anyhow::Result<JobsPage> {
+        self.jobs_repository.list(filter, pagination).await
+    }
+
+    #[instrument(skip_all)]
+    async fn list_jobs_with_playlist_names(
+        &self,
+        filter: &JobFilter,
+        pagination: Pagination,
+    ) -> anyhow::Result<NamedJobsPage> {
+        self.jobs_repository
+            .list_with_playlist_names(filter, pagination)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn pending_job_count(&self) -> anyhow::Result<u64> {
+        self.jobs_repository.get_pending_count().await
+    }
+
+    #[instrument(skip_all, fields(limit = limit))]
+    async fn search_spotify_playlists(
+        &self,
+        query: &str,
+        limit: u32,
+    ) -> anyhow::Result<Vec<SpotifyPlaylistSummary>> {
+        self.spotify_client.search_playlists(query, limit).await
+    }
+
+    #[instrument(skip_all)]
+    async fn check_health(&self) -> HealthReport {
+        let database = self
+            .jobs_repository
+            .ping()
+            .await
+            .map_err(|e| format!("{e:#}"));
+        let spotify = if self.spotify_client.has_token().await {
+            Ok(())
+        } else {
+            Err("no access token".to_string())
+        };
+
+        HealthReport { database, spotify }
+    }
+
+    #[instrument(skip_all)]
+    async fn job_stats(&self) -> anyhow::Result<JobStats> {
+        let count = |status| async move { self.jobs_repository.count_by_status(&status).await };
+
+        Ok(JobStats {
+            pending: count(JobStatus::Pending).await?,
+            processing: count(JobStatus::Processing).await?,
+            retrying: count(JobStatus::Retrying).await?,
+            completed: count(JobStatus::Completed).await?,
+            failed: count(JobStatus::Failed).await?,
+            cancelled: count(JobStatus::Cancelled).await?,
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn library_stats(&self) -> anyhow::Result<LibraryStats> {
+        let (playlists, tracks) = tokio::try_join!(
+            self.playlist_repository.count_playlists(),
+            self.playlist_repository.count_tracks()
+        )?;
+        Ok(LibraryStats { playlists, tracks })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::PdfGenerator;
+    use crate::domain::SessionId;
+    use crate::fixtures;
+    use crate::infrastructure::playlist::MockPlaylistRepository;
+    use crate::infrastructure::spotify::MockSpotifyClient;
+    use crate::infrastructure::{EventStore, PlaylistRepository, test_pool};
+
+    fn spotify_id() -> SpotifyId {
+        SpotifyId::parse("37i9dQZF1DXcBWIGoYBM5M").unwrap()
+    }
+
+    fn user() -> UserContext {
+        UserContext::new(SessionId::new())
+    }
+
+    #[tokio::test]
+    async fn test_refresh_album_covers_batches_requests() {
+        let playlist = fixtures::playlist("Covers", fixtures::tracks(51));
+        let spotify_client =
+            playlist
+                .tracks
+                .iter()
+                .fold(MockSpotifyClient::new(), |client, track| {
+                    client.with_track(Track {
+                        album_cover_url: Some(format!("{}/cover", track.spotify_url)),
+                        ..track.clone()
+                    })
+                });
+        let (service, playlist_repository) =
+            fixtures::playlist_service(spotify_client.clone()).await;
+        let user = user();
+        playlist_repository
+            .create(&user.session_id, &playlist)
+            .await
+            .unwrap();
+
+        service
+            .refresh_album_covers(&user, &playlist.id)
+            .await
+            .unwrap();
+
+        assert_eq!(spotify_client.refresh_batches(), vec![50, 1]);
+        let stored = playlist_repository
+            .get(&user.session_id, &playlist.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.tracks.iter().all(|t| t.album_cover_url.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_create_from_spotify_dedups_tracks() {
+        let track = fixtures::track("twice");
+        let spotify_client = MockSpotifyClient::new().with_playlist(
+            spotify_id(),
+            Some(fixtures::playlist("Duplicates", vec![track.clone(), track])),
+        );
+        let (service, playlist_repository, _) = fixtures::playlist_service_with(
+            spotify_client,
+            MockPlaylistRepository::new(),
+            test_pool().await,
+            true,
+        )
+        .await;
+
+        let user = user();
+        let spotify_id = spotify_id();
+        let created = service
+            .create_from_spotify(&user, &spotify_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let stored = playlist_repository
+            .get(&user.session_id, &created.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tracks.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_generate_playlist_pdfs_requires_a_stored_playlist() {
+        let (service, playlist_repository) =
+            fixtures::playlist_service(MockSpotifyClient::new()).await;
+        let user = user();
+        let playlist = playlist_repository
+            .create(&user.session_id, &fixtures::playlist("Printable", vec![]))
+            .await
+            .unwrap();
+
+        let job = service
+            .generate_playlist_pdfs(&user, &playlist.id)
+            .await
+            .unwrap();
+        assert_eq!(job.playlist_id(), Some(playlist.id.clone()));
+
+        assert!(
+            service
+                .generate_playlist_pdfs(&user, &PlaylistId::new())
+                .await
+                .is_err()
+        );
+
+        playlist_repository.set_should_fail(true);
+        assert!(
+            service
+                .generate_playlist_pdfs(&user, &playlist.id)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_jobs_spans_playlists_and_filters_by_status() {
+        let (service, _, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let session_id = SessionId::new();
+        for (playlist_id, status) in [
+            (PlaylistId::new(), JobStatus::Completed),
+            (PlaylistId::new(), JobStatus::Failed),
+            (PlaylistId::new(), JobStatus::Completed),
+        ] {
+            let mut job = Job::new(
+                serde_json::to_value(worker::GeneratePlaylistPdfsTask::<
+                    PlaylistRepository,
+                    PdfGenerator,
+                    EventStore,
+                >::new(playlist_id, session_id.clone()))
+                .unwrap(),
+            );
+            job.status = status;
+            jobs_repository.create(job).await.unwrap();
+        }
+        let all = Pagination {
+            page: 1,
+            per_page: 10,
+        };
+
+        let page = service.list_jobs(&JobFilter::default(), all).await.unwrap();
+        assert_eq!(page.total, 3);
+        let playlists: std::collections::HashSet<_> =
+            page.jobs.iter().filter_map(Job::playlist_id).collect();
+        assert_eq!(playlists.len(), 3);
+        assert!(
+            page.jobs
+                .iter()
+                .all(|job| job.session_id() == Some(session_id.clone()))
+        );
+
+        let failed = service
+            .list_jobs(
+                &JobFilter {
+                    status: Some(JobStatus::Failed),
+                    ..Default::default()
+                },
+                all,
+            )
+            .await
+            .unwrap();
+        assert_eq!(failed.total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_from_spotify_rejects_oversized_playlist_before_fetching_tracks() {
+        let spotify_client = MockSpotifyClient::new().with_track_count(1001);
+        let (service, _) = fixtures::playlist_service(spotify_client.clone()).await;
+
+        let spotify_id = spotify_id();
+        let err = service
+            .create_from_spotify(&user(), &spotify_id)
+            .await
+            .unwrap_err();
+
+        let too_large = err.downcast_ref::<PlaylistTooLargeError>().unwrap();
+        assert_eq!(too_large.track_count, 1001);
+        assert_eq!(too_large.max_tracks, 1000);
+        assert_eq!(spotify_client.calls("get_playlist_with_tracks"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_get_their_own_copy_of_a_spotify_playlist() {
+        let spotify_client = MockSpotifyClient::new()
+            .with_playlist(spotify_id(), Some(fixtures::playlist("Shared", vec![])));
+        let (service, _) = fixtures::playlist_service(spotify_client).await;
+        let (alice, bob) = (user(), user());
+
+        let spotify_id = spotify_id();
+        let alices = service
+            .create_from_spotify(&alice, &spotify_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let bobs = service
+            .create_from_spotify(&bob, &spotify_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(alices.id, bobs.id);
+
+        let listed = service
+            .list_playlists(
+                &alice,
+                Pagination {
+                    page: 1,
+                    per_page: 10,
+                },
+                PlaylistOrder::Created,
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed.total, 1);
+        assert_eq!(listed.playlists[0].id, alices.id);
+        assert!(
+            service
+                .get_playlist(&alice, &bobs.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partial_import_stores_the_spotify_track_count() {
+        let spotify_client = |track_count| {
+            MockSpotifyClient::new()
+                .with_playlist(spotify_id(), Some(fixtures::playlist("Counted", vec![])))
+                .with_track_count(track_count)
+        };
+        let spotify_id = spotify_id();
+
+        let (counted, _, _) = fixtures::sqlite_playlist_service(spotify_client(7)).await;
+        let (playlist, _) = counted
+            .create_partial_playlist_from_spotify(&user(), &spotify_id)
+            .await
+            .unwrap();
+        assert_eq!(playlist.unwrap().spotify_track_count, Some(7));
+
+        let (too_large, _, _) = fixtures::sqlite_playlist_service(spotify_client(1001)).await;
+        let err = too_large
+            .create_partial_playlist_from_spotify(&user(), &spotify_id)
+            .await
+            .unwrap_err();
+        assert!(err.is::<PlaylistTooLargeError>());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_partial_imports_create_one_playlist() {
+        let spotify_client = MockSpotifyClient::new().with_playlist(
+            spotify_id(),
+            Some(fixtures::playlist("Double click", vec![])),
+        );
+        let (service, _, _) = fixtures::sqlite_playlist_service(spotify_client).await;
+        let user = user();
+
+        let spotify_id = spotify_id();
+        let (first, second) = tokio::join!(
+            service.create_partial_playlist_from_spotify(&user, &spotify_id),
+            service.create_partial_playlist_from_spotify(&user, &spotify_id),
+        );
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert_eq!(first.0.unwrap().id, second.0.unwrap().id);
+        assert_eq!(
+            [first.1, second.1]
+                .iter()
+                .filter(|job| job.is_some())
+                .count(),
+            1
+        );
+
+        let listed = service
+            .list_playlists(
+                &user,
+                Pagination {
+                    page: 1,
+                    per_page: 10,
+                },
+                PlaylistOrder::Created,
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed.total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_imports_create_one_playlist() {
+        let spotify_client = MockSpotifyClient::new().with_playlist(
+            spotify_id(),
+            Some(fixtures::playlist("Double submit", vec![])),
+        );
+        let (service, _, _) = fixtures::sqlite_playlist_service(spotify_client).await;
+        let user = user();
+
+        let spotify_id = spotify_id();
+        let (first, second) = tokio::join!(
+            service.create_from_spotify(&user, &spotify_id),
+            service.create_from_spotify(&user, &spotify_id),
+        );
+        assert_eq!(first.unwrap().unwrap().id, second.unwrap().unwrap().id);
+
+        let listed = service
+            .list_playlists(
+                &user,
+                Pagination {
+                    page: 1,
+                    per_page: 10,
+                },
+                PlaylistOrder::Created,
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed.total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_playlist_removes_jobs_and_pdfs() {
+        let (service, playlist_repository, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let user = user();
+        let playlist = playlist_repository
+            .create(&user.session_id, &fixtures::playlist("Doomed", vec![]))
+            .await
+            .unwrap();
+
+        let mut job = Job::new(
+            serde_json::to_value(worker::GeneratePlaylistPdfsTask::<
+                PlaylistRepository,
+                PdfGenerator,
+                EventStore,
+            >::new(
+                playlist.id.clone(), user.session_id.clone()
+            ))
+            .unwrap(),
+        );
+        job.status = JobStatus::Completed;
+        job.result = Some(
+            serde_json::to_value(GeneratePlaylistPdfsResult {
+                front_size: 5,
+                back_size: 4,
+            })
+            .unwrap(),
+        );
+        let job = jobs_repository.create(job).await.unwrap();
+        playlist_repository
+            .save_pdfs(
+                &job.id,
+                &[b"front".to_vec().into(), b"back".to_vec().into()],
+            )
+            .await
+            .unwrap();
+
+        // Other sessions cannot delete the playlist
+        assert!(
+            !service
+                .delete_playlist(&self::user(), &playlist.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            playlist_repository
+                .get_pdfs(&job.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        assert!(service.delete_playlist(&user, &playlist.id).await.unwrap());
+        assert!(
+            service
+                .get_playlist(&user, &playlist.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            jobs_repository
+                .get_by_playlist_id(&playlist.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            playlist_repository
+                .get_pdfs(&job.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_from_spotify_album() {
+        let spotify_client = MockSpotifyClient::new().with_album(
+            SpotifyId::parse_album("0ETFjACtuP2ADo6LFhL6HN").unwrap(),
+            Some(fixtures::playlist(
+                "Abbey Road",
+                (1..=12)
+                    .map(|i| Track {
+                        title: format!("Track {i}"),
+                        artist: "The Beatles".to_string(),
+                        year: 1969,
+                        album_cover_url: Some("https://i.scdn.co/image/abbey-road".to_string()),
+                        ..fixtures::track(i)
+                    })
+                    .collect(),
+            )),
+        );
+        let (service, playlist_repository) = fixtures::playlist_service(spotify_client).await;
+
+        let user = user();
+        let album_id = SpotifyId::parse_album("0ETFjACtuP2ADo6LFhL6HN").unwrap();
+        let created = service
+            .create_from_spotify_album(&user, &album_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let stored = playlist_repository
+            .get(&user.session_id, &created.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.name, "Abbey Road");
+        assert_eq!(stored.tracks.len(), 12);
+        assert_eq!(stored.tracks[11].title, "Track 12");
+        assert!(stored.tracks.iter().all(|t| t.year == 1969));
+    }
+
+    #[tokio::test]
+    async fn test_update_track_notes() {
+        let (service, playlist_repository) =
+            fixtures::playlist_service(MockSpotifyClient::new()).await;
+        let user = user();
+        let playlist = fixtures::playlist("Notes", fixtures::tracks(1));
+        playlist_repository
+            .create(&user.session_id, &playlist)
+            .await
+            .unwrap();
+
+        let note = Some("This was number 1 for 10 weeks".to_string());
+        service
+            .update_track_notes(&user, &playlist.id, 0, note.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        let stored = playlist_repository
+            .get(&user.session_id, &playlist.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tracks[0].notes, note);
+
+        let err = service
+            .update_track_notes(&user, &playlist.id, 0, Some("a".repeat(201)))
+            .await
+            .unwrap_err();
+        assert!(err.is::<crate::domain::TrackNotesError>());
+
+        let missing = service
+            .update_track_notes(&user, &playlist.id, 1, None)
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rename_playlist_keeps_the_spotify_name() {
+        let (service, playlist_repository, _) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let user = user();
+        let playlist = fixtures::playlist("Liked Songs", vec![]);
+        playlist_repository
+            .create(&user.session_id, &playlist)
+            .await
+            .unwrap();
+
+        let renamed = service
+            .rename_playlist(&user, &playlist.id, " Party Mix ")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(renamed.display_name(), "Party Mix");
+
+        let stored = playlist_repository
+            .get(&user.session_id, &playlist.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.name, "Liked Songs");
+        assert_eq!(stored.custom_name.as_deref(), Some("Party Mix"));
+
+        let err = service
+            .rename_playlist(&user, &playlist.id, "  ")
+            .await
+            .unwrap_err();
+        assert!(err.is::<crate::domain::PlaylistNameError>());
+
+        let missing = service
+            .rename_playlist(&user, &PlaylistId::new(), "Other")
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_latest_job_by_type() {
+        let (service, _, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let playlist_id = PlaylistId::new();
+        let job = |job_type: JobType| {
+            Job::new(serde_json::json!({
+                "type": job_type,
+                "playlist_id": playlist_id,
+            }))
+        };
+
+        let pdf_job = jobs_repository
+            .create(job(JobType::GeneratePlaylistPdfs))
+            .await
+            .unwrap();
+        let refetch_job = jobs_repository
+            .create(job(JobType::RefetchPlaylist))
+            .await
+            .unwrap();
+
+        let latest_pdf_job = service
+            .get_latest_job_by_type(&playlist_id, JobType::GeneratePlaylistPdfs)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest_pdf_job.id, pdf_job.id);
+
+        let latest_refetch_job = service
+            .get_latest_job_by_type(&playlist_id, JobType::RefetchPlaylist)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest_refetch_job.id, refetch_job.id);
+
+        let other_playlist = service
+            .get_latest_job_by_type(&PlaylistId::new(), JobType::RefetchPlaylist)
+            .await
+            .unwrap();
+        assert!(other_playlist.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_job() {
+        let (service, _, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let user = user();
+        let payload = serde_json::json!({
+            "type": JobType::RefetchPlaylist,
+            "session_id": user.session_id,
+        });
+        let pending = jobs_repository
+            .create(Job::new(payload.clone()))
+            .await
+            .unwrap();
+        let mut completed = Job::new(payload);
+        completed.status = JobStatus::Completed;
+        let completed = jobs_repository.create(completed).await.unwrap();
+
+        // Jobs of other sessions are left alone
+        let other = UserContext::new(SessionId::new());
+        assert!(!service.cancel_job(&other, &pending.id).await.unwrap());
+
+        assert!(service.cancel_job(&user, &pending.id).await.unwrap());
+        let pending = jobs_repository.get(&pending.id).await.unwrap().unwrap();
+        assert_eq!(pending.status, JobStatus::Cancelled);
+        assert!(pending.completed_at.is_some());
+
+        let err = service.cancel_job(&user, &completed.id).await.unwrap_err();
+        assert!(err.is::<JobAlreadyDoneError>());
+        let err = service.cancel_job(&user, &pending.id).await.unwrap_err();
+        assert!(err.is::<JobAlreadyDoneError>());
+        assert!(!service.cancel_job(&user, &JobId::new()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_job_stats_count_cancelled_jobs() {
+        let (service, _, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        for status in [
+            JobStatus::Pending,
+            JobStatus::Cancelled,
+            JobStatus::Cancelled,
+        ] {
+            let mut job = Job::new(serde_json::json!({ "type": JobType::RefetchPlaylist }));
+            job.status = status;
+            jobs_repository.create(job).await.unwrap();
+        }
+
+        let stats = service.job_stats().await.unwrap();
+        assert_eq!(
+            stats,
+            JobStats {
+                pending: 1,
+                cancelled: 2,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_job_by_id_only_returns_jobs_of_the_session() {
+        let (service, _, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let user = user();
+        let job = jobs_repository
+            .create(Job::new(serde_json::json!({
+                "type": JobType::RefetchPlaylist,
+                "session_id": user.session_id,
+            })))
+            .await
+            .unwrap();
+
+        let own = service.get_job_by_id(&user, &job.id).await.unwrap();
+        assert_eq!(own.map(|own| own.id), Some(job.id.clone()));
+        let other_session = UserContext::new(SessionId::new());
+        assert!(
+            service
+                .get_job_by_id(&other_session, &job.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_jobs_for_playlist_since_only_returns_recent_jobs_of_the_session() {
+        let (service, _, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let user = user();
+        let playlist_id = PlaylistId::new();
+        let job_at = |session_id: &SessionId, created_at| {
+            let mut job = Job::new(serde_json::json!({
+                "type": JobType::RefetchPlaylist,
+                "playlist_id": playlist_id,
+                "session_id": session_id,
+            }));
+            job.created_at = created_at;
+            job
+        };
+        let now = chrono::Utc::now();
+        jobs_repository
+            .create(job_at(&user.session_id, now - chrono::Duration::hours(1)))
+            .await
+            .unwrap();
+        let recent = jobs_repository
+            .create(job_at(&user.session_id, now))
+            .await
+            .unwrap();
+        jobs_repository
+            .create(job_at(&SessionId::new(), now))
+            .await
+            .unwrap();
+
+        let jobs = service
+            .get_jobs_for_playlist_since(&user, &playlist_id, now)
+            .await
+            .unwrap();
+        let ids: Vec<_> = jobs.into_iter().map(|job| job.id).collect();
+        assert_eq!(ids, [recent.id]);
+    }
+
+    #[tokio::test]
+    async fn test_get_jobs_for_playlist() {
+        let (service, playlist_repository, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let user = user();
+        let playlist = playlist_repository
+            .create(&user.session_id, &fixtures::playlist("History", vec![]))
+            .await
+            .unwrap();
+
+        let jobs = service
+            .get_jobs_for_playlist(&user, &playlist.id)
+            .await
+            .unwrap();
+        assert_eq!(jobs.map(|jobs| jobs.len()), Some(0));
+
+        for job_type in [JobType::RefetchPlaylist, JobType::GeneratePlaylistPdfs] {
+            jobs_repository
+                .create(Job::new(serde_json::json!({
+                    "type": job_type,
+                    "playlist_id": playlist.id,
+                })))
+                .await
+                .unwrap();
+        }
+        let jobs = service
+            .get_jobs_for_playlist(&user, &playlist.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(jobs.len(), 2);
+
+        // Other sessions do not see the playlist or its jobs
+        assert!(
+            service
+                .get_jobs_for_playlist(&self::user(), &playlist.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
     }
 }
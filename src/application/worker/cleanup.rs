@@ -0,0 +1,199 @@
+use super::{IWorkerTask, Worker};
+use crate::application::{IJobsRepository, IPlaylistRepository};
+use crate::domain::JobStatus;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// How often finished jobs past their retention are deleted
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How often jobs stuck in pending or processing are looked for
+const STALE_JOB_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Deletes finished jobs older than `retention` once a day, starting right away.
+///
+/// Pending and processing jobs are never deleted.
+pub fn spawn_job_cleanup<JR: IJobsRepository>(
+    jobs_repository: Arc<JR>,
+    retention: chrono::Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let cutoff = chrono::Utc::now() - retention;
+            match jobs_repository
+                .delete_older_than(cutoff, &[JobStatus::Pending, JobStatus::Processing])
+                .await
+            {
+                Ok(deleted) => info!("Deleted {} jobs completed before {}", deleted, cutoff),
+                Err(e) => error!("Failed to delete old jobs: {:#}", e),
+            }
+        }
+    })
+}
+
+/// Queues jobs of `WT` again that are still pending or processing `stale_after` after they were
+/// enqueued, every five minutes.
+///
+/// Such jobs were lost by their worker, for example when updating their status failed.
+/// Jobs the worker still owns are left alone, and each job is claimed in the repository
+/// first, so it is queued again at most once every `stale_after`.
+pub fn spawn_stale_job_requeue<PR, JR, WT>(
+    playlist_repository: Arc<PR>,
+    worker: Arc<Worker<JR, WT>>,
+    stale_after: chrono::Duration,
+) -> JoinHandle<()>
+where
+    PR: IPlaylistRepository,
+    JR: IJobsRepository,
+    WT: IWorkerTask,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(STALE_JOB_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match requeue_stale_jobs(playlist_repository.as_ref(), worker.as_ref(), stale_after)
+                .await
+            {
+                Ok(0) => {}
+                Ok(requeued) => info!("Requeued {} stale {:?} jobs", requeued, WT::JOB_TYPE),
+                Err(e) => error!("Failed to requeue stale jobs: {:#}", e),
+            }
+        }
+    })
+}
+
+/// Number of stale jobs of `WT` queued again, see [`spawn_stale_job_requeue`]
+async fn requeue_stale_jobs<PR, JR, WT>(
+    playlist_repository: &PR,
+    worker: &Worker<JR, WT>,
+    stale_after: chrono::Duration,
+) -> anyhow::Result<usize>
+where
+    PR: IPlaylistRepository,
+    JR: IJobsRepository,
+    WT: IWorkerTask,
+{
+    let cutoff = chrono::Utc::now() - stale_after;
+    let mut requeued = 0;
+    for playlist_id in playlist_repository
+        .get_playlists_with_stale_jobs(stale_after)
+        .await?
+    {
+        let jobs = playlist_repository
+            .get_jobs(&playlist_id)
+            .await?
+            .unwrap_or_default();
+        for job in jobs.into_iter().filter(|job| {
+            matches!(job.status, JobStatus::Pending | JobStatus::Processing)
+                && job.job_type() == Some(WT::JOB_TYPE)
+                && !worker.owns(&job.id)
+        }) {
+            if !worker.jobs_repository.claim_stale(&job.id, cutoff).await? {
+                continue;
+            }
+            let job_id = job.id.clone();
+            match worker.requeue(job) {
+                Ok(()) => requeued += 1,
+                Err(e) => warn!("Failed to requeue stale job {}: {:#}", job_id, e),
+            }
+        }
+    }
+    Ok(requeued)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Job, JobId, JobType, PlaylistId};
+    use crate::infrastructure::playlist::MockPlaylistRepository;
+    use crate::infrastructure::{JobsRepository, test_pool};
+    use serde::{Deserialize, Serialize};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Semaphore;
+
+    /// Lets tasks finish one permit at a time
+    #[derive(Clone)]
+    struct Gate {
+        runs: Arc<AtomicUsize>,
+        release: Arc<Semaphore>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct GatedTask {
+        playlist_id: PlaylistId,
+    }
+
+    impl IWorkerTask for GatedTask {
+        const JOB_TYPE: JobType = JobType::RefetchPlaylist;
+        type State = Gate;
+        type Output = ();
+
+        async fn run(&self, gate: &Gate, _job_id: &JobId) -> anyhow::Result<()> {
+            gate.runs.fetch_add(1, Ordering::SeqCst);
+            gate.release.acquire().await?.forget();
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stale_jobs_are_requeued_once() {
+        let jobs_repository = Arc::new(JobsRepository::new(test_pool().await));
+        let playlist_repository = MockPlaylistRepository::new();
+        let gate = Gate {
+            runs: Arc::new(AtomicUsize::new(0)),
+            release: Arc::new(Semaphore::new(0)),
+        };
+        let worker: Worker<_, GatedTask> =
+            Worker::new(jobs_repository.clone(), Arc::new(gate.clone()));
+        let stale_after = chrono::Duration::minutes(30);
+
+        // Left pending an hour ago by a worker that lost it
+        let mut job = Job::new(serde_json::json!({
+            "type": JobType::RefetchPlaylist,
+            "playlist_id": PlaylistId::new(),
+        }));
+        job.created_at = chrono::Utc::now() - chrono::Duration::hours(1);
+        let job = jobs_repository.create(job).await.unwrap();
+        playlist_repository.add_job(job.clone());
+
+        assert_eq!(
+            requeue_stale_jobs(&playlist_repository, &worker, stale_after)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(worker.owns(&job.id));
+
+        // Still running, so neither this worker nor another one queues it again
+        assert_eq!(
+            requeue_stale_jobs(&playlist_repository, &worker, stale_after)
+                .await
+                .unwrap(),
+            0
+        );
+        let other_worker: Worker<_, GatedTask> =
+            Worker::new(jobs_repository.clone(), Arc::new(gate.clone()));
+        assert_eq!(
+            requeue_stale_jobs(&playlist_repository, &other_worker, stale_after)
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(worker.requeue(job.clone()).is_err());
+
+        gate.release.add_permits(1);
+        for _ in 0..100 {
+            if !worker.owns(&job.id) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        let done = jobs_repository.get(&job.id).await.unwrap().unwrap();
+        assert_eq!(done.status, JobStatus::Completed);
+        assert_eq!(gate.runs.load(Ordering::SeqCst), 1);
+    }
+}
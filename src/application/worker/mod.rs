@@ -1,44 +1,117 @@
+mod cleanup;
 mod tasks;
+mod token_refresh;
 
+pub use cleanup::{spawn_job_cleanup, spawn_stale_job_requeue};
 pub use tasks::*;
+pub use token_refresh::spawn_spotify_token_refresh;
 
 use crate::application::interfaces::IJobsRepository;
-use crate::domain::job::Job;
+use crate::domain::job::{Job, JobId, JobStatus, JobType};
+use anyhow::Context;
+use futures_util::FutureExt;
 use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::HashSet;
 use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
-use tokio::sync::mpsc;
-use tokio::sync::mpsc::UnboundedSender;
-use tracing::{error, info};
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{Mutex, broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
 
 pub trait IWorkerTask: Serialize + for<'de> Deserialize<'de> + Send + 'static {
+    /// Recorded as `type` in the job payload
+    const JOB_TYPE: JobType;
     type State: Clone + Send + Sync;
     type Output: Serialize + for<'de> Deserialize<'de> + Send + Sync;
+    /// Times a failed job is run again before it is marked as failed
+    const MAX_RETRIES: u32 = 3;
 
-    fn run(&self, state: &Self::State)
-    -> impl Future<Output = anyhow::Result<Self::Output>> + Send;
+    /// Runs the task of the job `job_id`
+    fn run(
+        &self,
+        state: &Self::State,
+        job_id: &JobId,
+    ) -> impl Future<Output = anyhow::Result<Self::Output>> + Send;
 }
 pub trait IWorker: Send + Sync {
     type Task: IWorkerTask;
     fn enqueue(&self, task: Self::Task) -> impl Future<Output = Result<Job, anyhow::Error>> + Send;
 }
 
+/// Jobs a worker queues before rejecting new ones, unless configured otherwise
+pub const DEFAULT_QUEUE_CAPACITY: usize = 100;
+
+/// How many jobs a worker runs and queues, and how long it waits before retrying
+#[derive(Debug, Clone, Copy)]
+pub struct WorkerOptions {
+    /// Worker loops sharing one queue, each job is run by the first idle loop
+    pub concurrency: usize,
+    /// Enqueueing fails with [`WorkerFullError`] while this many jobs are waiting
+    pub capacity: usize,
+    /// Wait before the first retry of a failed job, doubled for every further retry
+    pub retry_base_delay: Duration,
+}
+
+impl Default for WorkerOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 1,
+            capacity: DEFAULT_QUEUE_CAPACITY,
+            retry_base_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+#[derive(Debug, displaydoc::Display, thiserror::Error)]
+/// Worker queue is full with {capacity} waiting jobs
+pub struct WorkerFullError {
+    pub capacity: usize,
+}
+
 pub struct Worker<JR: IJobsRepository, WT: IWorkerTask> {
     jobs_repository: Arc<JR>,
-    task_sender: UnboundedSender<(Job, WT)>,
+    task_sender: Sender<(Job, WT)>,
+    /// Tells the worker loops to stop taking jobs, see [`Worker::shutdown`]
+    shutdown: broadcast::Sender<()>,
+    loops: std::sync::Mutex<Vec<JoinHandle<()>>>,
+    /// Jobs queued, running or waiting for a retry in this worker, see [`Worker::owns`]
+    owned: Arc<std::sync::Mutex<HashSet<JobId>>>,
 }
 
 impl<JR: IJobsRepository, WT: IWorkerTask> IWorker for Worker<JR, WT> {
     type Task = WT;
 
     async fn enqueue(&self, task: Self::Task) -> Result<Job, anyhow::Error> {
-        let payload = serde_json::to_value(&task)?;
-        let job = Job::new(payload);
-        let job = self.jobs_repository.create(job).await?;
-
-        if let Err(e) = self.task_sender.send((job.clone(), task)) {
-            return Err(anyhow::anyhow!("Failed to send task to worker {e}"));
+        let mut payload = serde_json::to_value(&task)?;
+        if let Some(fields) = payload.as_object_mut() {
+            fields.insert("type".to_string(), serde_json::to_value(WT::JOB_TYPE)?);
         }
+        // Reserve a queue slot first so rejected tasks leave no job behind
+        let permit = match self.task_sender.try_reserve() {
+            Ok(permit) => permit,
+            Err(TrySendError::Full(())) => {
+                return Err(WorkerFullError {
+                    capacity: self.task_sender.max_capacity(),
+                }
+                .into());
+            }
+            Err(TrySendError::Closed(())) => {
+                return Err(anyhow::anyhow!("Failed to send task to worker, it stopped"));
+            }
+        };
+        let job = Job::new(payload);
+        let job = self
+            .jobs_repository
+            .create(job)
+            .await
+            .context("while enqueueing job")?;
+        self.owned.lock().unwrap().insert(job.id.clone());
+        permit.send((job.clone(), task));
 
         Ok(job)
     }
@@ -46,58 +119,714 @@ impl<JR: IJobsRepository, WT: IWorkerTask> IWorker for Worker<JR, WT> {
 
 impl<JR: IJobsRepository, WT: IWorkerTask> Worker<JR, WT> {
     pub fn new(jobs_repository: Arc<JR>, state: Arc<WT::State>) -> Self {
-        let (task_sender, mut task_receiver) = mpsc::unbounded_channel::<(Job, WT)>();
+        Self::with_options(jobs_repository, state, WorkerOptions::default())
+    }
 
-        let _state = state.clone();
-        let _jobs_repository = jobs_repository.clone();
-        tokio::spawn(async move {
-            let state = _state;
-            let jobs_repository = _jobs_repository;
-
-            while let Some((mut job, task)) = task_receiver.recv().await {
-                job.status = crate::domain::JobStatus::Processing;
-                if let Err(e) = jobs_repository.update(job.clone()).await {
-                    error!("Failed to update job status to processing: {:?}", e);
-                    continue;
-                }
+    pub fn with_options(
+        jobs_repository: Arc<JR>,
+        state: Arc<WT::State>,
+        options: WorkerOptions,
+    ) -> Self {
+        let (task_sender, task_receiver) = mpsc::channel::<(Job, WT)>(options.capacity.max(1));
+        let task_receiver = Arc::new(Mutex::new(task_receiver));
+        let (shutdown, _) = broadcast::channel(1);
+        let owned = Arc::new(std::sync::Mutex::new(HashSet::new()));
 
-                // Run the task
-                let started_at = chrono::Utc::now();
-                let result = task.run(&state).await;
-                let ended_at = chrono::Utc::now();
-                let diff = ended_at - started_at;
-                info!("Task finished after {} ms", diff.num_milliseconds());
-
-                match result {
-                    Ok(output) => {
-                        job.status = crate::domain::JobStatus::Completed;
-                        job.completed_at = Some(chrono::Utc::now());
-                        match serde_json::to_value(output) {
-                            Ok(output_value) => job.result = Some(output_value),
-                            Err(e) => {
-                                error!("Failed to serialize task output: {:?}", e);
-                                job.status = crate::domain::JobStatus::Failed;
-                            }
-                        }
-                        if let Err(e) = jobs_repository.update(job.clone()).await {
-                            error!("Failed to update completed job: {:?}", e);
-                        }
-                    }
-                    Err(e) => {
-                        error!("Task failed to complete: {:?}", e);
-                        job.status = crate::domain::JobStatus::Failed;
-                        job.completed_at = Some(chrono::Utc::now());
-                        if let Err(e) = jobs_repository.update(job.clone()).await {
-                            error!("Failed to update failed job: {:?}", e);
+        let mut loops = Vec::new();
+        for _ in 0..options.concurrency.max(1) {
+            let mut shutdown_receiver = shutdown.subscribe();
+            let state = state.clone();
+            let jobs_repository = jobs_repository.clone();
+            let task_receiver = task_receiver.clone();
+            let owned = owned.clone();
+            // Weak, so the queue still closes once the worker is dropped
+            let retry_sender = task_sender.downgrade();
+            loops.push(tokio::spawn(async move {
+                loop {
+                    // The lock is only held while waiting, so other loops can take the next job.
+                    // A running job is never interrupted, the signal is only seen between jobs.
+                    let next = tokio::select! {
+                        biased;
+                        _ = shutdown_receiver.recv() => break,
+                        next = async { task_receiver.lock().await.recv().await } => next,
+                    };
+                    let Some((job, task)) = next else {
+                        break;
+                    };
+                    let job_id = job.id.clone();
+                    let Some((job, task)) =
+                        run_job(jobs_repository.as_ref(), state.as_ref(), job, task).await
+                    else {
+                        owned.lock().unwrap().remove(&job_id);
+                        continue;
+                    };
+
+                    // Requeued after the delay so the loop can run other jobs meanwhile
+                    let delay = retry_delay(options.retry_base_delay, job.attempt() - 1);
+                    let Some(retry_sender) = retry_sender.upgrade() else {
+                        break;
+                    };
+                    let owned = owned.clone();
+                    tokio::spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        if retry_sender.send((job, task)).await.is_err() {
+                            error!("Failed to queue retry of job {}", job_id);
+                            owned.lock().unwrap().remove(&job_id);
                         }
-                    }
+                    });
                 }
-            }
-        });
+            }));
+        }
 
         Self {
             jobs_repository,
             task_sender,
+            shutdown,
+            loops: std::sync::Mutex::new(loops),
+            owned,
+        }
+    }
+
+    /// Whether the job is queued, running or waiting for a retry in this worker
+    pub fn owns(&self, job_id: &JobId) -> bool {
+        self.owned.lock().unwrap().contains(job_id)
+    }
+
+    /// Stops taking jobs and waits for the jobs already running to finish.
+    ///
+    /// Queued jobs stay pending in the repository and are resumed on the next start
+    /// with [`load_pending_tasks`].
+    pub async fn shutdown(&self) {
+        // Fails when every loop has already stopped, which is fine
+        let _ = self.shutdown.send(());
+        let loops = std::mem::take(&mut *self.loops.lock().unwrap());
+        for handle in loops {
+            if let Err(e) = handle.await {
+                error!("Worker loop stopped abnormally: {}", e);
+            }
+        }
+    }
+
+    /// Queues an existing job of `WT` again, as when it was left pending or processing.
+    ///
+    /// Fails when the worker already owns the job, its payload does not deserialize
+    /// into a task or the queue is full.
+    pub fn requeue(&self, job: Job) -> anyhow::Result<()> {
+        let task = serde_json::from_value::<WT>(job.payload.clone())
+            .with_context(|| format!("while restoring task of job {}", job.id))?;
+        let mut owned = self.owned.lock().unwrap();
+        if owned.contains(&job.id) {
+            anyhow::bail!("Job {} is already queued or running", job.id);
+        }
+        let job_id = job.id.clone();
+        self.task_sender
+            .try_send((job, task))
+            .map_err(|e| match e {
+                TrySendError::Full(_) => anyhow::Error::from(WorkerFullError {
+                    capacity: self.task_sender.max_capacity(),
+                }),
+                TrySendError::Closed(_) => {
+                    anyhow::anyhow!("Failed to send task to worker, it stopped")
+                }
+            })?;
+        owned.insert(job_id);
+        Ok(())
+    }
+
+    /// Queues jobs restored with [`load_pending_tasks`].
+    ///
+    /// They are queued in the background as the queue has room, so restored jobs
+    /// beyond its capacity do not block startup.
+    pub fn with_pending(self, pending: Vec<(Job, WT)>) -> Self {
+        if pending.is_empty() {
+            return self;
+        }
+        info!("Resuming {} pending {:?} jobs", pending.len(), WT::JOB_TYPE);
+        self.owned
+            .lock()
+            .unwrap()
+            .extend(pending.iter().map(|(job, _)| job.id.clone()));
+        let task_sender = self.task_sender.clone();
+        tokio::spawn(async move {
+            for (job, task) in pending {
+                let job_id = job.id.clone();
+                if task_sender.send((job, task)).await.is_err() {
+                    error!("Failed to queue resumed job {}", job_id);
+                }
+            }
+        });
+        self
+    }
+}
+
+/// Pending jobs of `WT` with their tasks, so jobs left over by a previous run can be replayed.
+///
+/// Jobs whose payload no longer deserializes into a task are marked as failed.
+pub async fn load_pending_tasks<JR: IJobsRepository, WT: IWorkerTask>(
+    jobs_repository: &JR,
+) -> anyhow::Result<Vec<(Job, WT)>> {
+    let mut pending = Vec::new();
+    for mut job in jobs_repository.get_pending_by_type(WT::JOB_TYPE).await? {
+        match serde_json::from_value::<WT>(job.payload.clone()) {
+            Ok(task) => pending.push((job, task)),
+            Err(e) => {
+                error!("Failed to restore task of job {}: {}", job.id, e);
+                job.status = crate::domain::JobStatus::Failed;
+                job.error_message = Some(format!("Failed to restore task: {e}"));
+                job.completed_at = Some(chrono::Utc::now());
+                jobs_repository
+                    .update(job)
+                    .await
+                    .context("while marking unrestorable job as failed")?;
+            }
+        }
+    }
+    Ok(pending)
+}
+
+/// `2^retries * base`, the wait before retrying a job that was already retried `retries` times
+fn retry_delay(base: Duration, retries: u32) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(retries))
+}
+
+/// Runs a single task and records its outcome on the job.
+///
+/// Status changes only apply while the job is still in the status the worker left it
+/// in, so a job cancelled with [`IPlaylistService::cancel_job`] is skipped, and its
+/// outcome discarded once it was cancelled while running.
+///
+/// Returns the job and task when the task failed and should be retried.
+///
+/// [`IPlaylistService::cancel_job`]: crate::application::playlist_service::IPlaylistService::cancel_job
+async fn run_job<JR: IJobsRepository, WT: IWorkerTask>(
+    jobs_repository: &JR,
+    state: &WT::State,
+    mut job: Job,
+    task: WT,
+) -> Option<(Job, WT)> {
+    job.status = JobStatus::Processing;
+    match jobs_repository
+        .update_if_status(job.clone(), &[JobStatus::Pending, JobStatus::Retrying])
+        .await
+        .with_context(|| format!("while marking job {} as processing", job.id))
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            info!("Skipping job {}, it was cancelled", job.id);
+            return None;
+        }
+        Err(e) => {
+            error!("Failed to update job status to processing: {:?}", e);
+            return None;
         }
     }
+
+    // Run the task
+    let started_at = chrono::Utc::now();
+    // A panicking task must not take the worker loop down with it
+    let result = match AssertUnwindSafe(task.run(state, &job.id))
+        .catch_unwind()
+        .await
+    {
+        Ok(result) => result,
+        Err(panic) => {
+            crate::metrics::WORKER_PANICS_TOTAL.inc();
+            Err(anyhow::anyhow!(
+                "Worker panicked: {}",
+                panic_message(&*panic)
+            ))
+        }
+    };
+    let ended_at = chrono::Utc::now();
+    let diff = ended_at - started_at;
+    info!("Task finished after {} ms", diff.num_milliseconds());
+
+    match result {
+        Ok(output) => {
+            job.status = JobStatus::Completed;
+            job.completed_at = Some(chrono::Utc::now());
+            match serde_json::to_value(output) {
+                Ok(output_value) => job.result = Some(output_value),
+                Err(e) => {
+                    error!("Failed to serialize task output: {:?}", e);
+                    job.status = JobStatus::Failed;
+                }
+            }
+            if let Err(e) = finish(jobs_repository, job.clone())
+                .await
+                .with_context(|| format!("while marking job {} as completed", job.id))
+            {
+                error!("Failed to update completed job: {:?}", e);
+            }
+            None
+        }
+        Err(e) if job.attempt() < WT::MAX_RETRIES => {
+            job.set_attempt(job.attempt() + 1);
+            warn!(
+                "Task failed, retrying ({}/{}): {:?}",
+                job.attempt(),
+                WT::MAX_RETRIES,
+                e
+            );
+            job.status = JobStatus::Retrying;
+            // With the context chain, the outermost context alone rarely says what went wrong
+            job.error_message = Some(format!("{e:#}"));
+            match finish(jobs_repository, job.clone())
+                .await
+                .with_context(|| format!("while marking job {} as retrying", job.id))
+            {
+                Ok(true) => Some((job, task)),
+                Ok(false) => None,
+                Err(e) => {
+                    error!("Failed to update retrying job: {:?}", e);
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            error!("Task failed to complete: {:?}", e);
+            job.status = JobStatus::Failed;
+            job.error_message = Some(format!("{e:#}"));
+            job.completed_at = Some(chrono::Utc::now());
+            if let Err(e) = finish(jobs_repository, job.clone())
+                .await
+                .with_context(|| format!("while marking job {} as failed", job.id))
+            {
+                error!("Failed to update failed job: {:?}", e);
+            }
+            None
+        }
+    }
+}
+
+/// Records the outcome of a running job, returning `false` without touching it when it
+/// was cancelled while running
+async fn finish<JR: IJobsRepository>(jobs_repository: &JR, job: Job) -> anyhow::Result<bool> {
+    let job_id = job.id.clone();
+    let finished = jobs_repository
+        .update_if_status(job, &[JobStatus::Processing])
+        .await?;
+    if !finished {
+        info!("Discarding outcome of job {}, it was cancelled", job_id);
+    }
+    Ok(finished)
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{IPdfGenerator, PdfGeneratorConfig};
+    use crate::domain::{JobStatus, Playlist, PlaylistId};
+    use crate::fixtures::{self, StoredPlaylist};
+    use crate::infrastructure::{EventStore, JobsRepository, PlaylistRepository};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Clone)]
+    struct MockPdfGenerator;
+
+    impl MockPdfGenerator {
+        fn that_panics() -> Self {
+            Self
+        }
+    }
+
+    impl IPdfGenerator for MockPdfGenerator {
+        async fn generate_front_cards(
+            &self,
+            _playlist: &Playlist,
+            _config: &PdfGeneratorConfig,
+        ) -> anyhow::Result<Vec<u8>> {
+            panic!("front cards exploded");
+        }
+
+        async fn generate_back_cards(
+            &self,
+            _playlist: &Playlist,
+            _config: &PdfGeneratorConfig,
+        ) -> anyhow::Result<Vec<u8>> {
+            panic!("back cards exploded");
+        }
+    }
+
+    /// Takes `delay` to generate the front cards, recording how many run at once
+    #[derive(Clone)]
+    struct SlowPdfGenerator {
+        delay: std::time::Duration,
+        running: Arc<AtomicUsize>,
+        max_running: Arc<AtomicUsize>,
+    }
+
+    impl IPdfGenerator for SlowPdfGenerator {
+        async fn generate_front_cards(
+            &self,
+            _playlist: &Playlist,
+            _config: &PdfGeneratorConfig,
+        ) -> anyhow::Result<Vec<u8>> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(Vec::new())
+        }
+
+        async fn generate_back_cards(
+            &self,
+            _playlist: &Playlist,
+            _config: &PdfGeneratorConfig,
+        ) -> anyhow::Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Fails the first `failures` front card generations
+    #[derive(Clone)]
+    struct FlakyPdfGenerator {
+        failures: usize,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl IPdfGenerator for FlakyPdfGenerator {
+        async fn generate_front_cards(
+            &self,
+            _playlist: &Playlist,
+            _config: &PdfGeneratorConfig,
+        ) -> anyhow::Result<Vec<u8>> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                anyhow::bail!("printer on fire");
+            }
+            Ok(Vec::new())
+        }
+
+        async fn generate_back_cards(
+            &self,
+            _playlist: &Playlist,
+            _config: &PdfGeneratorConfig,
+        ) -> anyhow::Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn no_retry_delay() -> WorkerOptions {
+        WorkerOptions {
+            retry_base_delay: Duration::ZERO,
+            ..Default::default()
+        }
+    }
+
+    async fn wait_until_done(jobs_repository: &JobsRepository, job: &Job) -> Job {
+        for _ in 0..100 {
+            let job = jobs_repository.get(&job.id).await.unwrap().unwrap();
+            if job.status.is_done() {
+                return job;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("job {} did not finish in time", job.id);
+    }
+
+    #[tokio::test]
+    async fn test_worker_survives_panicking_task() {
+        let StoredPlaylist {
+            jobs_repository,
+            state,
+            playlist,
+            session_id,
+        } = fixtures::stored_playlist("Panics", MockPdfGenerator::that_panics()).await;
+        let worker: Worker<
+            _,
+            GeneratePlaylistPdfsTask<PlaylistRepository, MockPdfGenerator, EventStore>,
+        > = Worker::with_options(jobs_repository.clone(), state, no_retry_delay());
+        let panics_before = crate::metrics::WORKER_PANICS_TOTAL.get();
+
+        let panicked = worker
+            .enqueue(GeneratePlaylistPdfsTask::new(
+                playlist.id,
+                session_id.clone(),
+            ))
+            .await
+            .unwrap();
+        let panicked = wait_until_done(&jobs_repository, &panicked).await;
+        assert_eq!(panicked.status, JobStatus::Failed);
+        assert_eq!(panicked.job_type(), Some(JobType::GeneratePlaylistPdfs));
+        assert_eq!(panicked.attempt(), 3);
+        assert!(
+            panicked
+                .error_message
+                .unwrap()
+                .starts_with("Worker panicked: ")
+        );
+        assert!(crate::metrics::WORKER_PANICS_TOTAL.get() > panics_before);
+
+        // The worker keeps processing jobs after the panic
+        let next = worker
+            .enqueue(GeneratePlaylistPdfsTask::new(PlaylistId::new(), session_id))
+            .await
+            .unwrap();
+        let next = wait_until_done(&jobs_repository, &next).await;
+        assert_eq!(next.status, JobStatus::Failed);
+        assert!(next.error_message.unwrap().contains("playlist not found"));
+    }
+
+    #[tokio::test]
+    async fn test_workers_run_jobs_in_parallel() {
+        let delay = std::time::Duration::from_millis(300);
+        let pdf_generator = SlowPdfGenerator {
+            delay,
+            running: Arc::new(AtomicUsize::new(0)),
+            max_running: Arc::new(AtomicUsize::new(0)),
+        };
+        let StoredPlaylist {
+            jobs_repository,
+            state,
+            playlist,
+            session_id,
+        } = fixtures::stored_playlist("Parallel", pdf_generator.clone()).await;
+        let pdf_worker_count = 2;
+        let worker: Worker<
+            _,
+            GeneratePlaylistPdfsTask<PlaylistRepository, SlowPdfGenerator, EventStore>,
+        > = Worker::with_options(
+            jobs_repository.clone(),
+            state,
+            WorkerOptions {
+                concurrency: pdf_worker_count,
+                ..Default::default()
+            },
+        );
+
+        let started = std::time::Instant::now();
+        let mut jobs = Vec::new();
+        for _ in 0..3 {
+            let task = GeneratePlaylistPdfsTask::new(playlist.id.clone(), session_id.clone());
+            jobs.push(worker.enqueue(task).await.unwrap());
+        }
+        for job in &jobs {
+            let job = wait_until_done(&jobs_repository, job).await;
+            assert_eq!(job.status, JobStatus::Completed);
+        }
+
+        // Two rounds of two workers, where one worker would need three
+        assert!(started.elapsed() < delay * 3);
+        assert_eq!(pdf_generator.max_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_worker_resumes_pending_jobs() {
+        let StoredPlaylist {
+            jobs_repository,
+            state,
+            playlist,
+            session_id,
+        } = fixtures::stored_playlist(
+            "Restarted",
+            SlowPdfGenerator {
+                delay: std::time::Duration::ZERO,
+                running: Arc::new(AtomicUsize::new(0)),
+                max_running: Arc::new(AtomicUsize::new(0)),
+            },
+        )
+        .await;
+
+        // Left behind by a previous run, as enqueue would have stored it
+        let mut payload = serde_json::to_value(GeneratePlaylistPdfsTask::<
+            PlaylistRepository,
+            SlowPdfGenerator,
+            EventStore,
+        >::new(playlist.id, session_id))
+        .unwrap();
+        payload["type"] = serde_json::to_value(JobType::GeneratePlaylistPdfs).unwrap();
+        let left_over = jobs_repository.create(Job::new(payload)).await.unwrap();
+        let mut broken = Job::new(serde_json::json!({"type": JobType::GeneratePlaylistPdfs}));
+        broken.status = JobStatus::Processing;
+        let broken = jobs_repository.create(broken).await.unwrap();
+
+        assert_eq!(jobs_repository.reset_processing().await.unwrap(), 1);
+        let pending = load_pending_tasks::<
+            _,
+            GeneratePlaylistPdfsTask<PlaylistRepository, SlowPdfGenerator, EventStore>,
+        >(jobs_repository.as_ref())
+        .await
+        .unwrap();
+        assert_eq!(pending.len(), 1);
+        let broken = jobs_repository.get(&broken.id).await.unwrap().unwrap();
+        assert_eq!(broken.status, JobStatus::Failed);
+
+        let _worker: Worker<
+            _,
+            GeneratePlaylistPdfsTask<PlaylistRepository, SlowPdfGenerator, EventStore>,
+        > = Worker::new(jobs_repository.clone(), state).with_pending(pending);
+
+        let left_over = wait_until_done(&jobs_repository, &left_over).await;
+        assert_eq!(left_over.status, JobStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_rejects_tasks_when_queue_is_full() {
+        let pdf_generator = SlowPdfGenerator {
+            delay: std::time::Duration::from_millis(300),
+            running: Arc::new(AtomicUsize::new(0)),
+            max_running: Arc::new(AtomicUsize::new(0)),
+        };
+        let StoredPlaylist {
+            jobs_repository,
+            state,
+            playlist,
+            session_id,
+        } = fixtures::stored_playlist("Busy", pdf_generator.clone()).await;
+        let worker: Worker<
+            _,
+            GeneratePlaylistPdfsTask<PlaylistRepository, SlowPdfGenerator, EventStore>,
+        > = Worker::with_options(
+            jobs_repository.clone(),
+            state,
+            WorkerOptions {
+                capacity: 1,
+                ..Default::default()
+            },
+        );
+        let task = || GeneratePlaylistPdfsTask::new(playlist.id.clone(), session_id.clone());
+
+        let running = worker.enqueue(task()).await.unwrap();
+        while pdf_generator.running.load(Ordering::SeqCst) == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let queued = worker.enqueue(task()).await.unwrap();
+
+        let err = worker.enqueue(task()).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<WorkerFullError>().unwrap().capacity, 1);
+        assert_eq!(jobs_repository.get_pending_count().await.unwrap(), 1);
+
+        for job in [running, queued] {
+            let job = wait_until_done(&jobs_repository, &job).await;
+            assert_eq!(job.status, JobStatus::Completed);
+        }
+        assert!(worker.enqueue(task()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_finishes_running_job_and_leaves_queued_pending() {
+        let pdf_generator = SlowPdfGenerator {
+            delay: std::time::Duration::from_millis(200),
+            running: Arc::new(AtomicUsize::new(0)),
+            max_running: Arc::new(AtomicUsize::new(0)),
+        };
+        let StoredPlaylist {
+            jobs_repository,
+            state,
+            playlist,
+            session_id,
+        } = fixtures::stored_playlist("Shutting down", pdf_generator.clone()).await;
+        let worker: Worker<
+            _,
+            GeneratePlaylistPdfsTask<PlaylistRepository, SlowPdfGenerator, EventStore>,
+        > = Worker::new(jobs_repository.clone(), state);
+        let task = || GeneratePlaylistPdfsTask::new(playlist.id.clone(), session_id.clone());
+
+        let running = worker.enqueue(task()).await.unwrap();
+        while pdf_generator.running.load(Ordering::SeqCst) == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let queued = worker.enqueue(task()).await.unwrap();
+
+        worker.shutdown().await;
+
+        let running = jobs_repository.get(&running.id).await.unwrap().unwrap();
+        assert_eq!(running.status, JobStatus::Completed);
+        let queued = jobs_repository.get(&queued.id).await.unwrap().unwrap();
+        assert_eq!(queued.status, JobStatus::Pending);
+    }
+
+    async fn run_flaky_job(failures: usize) -> (Job, usize) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let StoredPlaylist {
+            jobs_repository,
+            state,
+            playlist,
+            session_id,
+        } = fixtures::stored_playlist(
+            "Flaky",
+            FlakyPdfGenerator {
+                failures,
+                calls: calls.clone(),
+            },
+        )
+        .await;
+        let worker: Worker<
+            _,
+            GeneratePlaylistPdfsTask<PlaylistRepository, FlakyPdfGenerator, EventStore>,
+        > = Worker::with_options(jobs_repository.clone(), state, no_retry_delay());
+
+        let job = worker
+            .enqueue(GeneratePlaylistPdfsTask::new(playlist.id, session_id))
+            .await
+            .unwrap();
+        let job = wait_until_done(&jobs_repository, &job).await;
+        (job, calls.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_failed_jobs_are_retried() {
+        let (job, calls) = run_flaky_job(2).await;
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.attempt(), 2);
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_fail_after_max_retries() {
+        let (job, calls) = run_flaky_job(usize::MAX).await;
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.attempt(), 3);
+        assert_eq!(job.error_message.as_deref(), Some("printer on fire"));
+        assert_eq!(calls, 4);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_jobs_are_skipped() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let StoredPlaylist {
+            jobs_repository,
+            state,
+            playlist,
+            session_id,
+        } = fixtures::stored_playlist(
+            "Cancelled",
+            FlakyPdfGenerator {
+                failures: 0,
+                calls: calls.clone(),
+            },
+        )
+        .await;
+        let task = GeneratePlaylistPdfsTask::new(playlist.id, session_id);
+        let mut job = Job::new(serde_json::to_value(&task).unwrap());
+        job.status = JobStatus::Cancelled;
+        let job = jobs_repository.create(job).await.unwrap();
+
+        assert!(
+            run_job(jobs_repository.as_ref(), state.as_ref(), job.clone(), task)
+                .await
+                .is_none()
+        );
+
+        let job = jobs_repository.get(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Cancelled);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        let base = Duration::from_millis(100);
+        assert_eq!(retry_delay(base, 0), Duration::from_millis(100));
+        assert_eq!(retry_delay(base, 2), Duration::from_millis(400));
+    }
 }
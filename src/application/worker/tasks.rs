@@ -1,116 +1,210 @@
 use crate::application::worker::IWorkerTask;
-use crate::application::{IPdfGenerator, IPlaylistRepository, ISpotifyClient};
-use crate::domain::PlaylistId;
+use crate::application::{
+    FetchProgress, IEventStore, IJobsRepository, IPdfGenerator, IPlaylistRepository,
+    ISpotifyClient, PartialFetchError, PdfGeneratorConfig,
+};
+use crate::domain::{
+    DomainEvent, JobId, JobType, PlaylistDomainService, PlaylistId, SessionId, Track,
+};
 use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{error, warn};
+
+/// How often a running refetch stores how many tracks it fetched
+const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_millis(500);
 
 #[derive(Serialize, Deserialize)]
-pub struct GeneratePlaylistPdfsTask<PR: IPlaylistRepository, PG: IPdfGenerator> {
+pub struct GeneratePlaylistPdfsTask<PR: IPlaylistRepository, PG: IPdfGenerator, ES: IEventStore> {
     pub playlist_id: PlaylistId,
-    _marker: std::marker::PhantomData<(PR, PG)>,
+    /// Session owning the playlist
+    pub session_id: SessionId,
+    _marker: std::marker::PhantomData<(PR, PG, ES)>,
 }
 
-impl<PR: IPlaylistRepository, PG: IPdfGenerator> GeneratePlaylistPdfsTask<PR, PG> {
-    pub fn new(playlist_id: PlaylistId) -> Self {
+impl<PR: IPlaylistRepository, PG: IPdfGenerator, ES: IEventStore>
+    GeneratePlaylistPdfsTask<PR, PG, ES>
+{
+    pub fn new(playlist_id: PlaylistId, session_id: SessionId) -> Self {
         Self {
             playlist_id,
+            session_id,
             _marker: std::marker::PhantomData,
         }
     }
 }
 
-pub struct GeneratePlaylistPdfsState<PR: IPlaylistRepository, PG: IPdfGenerator> {
+pub struct GeneratePlaylistPdfsState<PR: IPlaylistRepository, PG: IPdfGenerator, ES: IEventStore> {
     pub playlist_repository: Arc<PR>,
     pub pdf_generator: Arc<PG>,
+    pub event_store: Arc<ES>,
+    pub pdf_config: PdfGeneratorConfig,
 }
 
-impl<PR: IPlaylistRepository, PG: IPdfGenerator> Clone for GeneratePlaylistPdfsState<PR, PG> {
+impl<PR: IPlaylistRepository, PG: IPdfGenerator, ES: IEventStore> Clone
+    for GeneratePlaylistPdfsState<PR, PG, ES>
+{
     fn clone(&self) -> Self {
         Self {
             playlist_repository: self.playlist_repository.clone(),
             pdf_generator: self.pdf_generator.clone(),
+            event_store: self.event_store.clone(),
+            pdf_config: self.pdf_config,
         }
     }
 }
 
+/// The PDFs themselves are stored with the job, see [`IPlaylistRepository::get_pdfs`]
 #[derive(Serialize, Deserialize)]
 pub struct GeneratePlaylistPdfsResult {
-    pub front: PathBuf,
-    pub back: PathBuf,
+    /// Size of the front cards PDF in bytes
+    pub front_size: usize,
+    /// Size of the back cards PDF in bytes
+    pub back_size: usize,
 }
-impl<PR: IPlaylistRepository, PG: IPdfGenerator> IWorkerTask for GeneratePlaylistPdfsTask<PR, PG> {
-    type State = GeneratePlaylistPdfsState<PR, PG>;
+impl<PR: IPlaylistRepository, PG: IPdfGenerator, ES: IEventStore> IWorkerTask
+    for GeneratePlaylistPdfsTask<PR, PG, ES>
+{
+    const JOB_TYPE: JobType = JobType::GeneratePlaylistPdfs;
+    type State = GeneratePlaylistPdfsState<PR, PG, ES>;
     type Output = GeneratePlaylistPdfsResult;
 
-    async fn run(&self, state: &Self::State) -> anyhow::Result<GeneratePlaylistPdfsResult> {
+    async fn run(
+        &self,
+        state: &Self::State,
+        job_id: &JobId,
+    ) -> anyhow::Result<GeneratePlaylistPdfsResult> {
+        let started = Instant::now();
         let playlist = state
             .playlist_repository
-            .get(&self.playlist_id)
+            .get(&self.session_id, &self.playlist_id)
             .await?
             .ok_or(anyhow!("playlist not found for id: {}", &self.playlist_id))?;
 
-        let front_pdf_data_fut = state.pdf_generator.generate_front_cards(&playlist);
-        let back_pdf_data_fut = state.pdf_generator.generate_back_cards(&playlist);
+        let front_pdf_data_fut = state
+            .pdf_generator
+            .generate_front_cards(&playlist, &state.pdf_config);
+        let back_pdf_data_fut = state
+            .pdf_generator
+            .generate_back_cards(&playlist, &state.pdf_config);
         let (front_pdf_data, back_pdf_data) =
             tokio::try_join!(front_pdf_data_fut, back_pdf_data_fut)?;
 
-        // Create output directory if it doesn't exist
-        let output_dir = std::path::PathBuf::from("generated_pdfs");
-        tokio::fs::create_dir_all(&output_dir).await?;
-
-        let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-        let base_filename = format!("{}_{}", playlist.id, timestamp);
-
-        let front_path = output_dir.join(format!("{}_front.pdf", base_filename));
-        let back_path = output_dir.join(format!("{}_back.pdf", base_filename));
-
-        tokio::fs::write(&front_path, front_pdf_data).await?;
-        tokio::fs::write(&back_path, back_pdf_data).await?;
+        let result = GeneratePlaylistPdfsResult {
+            front_size: front_pdf_data.len(),
+            back_size: back_pdf_data.len(),
+        };
+        state
+            .playlist_repository
+            .save_pdfs(job_id, &[front_pdf_data.into(), back_pdf_data.into()])
+            .await?;
+        state
+            .event_store
+            .record(DomainEvent::PdfGenerationCompleted {
+                playlist_id: playlist.id,
+                job_id: job_id.clone(),
+                duration_ms: started.elapsed().as_millis() as u64,
+            })
+            .await;
 
-        Ok(GeneratePlaylistPdfsResult {
-            front: front_path,
-            back: back_path,
-        })
+        Ok(result)
     }
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct RefetchPlaylistTask<PR: IPlaylistRepository, SC: ISpotifyClient> {
+pub struct RefetchPlaylistTask<
+    PR: IPlaylistRepository,
+    SC: ISpotifyClient,
+    ES: IEventStore,
+    JR: IJobsRepository,
+> {
     pub playlist_id: PlaylistId,
-    _marker: std::marker::PhantomData<(PR, SC)>,
+    /// Session owning the playlist
+    pub session_id: SessionId,
+    _marker: std::marker::PhantomData<(PR, SC, ES, JR)>,
 }
 
-impl<PR: IPlaylistRepository, SC: ISpotifyClient> RefetchPlaylistTask<PR, SC> {
-    pub fn new(playlist_id: PlaylistId) -> Self {
+impl<PR: IPlaylistRepository, SC: ISpotifyClient, ES: IEventStore, JR: IJobsRepository>
+    RefetchPlaylistTask<PR, SC, ES, JR>
+{
+    pub fn new(playlist_id: PlaylistId, session_id: SessionId) -> Self {
         Self {
             playlist_id,
+            session_id,
             _marker: std::marker::PhantomData,
         }
     }
 }
 
-pub struct RefetchPlaylistState<PR: IPlaylistRepository, SC: ISpotifyClient> {
+/// Tracks the refetch changed, stored as the job result
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefetchPlaylistResult {
+    pub added: Vec<Track>,
+    pub removed: Vec<Track>,
+    /// Whether tracks kept in the playlist changed their order
+    pub reordered: bool,
+}
+
+impl RefetchPlaylistResult {
+    /// Short description of the changes, such as "12 tracks added, 3 removed"
+    pub fn summary(&self) -> String {
+        let added = match self.added.len() {
+            1 => "1 track added".to_string(),
+            count => format!("{count} tracks added"),
+        };
+        let mut summary = format!("{added}, {} removed", self.removed.len());
+        if self.reordered {
+            summary.push_str(", reordered");
+        }
+        summary
+    }
+}
+
+pub struct RefetchPlaylistState<
+    PR: IPlaylistRepository,
+    SC: ISpotifyClient,
+    ES: IEventStore,
+    JR: IJobsRepository,
+> {
     pub playlist_repository: Arc<PR>,
     pub spotify_client: Arc<SC>,
+    pub event_store: Arc<ES>,
+    /// Receives the progress of running refetches
+    pub jobs_repository: Arc<JR>,
+    /// Drop tracks added to the Spotify playlist more than once, as imports do
+    pub dedup_on_import: bool,
 }
 
-impl<PR: IPlaylistRepository, SC: ISpotifyClient> Clone for RefetchPlaylistState<PR, SC> {
+impl<PR: IPlaylistRepository, SC: ISpotifyClient, ES: IEventStore, JR: IJobsRepository> Clone
+    for RefetchPlaylistState<PR, SC, ES, JR>
+{
     fn clone(&self) -> Self {
         Self {
             playlist_repository: self.playlist_repository.clone(),
             spotify_client: self.spotify_client.clone(),
+            event_store: self.event_store.clone(),
+            jobs_repository: self.jobs_repository.clone(),
+            dedup_on_import: self.dedup_on_import,
         }
     }
 }
 
-impl<PR: IPlaylistRepository, SC: ISpotifyClient> IWorkerTask for RefetchPlaylistTask<PR, SC> {
-    type State = RefetchPlaylistState<PR, SC>;
-    type Output = ();
+impl<PR: IPlaylistRepository, SC: ISpotifyClient, ES: IEventStore, JR: IJobsRepository> IWorkerTask
+    for RefetchPlaylistTask<PR, SC, ES, JR>
+{
+    const JOB_TYPE: JobType = JobType::RefetchPlaylist;
+    type State = RefetchPlaylistState<PR, SC, ES, JR>;
+    type Output = RefetchPlaylistResult;
 
-    async fn run(&self, state: &Self::State) -> anyhow::Result<Self::Output> {
-        let current_playlist = match state.playlist_repository.get(&self.playlist_id).await? {
+    async fn run(&self, state: &Self::State, job_id: &JobId) -> anyhow::Result<Self::Output> {
+        let current_playlist = match state
+            .playlist_repository
+            .get(&self.session_id, &self.playlist_id)
+            .await?
+        {
             Some(playlist) => playlist,
             None => {
                 anyhow::bail!("Playlist with ID {} not found", &self.playlist_id);
@@ -128,13 +222,41 @@ impl<PR: IPlaylistRepository, SC: ISpotifyClient> IWorkerTask for RefetchPlaylis
             }
         };
 
-        // Fetch fresh data from Spotify
-        let fresh_playlist = match state
+        // Fetch all fresh data before touching the database, a failed fetch keeps the stored tracks
+        let progress = FetchProgress::default();
+        let fetch = state
             .spotify_client
-            .get_playlist_with_tracks(&spotify_id)
-            .await?
-        {
-            Some(playlist) => playlist,
+            .get_playlist_with_tracks(&spotify_id, &progress);
+        let mut fetch = std::pin::pin!(fetch);
+        let mut report = tokio::time::interval(PROGRESS_REPORT_INTERVAL);
+        let fetched = loop {
+            tokio::select! {
+                fetched = &mut fetch => break fetched,
+                _ = report.tick() => {
+                    if let Some(progress) = progress.get()
+                        && let Err(e) = state.jobs_repository.set_progress(job_id, progress).await
+                    {
+                        warn!("Failed to store progress of job {}: {:#}", job_id, e);
+                    }
+                }
+            }
+        };
+        let fetched = match fetched {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                if let Some(partial) = e.downcast_ref::<PartialFetchError>() {
+                    crate::metrics::PARTIALLY_FETCHED_TRACKS_TOTAL
+                        .inc_by(partial.fetched_tracks as u64);
+                }
+                error!(
+                    "Refetch of playlist {} failed, keeping stored tracks: {:#}",
+                    self.playlist_id, e
+                );
+                return Err(e);
+            }
+        };
+        let fresh_playlist = match fetched {
+            Some(fetched) => fetched.playlist,
             None => {
                 anyhow::bail!(
                     "Playlist with Spotify ID {} not found in Spotify",
@@ -145,14 +267,274 @@ impl<PR: IPlaylistRepository, SC: ISpotifyClient> IWorkerTask for RefetchPlaylis
 
         // Create an updated playlist with the fresh data but preserve the original ID
         let mut updated_playlist = fresh_playlist;
+        if state.dedup_on_import {
+            let removed = updated_playlist.dedup_tracks();
+            if removed > 0 {
+                warn!(
+                    "Removed {} duplicate tracks from Spotify playlist {}",
+                    removed, spotify_id
+                );
+            }
+        }
         updated_playlist.id = current_playlist.id;
         updated_playlist.spotify_id = current_playlist.spotify_id;
         updated_playlist.created_at = current_playlist.created_at;
-        updated_playlist.updated_at = Some(chrono::Utc::now());
+        // Local changes survive the refetch, the Spotify name is updated underneath them
+        updated_playlist.custom_name = current_playlist.custom_name;
+        updated_playlist.back_text = current_playlist.back_text;
+
+        let old_track_count = current_playlist.tracks.len();
+        let diff = PlaylistDomainService::diff_playlists(
+            &current_playlist.tracks,
+            &updated_playlist.tracks,
+        );
+        // Notes are written by game masters, keep them for tracks still in the playlist
+        let notes: HashMap<_, _> = current_playlist
+            .tracks
+            .into_iter()
+            .filter_map(|track| Some((track.spotify_url, track.notes?)))
+            .collect();
+        for track in &mut updated_playlist.tracks {
+            track.notes = notes.get(&track.spotify_url).cloned();
+        }
 
         // Update the playlist in the repository
         state.playlist_repository.update(&updated_playlist).await?;
+        state
+            .event_store
+            .record(DomainEvent::PlaylistRefetched {
+                new_track_count: updated_playlist.track_count(),
+                playlist_id: updated_playlist.id,
+                old_track_count,
+            })
+            .await;
+
+        Ok(RefetchPlaylistResult {
+            added: diff.added,
+            removed: diff.removed,
+            reordered: !diff.reordered.is_empty(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Playlist, SpotifyId};
+    use crate::fixtures::{self, track};
+    use crate::infrastructure::playlist::MockPlaylistRepository;
+    use crate::infrastructure::spotify::MockSpotifyClient;
+    use crate::infrastructure::{EventStore, JobsRepository, PlaylistRepository, test_pool};
+
+    #[tokio::test]
+    async fn test_failed_refetch_keeps_stored_tracks() {
+        let pool = test_pool().await;
+        let playlist_repository = Arc::new(PlaylistRepository::new(pool.clone()).await.unwrap());
+        let session_id = SessionId::new();
+        let playlist = playlist_repository
+            .create(
+                &session_id,
+                &Playlist {
+                    spotify_id: Some(SpotifyId::parse("37i9dQZF1DXcBWIGoYBM5M").unwrap()),
+                    ..fixtures::playlist("Stored", vec![track("original")])
+                },
+            )
+            .await
+            .unwrap();
+        let state = fixtures::refetch_state(
+            playlist_repository.clone(),
+            MockSpotifyClient::new().with_fetch_timeout_after(3),
+            false,
+        )
+        .await;
+        let partial_before = crate::metrics::PARTIALLY_FETCHED_TRACKS_TOTAL.get();
+
+        let task = RefetchPlaylistTask::<
+            PlaylistRepository,
+            MockSpotifyClient,
+            EventStore,
+            JobsRepository,
+        >::new(playlist.id.clone(), session_id.clone());
+        assert!(task.run(&state, &JobId::new()).await.is_err());
+
+        let stored = playlist_repository
+            .get(&session_id, &playlist.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tracks, playlist.tracks);
+        assert_eq!(stored.updated_at, None);
+        assert!(crate::metrics::PARTIALLY_FETCHED_TRACKS_TOTAL.get() >= partial_before + 3);
+    }
+
+    #[tokio::test]
+    async fn test_refetch_reports_added_and_removed_tracks() {
+        let spotify_id = SpotifyId::parse("37i9dQZF1DXcBWIGoYBM5M").unwrap();
+        let stored = Playlist {
+            spotify_id: Some(spotify_id.clone()),
+            ..fixtures::playlist("Stored", vec![track("a"), track("b"), track("c")])
+        };
+        let fresh = Playlist {
+            tracks: vec![track("c"), track("a"), track("d"), track("e")],
+            ..stored.clone()
+        };
+        let playlist_repository = Arc::new(MockPlaylistRepository::new());
+        let session_id = SessionId::new();
+        playlist_repository
+            .create(&session_id, &stored)
+            .await
+            .unwrap();
+        let state = fixtures::refetch_state(
+            playlist_repository,
+            MockSpotifyClient::new().with_playlist(spotify_id, Some(fresh)),
+            false,
+        )
+        .await;
+
+        let result = RefetchPlaylistTask::<
+            MockPlaylistRepository,
+            MockSpotifyClient,
+            EventStore,
+            JobsRepository,
+        >::new(stored.id, session_id)
+        .run(&state, &JobId::new())
+        .await
+        .unwrap();
+
+        assert_eq!(result.added, vec![track("d"), track("e")]);
+        assert_eq!(result.removed, vec![track("b")]);
+        assert!(result.reordered);
+        assert_eq!(result.summary(), "2 tracks added, 1 removed, reordered");
+    }
+
+    #[tokio::test]
+    async fn test_refetch_updates_the_name_and_keeps_the_custom_name() {
+        let spotify_id = SpotifyId::parse("37i9dQZF1DXcBWIGoYBM5M").unwrap();
+        let stored = Playlist {
+            spotify_id: Some(spotify_id.clone()),
+            custom_name: Some("Party Mix".to_string()),
+            ..fixtures::playlist("Old Name", vec![track("a")])
+        };
+        let fresh = Playlist {
+            name: "New Name".to_string(),
+            custom_name: None,
+            ..stored.clone()
+        };
+        let playlist_repository = Arc::new(MockPlaylistRepository::new());
+        let session_id = SessionId::new();
+        playlist_repository
+            .create(&session_id, &stored)
+            .await
+            .unwrap();
+        let state = fixtures::refetch_state(
+            playlist_repository.clone(),
+            MockSpotifyClient::new().with_playlist(spotify_id, Some(fresh)),
+            false,
+        )
+        .await;
+
+        RefetchPlaylistTask::<MockPlaylistRepository, MockSpotifyClient, EventStore, JobsRepository>::new(
+            stored.id.clone(),
+            session_id.clone(),
+        )
+        .run(&state, &JobId::new())
+        .await
+        .unwrap();
+
+        let refetched = playlist_repository
+            .get(&session_id, &stored.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(refetched.name, "New Name");
+        assert_eq!(refetched.display_name(), "Party Mix");
+    }
+
+    #[tokio::test]
+    async fn test_refetch_stores_its_progress_while_fetching() {
+        let spotify_id = SpotifyId::parse("37i9dQZF1DXcBWIGoYBM5M").unwrap();
+        let stored = Playlist {
+            spotify_id: Some(spotify_id.clone()),
+            ..fixtures::playlist("Stored", vec![])
+        };
+        let fresh = Playlist {
+            tracks: vec![track("a"), track("b"), track("c")],
+            ..stored.clone()
+        };
+        let playlist_repository = Arc::new(MockPlaylistRepository::new());
+        let session_id = SessionId::new();
+        playlist_repository
+            .create(&session_id, &stored)
+            .await
+            .unwrap();
+        let state = fixtures::refetch_state(
+            playlist_repository,
+            MockSpotifyClient::new()
+                .with_playlist(spotify_id, Some(fresh))
+                .with_fetch_delay(PROGRESS_REPORT_INTERVAL * 2),
+            false,
+        )
+        .await;
+        let jobs_repository = state.jobs_repository.clone();
+        let job = jobs_repository
+            .create(crate::domain::Job::new(serde_json::json!({})))
+            .await
+            .unwrap();
+
+        RefetchPlaylistTask::<MockPlaylistRepository, MockSpotifyClient, EventStore, JobsRepository>::new(
+            stored.id, session_id,
+        )
+        .run(&state, &job.id)
+        .await
+        .unwrap();
+
+        let job = jobs_repository.get(&job.id).await.unwrap().unwrap();
+        assert_eq!(
+            job.progress,
+            Some(crate::domain::JobProgress { done: 3, total: 3 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refetch_drops_duplicate_tracks_when_deduplicating() {
+        let spotify_id = SpotifyId::parse("37i9dQZF1DXcBWIGoYBM5M").unwrap();
+        let stored = Playlist {
+            spotify_id: Some(spotify_id.clone()),
+            ..fixtures::playlist("Stored", vec![])
+        };
+        let fresh = Playlist {
+            tracks: vec![track("a"), track("b"), track("a")],
+            ..stored.clone()
+        };
+        let playlist_repository = Arc::new(MockPlaylistRepository::new());
+        let session_id = SessionId::new();
+        playlist_repository
+            .create(&session_id, &stored)
+            .await
+            .unwrap();
+        let state = fixtures::refetch_state(
+            playlist_repository.clone(),
+            MockSpotifyClient::new().with_playlist(spotify_id, Some(fresh)),
+            true,
+        )
+        .await;
+
+        let result = RefetchPlaylistTask::<
+            MockPlaylistRepository,
+            MockSpotifyClient,
+            EventStore,
+            JobsRepository,
+        >::new(stored.id.clone(), session_id.clone())
+        .run(&state, &JobId::new())
+        .await
+        .unwrap();
 
-        Ok(())
+        assert_eq!(result.added, vec![track("a"), track("b")]);
+        let refetched = playlist_repository
+            .get(&session_id, &stored.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(refetched.tracks, vec![track("a"), track("b")]);
     }
 }
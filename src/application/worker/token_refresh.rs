@@ -0,0 +1,50 @@
+use crate::application::ISpotifyClient;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{error, info};
+
+/// How often the Spotify token is renewed, well within the hour it stays valid
+const TOKEN_REFRESH_INTERVAL: Duration = Duration::from_secs(45 * 60);
+
+/// Requests a new Spotify token every 45 minutes, so requests never wait on an expired one.
+///
+/// The first refresh happens one interval after startup, when the client was authenticated.
+pub fn spawn_spotify_token_refresh<SC: ISpotifyClient>(spotify_client: Arc<SC>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval_at(
+            Instant::now() + TOKEN_REFRESH_INTERVAL,
+            TOKEN_REFRESH_INTERVAL,
+        );
+        loop {
+            interval.tick().await;
+            match spotify_client.refresh_token().await {
+                Ok(()) => info!("Refreshed Spotify token"),
+                Err(e) => error!("Failed to refresh Spotify token: {:#}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::spotify::MockSpotifyClient;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_is_refreshed_every_interval() {
+        let client = MockSpotifyClient::new();
+        let refresh = spawn_spotify_token_refresh(Arc::new(client.clone()));
+
+        tokio::time::sleep(TOKEN_REFRESH_INTERVAL - Duration::from_secs(1)).await;
+        assert_eq!(client.calls("refresh_token"), 0);
+
+        tokio::time::sleep(TOKEN_REFRESH_INTERVAL).await;
+        assert_eq!(client.calls("refresh_token"), 1);
+
+        tokio::time::sleep(TOKEN_REFRESH_INTERVAL).await;
+        assert_eq!(client.calls("refresh_token"), 2);
+        refresh.abort();
+    }
+}
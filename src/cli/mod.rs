@@ -0,0 +1,159 @@
+//! Command line interface for generating PDFs without starting the web server
+
+use crate::application::{
+    CardLayout, FetchProgress, IPdfGenerator, ISpotifyClient, PdfGeneratorConfig,
+};
+use crate::domain::SpotifyId;
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "hitster",
+    about = "Generate Hitster cards from Spotify playlists"
+)]
+pub struct Cli {
+    /// Runs the web server when no command is given
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Generate front and back PDFs for a Spotify playlist
+    Generate(GenerateCommand),
+}
+
+#[derive(Debug, Args)]
+pub struct GenerateCommand {
+    /// Spotify playlist URL or ID
+    pub url: String,
+    /// Directory the PDFs are written to
+    #[arg(short, long = "output", default_value = ".")]
+    pub output_dir: PathBuf,
+    /// Paper size of the generated PDFs, only `a4` is supported
+    #[arg(long, default_value = "a4")]
+    pub paper_size: String,
+    /// Card grid on each page, one of `3x4`, `2x3` or `4x6`, defaults to the configured layout
+    #[arg(long)]
+    pub layout: Option<CardLayout>,
+}
+
+/// Fetches the playlist and writes `{name}_front.pdf` and `{name}_back.pdf`,
+/// returning the paths of the written files
+pub async fn generate<SC: ISpotifyClient, PG: IPdfGenerator>(
+    command: &GenerateCommand,
+    spotify_client: &SC,
+    pdf_generator: &PG,
+    default_config: &PdfGeneratorConfig,
+) -> anyhow::Result<(PathBuf, PathBuf)> {
+    if !command.paper_size.eq_ignore_ascii_case("a4") {
+        anyhow::bail!("Unsupported paper size '{}'", command.paper_size);
+    }
+    let config = PdfGeneratorConfig {
+        layout: command.layout.unwrap_or(default_config.layout),
+        ..*default_config
+    };
+
+    let spotify_id = SpotifyId::parse(&command.url)?;
+
+    eprintln!("Fetching playlist {spotify_id} from Spotify...");
+    let fetched = spotify_client
+        .get_playlist_with_tracks(&spotify_id, &FetchProgress::default())
+        .await?
+        .with_context(|| format!("Playlist {spotify_id} not found on Spotify"))?;
+    if fetched.skipped_episodes > 0 {
+        eprintln!("Skipped {} podcast episodes", fetched.skipped_episodes);
+    }
+    let playlist = fetched.playlist;
+    eprintln!(
+        "Fetched '{}' with {} tracks",
+        playlist.name,
+        playlist.track_count()
+    );
+
+    eprintln!("Generating PDFs...");
+    let (front, back) = tokio::try_join!(
+        pdf_generator.generate_front_cards(&playlist, &config),
+        pdf_generator.generate_back_cards(&playlist, &config)
+    )?;
+
+    tokio::fs::create_dir_all(&command.output_dir)
+        .await
+        .with_context(|| format!("while creating {}", command.output_dir.display()))?;
+
+    let base_filename = playlist.file_stem();
+    let front_path = command
+        .output_dir
+        .join(format!("{base_filename}_front.pdf"));
+    let back_path = command.output_dir.join(format!("{base_filename}_back.pdf"));
+
+    tokio::fs::write(&front_path, front)
+        .await
+        .with_context(|| format!("while writing {}", front_path.display()))?;
+    tokio::fs::write(&back_path, back)
+        .await
+        .with_context(|| format!("while writing {}", back_path.display()))?;
+
+    eprintln!("Wrote {}", front_path.display());
+    eprintln!("Wrote {}", back_path.display());
+
+    Ok((front_path, back_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::PdfGenerator;
+    use crate::domain::Track;
+    use crate::fixtures;
+    use crate::infrastructure::spotify::MockSpotifyClient;
+
+    fn road_trip_client() -> MockSpotifyClient {
+        MockSpotifyClient::new().with_playlist(
+            "37i9dQZF1DXcBWIGoYBM5M".parse().unwrap(),
+            Some(fixtures::playlist(
+                "Road Trip",
+                vec![Track {
+                    title: "Song".to_string(),
+                    artist: "Artist".to_string(),
+                    year: 1984,
+                    spotify_url: "https://open.spotify.com/track/1".to_string(),
+                    album_cover_url: None,
+                    popularity: None,
+                    duration_ms: None,
+                    notes: None,
+                }],
+            )),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_generate_writes_front_and_back_pdfs() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let command = GenerateCommand {
+            url: "https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M".to_string(),
+            output_dir: output_dir.path().to_path_buf(),
+            paper_size: "a4".to_string(),
+            layout: None,
+        };
+
+        let spotify_client = road_trip_client();
+
+        let (front, back) = generate(
+            &command,
+            &spotify_client,
+            &PdfGenerator::new(),
+            &PdfGeneratorConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(front, output_dir.path().join("Road_Trip_front.pdf"));
+        assert_eq!(back, output_dir.path().join("Road_Trip_back.pdf"));
+        assert!(std::fs::metadata(front).unwrap().len() > 0);
+        assert!(std::fs::metadata(back).unwrap().len() > 0);
+        assert_eq!(spotify_client.calls("get_playlist_with_tracks"), 1);
+    }
+}
@@ -3,6 +3,7 @@
 //! This module handles loading configuration from environment variables,
 //! .env files, and configuration files using the `config` crate.
 
+use crate::application::{CardLayout, PdfGeneratorConfig};
 use config::{Config, File};
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
@@ -16,12 +17,129 @@ pub struct Settings {
     pub database: DatabaseConfig,
     /// Server configuration
     pub server: ServerConfig,
+    /// PDF generation configuration
+    #[serde(default)]
+    pub pdf: PdfConfig,
+    /// Use WebSockets instead of SSE for job status updates, for proxies that break SSE
+    #[serde(default)]
+    pub prefer_websocket: bool,
+    /// Drop tracks added to a Spotify playlist more than once when importing it
+    #[serde(default)]
+    pub dedup_on_import: bool,
+    /// Close a job status stream when its client has not read an event for this long
+    #[serde(default = "default_sse_idle_timeout_seconds")]
+    pub sse_idle_timeout_seconds: u64,
+    /// Job status streams, SSE and WebSocket alike, open at once before new ones are rejected with 503
+    #[serde(default = "default_max_sse_connections")]
+    pub max_sse_connections: usize,
+    /// PDF generation jobs run at once, between 1 and [`MAX_PDF_WORKER_COUNT`]
+    #[serde(default = "default_pdf_worker_count")]
+    pub pdf_worker_count: u8,
+    /// Jobs each worker queues before new ones are rejected with 429
+    #[serde(default = "default_job_queue_capacity")]
+    pub job_queue_capacity: usize,
+    /// PDF generations each client IP can start per minute before it is rejected with 429
+    #[serde(default = "default_pdf_rate_limit_per_minute")]
+    pub pdf_rate_limit_per_minute: u32,
+    /// Spotify playlists with more tracks are rejected before their tracks are fetched
+    #[serde(default = "default_max_playlist_tracks")]
+    pub max_playlist_tracks: u32,
+    /// Finished jobs are deleted once they completed this many days ago
+    #[serde(default = "default_job_retention_days")]
+    pub job_retention_days: u32,
+    /// Jobs still pending or processing this many minutes after they were enqueued are queued again
+    #[serde(default = "default_stale_job_minutes")]
+    pub stale_job_minutes: u32,
+    /// Bearer token for the admin pages, which are disabled while it is unset
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    /// Refuse to start when SQLite reports a corrupt database or broken foreign keys
+    #[serde(default = "default_db_integrity_check_on_startup")]
+    pub db_integrity_check_on_startup: bool,
+}
+
+/// Upper bound for [`Settings::pdf_worker_count`]
+pub const MAX_PDF_WORKER_COUNT: u8 = 8;
+/// Upper bound for [`DatabaseConfig::max_connections`]
+pub const MAX_DB_CONNECTIONS: u32 = 100;
+
+/// A setting that [`Settings::validate`] rejected
+#[derive(Debug, displaydoc::Display, thiserror::Error, PartialEq)]
+pub enum ConfigError {
+    /// {0} must be set
+    Missing(&'static str),
+    /// {field} must be between {min} and {max}, got {value}
+    OutOfRange {
+        field: &'static str,
+        min: u64,
+        max: u64,
+        value: u64,
+    },
+    /// database.path must be in an existing, writable directory, but {0} is not
+    DatabaseDirectoryNotWritable(String),
+    /// pdf.fallback_font_path must be an existing file, but {0} is not
+    FallbackFontNotFound(String),
+    /// {0} must be a positive number
+    NotPositive(&'static str),
+}
+
+fn default_sse_idle_timeout_seconds() -> u64 {
+    60
+}
+
+fn default_max_sse_connections() -> usize {
+    100
+}
+
+fn default_pdf_worker_count() -> u8 {
+    1
+}
+
+fn default_job_queue_capacity() -> usize {
+    crate::application::worker::DEFAULT_QUEUE_CAPACITY
+}
+
+fn default_pdf_rate_limit_per_minute() -> u32 {
+    10
+}
+
+fn default_max_playlist_tracks() -> u32 {
+    1000
+}
+
+fn default_job_retention_days() -> u32 {
+    30
+}
+
+fn default_stale_job_minutes() -> u32 {
+    30
+}
+
+fn default_db_integrity_check_on_startup() -> bool {
+    true
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct SpotifyConfig {
     pub client_id: String,
     pub client_secret: String,
+    /// Overrides the Spotify API and authentication base URL, for testing only—do not set in production
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+    /// Skip podcast episodes in mixed playlists instead of failing the import
+    #[serde(default = "default_skip_episodes")]
+    pub skip_episodes: bool,
+    /// Longest `Retry-After` wait honored when Spotify rate limits a request
+    #[serde(default = "default_max_retry_after_seconds")]
+    pub max_retry_after_seconds: u64,
+}
+
+fn default_skip_episodes() -> bool {
+    true
+}
+
+fn default_max_retry_after_seconds() -> u64 {
+    60
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -37,6 +155,107 @@ pub struct ServerConfig {
     pub port: u16,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct PdfConfig {
+    #[serde(default)]
+    pub card_border: CardBorder,
+    /// Print track notes in small text at the bottom of the card fronts
+    #[serde(default)]
+    pub show_notes: bool,
+    /// Grid of cards printed on each page
+    #[serde(default)]
+    pub card_layout: CardLayout,
+    /// How QR codes are embedded on card backs
+    #[serde(default)]
+    pub qr_code_format: QrCodeOutput,
+    /// Space between the card border and the text on card fronts
+    #[serde(default = "default_card_margin_mm")]
+    pub card_margin_mm: f64,
+    #[serde(default = "default_artist_font_size")]
+    pub artist_font_size: f64,
+    #[serde(default = "default_title_font_size")]
+    pub title_font_size: f64,
+    #[serde(default = "default_year_font_size")]
+    pub year_font_size: f64,
+    /// Space between the edges of a card back and the QR code or thumbnail
+    #[serde(default = "default_qr_padding_mm")]
+    pub qr_padding_mm: f64,
+    /// TrueType font for characters the card font lacks, such as Chinese, Japanese and Korean
+    #[serde(default)]
+    pub fallback_font_path: Option<PathBuf>,
+}
+
+impl Default for PdfConfig {
+    fn default() -> Self {
+        Self {
+            card_border: CardBorder::default(),
+            show_notes: false,
+            card_layout: CardLayout::default(),
+            qr_code_format: QrCodeOutput::default(),
+            card_margin_mm: default_card_margin_mm(),
+            artist_font_size: default_artist_font_size(),
+            title_font_size: default_title_font_size(),
+            year_font_size: default_year_font_size(),
+            qr_padding_mm: default_qr_padding_mm(),
+            fallback_font_path: None,
+        }
+    }
+}
+
+impl PdfConfig {
+    /// Card geometry and font sizes PDFs are generated with
+    pub fn generator_config(&self) -> PdfGeneratorConfig {
+        PdfGeneratorConfig {
+            card_margin_mm: self.card_margin_mm,
+            artist_font_size: self.artist_font_size,
+            title_font_size: self.title_font_size,
+            year_font_size: self.year_font_size,
+            layout: self.card_layout,
+            qr_padding_mm: self.qr_padding_mm,
+        }
+    }
+}
+
+fn default_card_margin_mm() -> f64 {
+    PdfGeneratorConfig::default().card_margin_mm
+}
+
+fn default_artist_font_size() -> f64 {
+    PdfGeneratorConfig::default().artist_font_size
+}
+
+fn default_title_font_size() -> f64 {
+    PdfGeneratorConfig::default().title_font_size
+}
+
+fn default_year_font_size() -> f64 {
+    PdfGeneratorConfig::default().year_font_size
+}
+
+fn default_qr_padding_mm() -> f64 {
+    PdfGeneratorConfig::default().qr_padding_mm
+}
+
+/// QR code format on card backs, `svg` stays sharp when printed at high DPI
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QrCodeOutput {
+    #[default]
+    Png,
+    Svg,
+}
+
+/// Border style printed around each card
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CardBorder {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+    None,
+}
+
 impl Settings {
     pub fn new() -> anyhow::Result<Self> {
         let config_dir = std::env::var("HITSTER_CONFIG_DIR")
@@ -61,6 +280,137 @@ impl Settings {
         Ok(settings)
     }
 
+    /// Checks the settings that deserialize fine but cannot work, reporting all of them at once.
+    ///
+    /// The database settings are left to [`Settings::validate_database`], as only the server
+    /// opens the database.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        let mut in_range = |field, value: u64, min, max| {
+            if !(min..=max).contains(&value) {
+                errors.push(ConfigError::OutOfRange {
+                    field,
+                    min,
+                    max,
+                    value,
+                });
+            }
+        };
+
+        in_range("server.port", self.server.port.into(), 1, u16::MAX.into());
+        in_range(
+            "pdf_worker_count",
+            self.pdf_worker_count.into(),
+            1,
+            MAX_PDF_WORKER_COUNT.into(),
+        );
+        in_range(
+            "job_queue_capacity",
+            self.job_queue_capacity as u64,
+            1,
+            u64::MAX,
+        );
+        in_range(
+            "pdf_rate_limit_per_minute",
+            self.pdf_rate_limit_per_minute.into(),
+            1,
+            u32::MAX.into(),
+        );
+        in_range(
+            "stale_job_minutes",
+            self.stale_job_minutes.into(),
+            1,
+            u32::MAX.into(),
+        );
+
+        for (field, value) in [
+            ("pdf.card_margin_mm", self.pdf.card_margin_mm),
+            ("pdf.artist_font_size", self.pdf.artist_font_size),
+            ("pdf.title_font_size", self.pdf.title_font_size),
+            ("pdf.year_font_size", self.pdf.year_font_size),
+            ("pdf.qr_padding_mm", self.pdf.qr_padding_mm),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                errors.push(ConfigError::NotPositive(field));
+            }
+        }
+
+        if self.spotify.client_id.trim().is_empty() {
+            errors.push(ConfigError::Missing("spotify.client_id"));
+        }
+        if self.spotify.client_secret.trim().is_empty() {
+            errors.push(ConfigError::Missing("spotify.client_secret"));
+        }
+        if self
+            .admin_token
+            .as_ref()
+            .is_some_and(|token| token.trim().is_empty())
+        {
+            errors.push(ConfigError::Missing("admin_token"));
+        }
+
+        if let Some(path) = &self.pdf.fallback_font_path
+            && !path.is_file()
+        {
+            errors.push(ConfigError::FallbackFontNotFound(
+                path.display().to_string(),
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks the database settings like [`Settings::validate`] checks the others
+    pub fn validate_database(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        let mut in_range = |field, value: u64, min, max| {
+            if !(min..=max).contains(&value) {
+                errors.push(ConfigError::OutOfRange {
+                    field,
+                    min,
+                    max,
+                    value,
+                });
+            }
+        };
+
+        in_range(
+            "database.max_connections",
+            self.database.max_connections.into(),
+            1,
+            MAX_DB_CONNECTIONS.into(),
+        );
+        in_range(
+            "database.timeout_seconds",
+            self.database.timeout_seconds,
+            1,
+            u64::MAX,
+        );
+
+        // SQLite creates the database file, but not the directory it is in
+        let directory = match Path::new(&self.database.path).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        // Permission bits do not tell whether this process may write there, creating a
+        // file does. It is removed again once dropped.
+        if tempfile::NamedTempFile::new_in(directory).is_err() {
+            errors.push(ConfigError::DatabaseDirectoryNotWritable(
+                directory.display().to_string(),
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     fn initialize_config_files(config_dir: &Path) -> anyhow::Result<()> {
         use std::fs;
 
@@ -79,3 +429,66 @@ impl Settings {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_settings() -> Settings {
+        Config::builder()
+            .add_source(File::from_str(
+                include_str!("../config.default.toml"),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_validate_reports_every_misconfigured_field() {
+        let mut settings = default_settings();
+        settings.spotify.client_id = "id".to_string();
+        settings.spotify.client_secret = "secret".to_string();
+        settings.database.path = std::env::temp_dir()
+            .join("hitster.db")
+            .display()
+            .to_string();
+        assert_eq!(settings.validate(), Ok(()));
+        assert_eq!(settings.validate_database(), Ok(()));
+
+        settings.spotify.client_secret = " ".to_string();
+        settings.database.max_connections = 0;
+        settings.server.port = 0;
+        settings.database.path = "./does/not/exist/hitster.db".to_string();
+        settings.pdf.year_font_size = 0.0;
+        settings.pdf.fallback_font_path = Some("./does/not/exist.ttf".into());
+        let errors = settings.validate().unwrap_err();
+
+        assert_eq!(errors.len(), 4);
+        assert!(errors.contains(&ConfigError::FallbackFontNotFound(
+            "./does/not/exist.ttf".to_string()
+        )));
+        assert!(errors.contains(&ConfigError::NotPositive("pdf.year_font_size")));
+        assert!(errors.contains(&ConfigError::Missing("spotify.client_secret")));
+        assert!(
+            errors
+                .iter()
+                .any(|error| error.to_string().starts_with("server.port"))
+        );
+
+        let errors = settings.validate_database().unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors.contains(&ConfigError::OutOfRange {
+            field: "database.max_connections",
+            min: 1,
+            max: 100,
+            value: 0,
+        }));
+        assert!(errors.contains(&ConfigError::DatabaseDirectoryNotWritable(
+            "./does/not/exist".to_string()
+        )));
+    }
+}
@@ -0,0 +1,42 @@
+use crate::domain::{JobId, PlaylistId, SpotifyId};
+use serde::Serialize;
+
+/// Something that happened to a playlist, recorded as its audit trail
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    PlaylistCreated {
+        playlist_id: PlaylistId,
+        spotify_id: Option<SpotifyId>,
+        track_count: usize,
+    },
+    PdfGenerationCompleted {
+        playlist_id: PlaylistId,
+        job_id: JobId,
+        duration_ms: u64,
+    },
+    PlaylistRefetched {
+        playlist_id: PlaylistId,
+        old_track_count: usize,
+        new_track_count: usize,
+    },
+}
+
+impl DomainEvent {
+    /// Name of the event, as in its serialized `type`
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DomainEvent::PlaylistCreated { .. } => "playlist_created",
+            DomainEvent::PdfGenerationCompleted { .. } => "pdf_generation_completed",
+            DomainEvent::PlaylistRefetched { .. } => "playlist_refetched",
+        }
+    }
+
+    pub fn playlist_id(&self) -> &PlaylistId {
+        match self {
+            DomainEvent::PlaylistCreated { playlist_id, .. }
+            | DomainEvent::PdfGenerationCompleted { playlist_id, .. }
+            | DomainEvent::PlaylistRefetched { playlist_id, .. } => playlist_id,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::domain::{PlaylistId, SessionId};
 use displaydoc::Display;
 use std::str::FromStr;
 use uuid::Uuid;
@@ -40,21 +41,48 @@ impl From<Uuid> for JobId {
     }
 }
 
-#[derive(Debug, Display, Clone, PartialEq)]
+#[derive(Debug, Display, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum JobStatus {
     /// pending
     Pending,
     /// processing
     Processing,
+    /// retrying
+    Retrying,
     /// completed
     Completed,
     /// failed
     Failed,
+    /// cancelled
+    Cancelled,
 }
 
 impl JobStatus {
     pub fn is_done(&self) -> bool {
-        matches!(self, JobStatus::Completed | JobStatus::Failed)
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// Kind of work a job performs, stored as `type` in the job payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobType {
+    #[serde(alias = "generate_pdfs")]
+    GeneratePlaylistPdfs,
+    RefetchPlaylist,
+}
+
+impl JobType {
+    /// Discriminant stored in the job payload
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobType::GeneratePlaylistPdfs => "generate_playlist_pdfs",
+            JobType::RefetchPlaylist => "refetch_playlist",
+        }
     }
 }
 
@@ -66,6 +94,17 @@ pub struct Job {
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
     pub payload: serde_json::Value,
     pub result: Option<serde_json::Value>,
+    /// Why the job failed, if it did
+    pub error_message: Option<String>,
+    /// How far the running job got, for jobs that report it
+    pub progress: Option<JobProgress>,
+}
+
+/// Units of work a job has done out of its total, such as tracks fetched by a refetch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct JobProgress {
+    pub done: u32,
+    pub total: u32,
 }
 
 impl Job {
@@ -77,6 +116,43 @@ impl Job {
             completed_at: None,
             payload,
             result: None,
+            error_message: None,
+            progress: None,
         }
     }
+
+    /// Kind of work the job performs, `None` for jobs enqueued before job types were recorded
+    pub fn job_type(&self) -> Option<JobType> {
+        serde_json::from_value(self.payload.get("type")?.clone()).ok()
+    }
+
+    /// Playlist the job works on
+    pub fn playlist_id(&self) -> Option<PlaylistId> {
+        serde_json::from_value(self.payload.get("playlist_id")?.clone()).ok()
+    }
+
+    /// Session that enqueued the job, `None` for jobs enqueued before playlists belonged to sessions
+    pub fn session_id(&self) -> Option<SessionId> {
+        serde_json::from_value(self.payload.get("session_id")?.clone()).ok()
+    }
+
+    /// Times the job was retried after failing, stored as `attempt` in the payload
+    pub fn attempt(&self) -> u32 {
+        self.payload
+            .get("attempt")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(0, |attempt| attempt as u32)
+    }
+
+    pub fn set_attempt(&mut self, attempt: u32) {
+        if let Some(fields) = self.payload.as_object_mut() {
+            fields.insert("attempt".to_string(), attempt.into());
+        }
+    }
+
+    /// Time from enqueueing until the job completed or failed
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        self.completed_at
+            .map(|completed_at| completed_at - self.created_at)
+    }
 }
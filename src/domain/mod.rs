@@ -1,9 +1,15 @@
+pub mod event;
 pub mod job;
 pub mod pdf;
 pub mod playlist;
+pub mod session;
 pub mod spotify_id;
+pub mod tag;
 
+pub use event::*;
 pub use job::*;
 pub use pdf::*;
 pub use playlist::*;
+pub use session::*;
 pub use spotify_id::*;
+pub use tag::*;
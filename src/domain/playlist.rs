@@ -1,11 +1,17 @@
 use super::SpotifyId;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Formatter;
 use std::str::FromStr;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Internal identifier of a stored playlist.
+///
+/// This is always a UUID generated by Hitster and never a Spotify ID,
+/// see [`SpotifyId`] for identifiers coming from Spotify.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PlaylistId(Uuid);
 impl std::fmt::Display for PlaylistId {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -14,8 +20,14 @@ impl std::fmt::Display for PlaylistId {
 }
 
 impl PlaylistId {
-    pub fn new() -> Result<Self, anyhow::Error> {
-        Ok(Self(Uuid::new_v4()))
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for PlaylistId {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -25,12 +37,35 @@ impl From<PlaylistId> for Uuid {
     }
 }
 
+impl From<&PlaylistId> for Uuid {
+    fn from(value: &PlaylistId) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<&str> for PlaylistId {
+    type Error = PlaylistIdError;
+
+    /// Parses a playlist ID from its UUID string representation
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.len() {
+            0 => return Err(PlaylistIdError::Empty),
+            // Simple and hyphenated UUID formats
+            32 | 36 => {}
+            length => return Err(PlaylistIdError::InvalidLength(length)),
+        }
+
+        let uuid = Uuid::parse_str(value)
+            .map_err(|_| PlaylistIdError::InvalidUuidFormat(value.to_string()))?;
+        Ok(Self(uuid))
+    }
+}
+
 impl FromStr for PlaylistId {
-    type Err = anyhow::Error;
+    type Err = PlaylistIdError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let uuid = Uuid::parse_str(s)?;
-        Ok(Self(uuid))
+        Self::try_from(s)
     }
 }
 
@@ -40,6 +75,44 @@ impl From<Uuid> for PlaylistId {
     }
 }
 
+/// Serializes as the hyphenated UUID string
+impl Serialize for PlaylistId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes from a string accepted by [`PlaylistId::from_str`]
+impl<'de> Deserialize<'de> for PlaylistId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PlaylistIdVisitor;
+
+        impl Visitor<'_> for PlaylistIdVisitor {
+            type Value = PlaylistId;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a playlist UUID")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<PlaylistId, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(PlaylistIdVisitor)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, displaydoc::Display, thiserror::Error)]
+pub enum PlaylistIdError {
+    /// Playlist ID cannot be empty
+    Empty,
+    /// Playlist ID is not a valid UUID: {0}
+    InvalidUuidFormat(String),
+    /// Playlist ID has an invalid length of {0} characters
+    InvalidLength(usize),
+}
+
 /// Represents a Spotify playlist with tracks
 #[derive(Debug, Clone)]
 pub struct Playlist {
@@ -49,8 +122,17 @@ pub struct Playlist {
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
     pub tracks: Vec<Track>,
+    /// Custom text printed on the back of each card
+    pub back_text: Option<String>,
+    /// Number of tracks Spotify reported for the playlist, known before its tracks are fetched
+    pub spotify_track_count: Option<u32>,
+    /// Name given by the user, shown in place of the Spotify name
+    pub custom_name: Option<String>,
 }
 
+const MAX_BACK_TEXT_LENGTH: usize = 100;
+const MAX_NAME_LENGTH: usize = 200;
+
 impl Playlist {
     pub fn track_count(&self) -> usize {
         self.tracks.len()
@@ -59,14 +141,584 @@ impl Playlist {
     pub fn is_empty(&self) -> bool {
         self.tracks.is_empty()
     }
+
+    /// Number of tracks without an album cover
+    pub fn missing_album_cover_count(&self) -> usize {
+        self.tracks
+            .iter()
+            .filter(|track| track.album_cover_url.is_none())
+            .count()
+    }
+
+    /// Number of tracks without a usable release year
+    pub fn missing_year_count(&self) -> usize {
+        self.tracks
+            .iter()
+            .filter(|track| track.has_missing_year())
+            .count()
+    }
+
+    /// Share of album covers and release years present, from 0.0 to 1.0.
+    ///
+    /// An empty playlist has nothing missing and scores 1.0.
+    pub fn completeness_score(&self) -> f64 {
+        if self.tracks.is_empty() {
+            return 1.0;
+        }
+
+        let total = self.tracks.len();
+        let present =
+            (total - self.missing_album_cover_count()) + (total - self.missing_year_count());
+        present as f64 / (2 * total) as f64
+    }
+
+    /// Removes tracks whose Spotify URL appeared earlier in the playlist.
+    ///
+    /// Keeps the first occurrence and the order of the remaining tracks,
+    /// returns how many tracks were removed.
+    pub fn dedup_tracks(&mut self) -> usize {
+        let before = self.tracks.len();
+
+        let mut indexed: Vec<(usize, Track)> = self.tracks.drain(..).enumerate().collect();
+        // Stable sort, so the first occurrence of a URL stays in front of its duplicates
+        indexed.sort_by(|(_, a), (_, b)| a.spotify_url.cmp(&b.spotify_url));
+        indexed.dedup_by(|(_, a), (_, b)| a.spotify_url == b.spotify_url);
+        indexed.sort_by_key(|(position, _)| *position);
+
+        self.tracks = indexed.into_iter().map(|(_, track)| track).collect();
+        before - self.tracks.len()
+    }
+
+    /// Gives the playlist a local display name in place of its Spotify name
+    pub fn rename(&mut self, name: &str) -> Result<(), PlaylistNameError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(PlaylistNameError::Empty);
+        }
+        let length = name.chars().count();
+        if length > MAX_NAME_LENGTH {
+            return Err(PlaylistNameError::TooLong(length));
+        }
+
+        self.custom_name = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Name to show for the playlist, the user's own name if it has one
+    pub fn display_name(&self) -> &str {
+        self.custom_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Sets the custom card back text, blank text clears it
+    pub fn set_back_text(&mut self, back_text: Option<&str>) -> Result<(), BackTextError> {
+        let back_text = back_text.map(str::trim).filter(|text| !text.is_empty());
+
+        if let Some(text) = back_text {
+            let length = text.chars().count();
+            if length > MAX_BACK_TEXT_LENGTH {
+                return Err(BackTextError::TooLong(length));
+            }
+            if text.contains(['<', '>']) {
+                return Err(BackTextError::ContainsHtml);
+            }
+        }
+
+        self.back_text = back_text.map(str::to_string);
+        Ok(())
+    }
+
+    /// Playlist name made safe for use in file names
+    pub fn file_stem(&self) -> String {
+        let stem: String = self
+            .display_name()
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        if stem.is_empty() {
+            "playlist".to_string()
+        } else {
+            stem
+        }
+    }
+
+    /// Text printed on the back of the cards, falling back to the playlist name
+    pub fn card_back_text(&self) -> &str {
+        self.back_text.as_deref().unwrap_or(self.display_name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, displaydoc::Display, thiserror::Error)]
+pub enum PlaylistNameError {
+    /// Playlist name cannot be empty
+    Empty,
+    /// Playlist name is too long ({0} characters), the maximum is 200
+    TooLong(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, displaydoc::Display, thiserror::Error)]
+pub enum BackTextError {
+    /// Back text is too long ({0} characters), the maximum is 100
+    TooLong(usize),
+    /// Back text cannot contain HTML
+    ContainsHtml,
+}
+
+const MAX_TRACK_NOTES_LENGTH: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq, displaydoc::Display, thiserror::Error)]
+pub enum TrackNotesError {
+    /// Track notes are too long ({0} characters), the maximum is 200
+    TooLong(usize),
 }
 
 /// Represents a song card with all necessary information
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Track {
     pub title: String,
     pub artist: String,
     pub year: i32,
     pub spotify_url: String,
     pub album_cover_url: Option<String>,
+    /// How much the track is currently streamed on Spotify, from 0 to 100
+    pub popularity: Option<u8>,
+    /// Length of the track in milliseconds
+    pub duration_ms: Option<u32>,
+    /// Game master notes such as hints, fun facts or point values
+    pub notes: Option<String>,
+}
+
+impl Track {
+    /// Sets the game master notes, blank notes clear them
+    pub fn set_notes(&mut self, notes: Option<&str>) -> Result<(), TrackNotesError> {
+        let notes = notes.map(str::trim).filter(|text| !text.is_empty());
+
+        if let Some(text) = notes {
+            let length = text.chars().count();
+            if length > MAX_TRACK_NOTES_LENGTH {
+                return Err(TrackNotesError::TooLong(length));
+            }
+        }
+
+        self.notes = notes.map(str::to_string);
+        Ok(())
+    }
+
+    /// Whether the release year is missing or too old to be a real release year
+    pub fn has_missing_year(&self) -> bool {
+        self.year < 1900
+    }
+
+    /// Track length as minutes and seconds, e.g. `3:42`
+    pub fn formatted_duration(&self) -> Option<String> {
+        let seconds = self.duration_ms? / 1000;
+        Some(format!("{}:{:02}", seconds / 60, seconds % 60))
+    }
+}
+
+/// Order in which the tracks of a playlist can be sorted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Hardest to guess first
+    Difficulty,
+}
+
+/// How hard a track is to guess, judged by its popularity within the playlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Medium => write!(f, "Medium"),
+            Difficulty::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+/// Tracks that differ between two versions of a playlist
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaylistDiff {
+    pub added: Vec<Track>,
+    pub removed: Vec<Track>,
+    /// Old position, new position and track of tracks kept in both versions that moved
+    pub reordered: Vec<(usize, usize, Track)>,
+}
+
+/// Playlist rules that look at the tracks as a whole
+pub struct PlaylistDomainService;
+
+impl PlaylistDomainService {
+    /// Tracks ordered from least to most popular, tracks without popularity last
+    pub fn sort_by_difficulty(playlist: &Playlist) -> Vec<&Track> {
+        let mut tracks: Vec<&Track> = playlist.tracks.iter().collect();
+        tracks.sort_by_key(|track| (track.popularity.is_none(), track.popularity));
+        tracks
+    }
+
+    /// Changes that turn the tracks `a` into `b`, matching tracks by Spotify URL.
+    ///
+    /// Reordered tracks are the fewest kept tracks that have to move for the rest to
+    /// stay in order, so adding, removing or moving one track does not reorder
+    /// everything after it.
+    pub fn diff_playlists(a: &[Track], b: &[Track]) -> PlaylistDiff {
+        let old_urls: HashSet<&str> = a.iter().map(|t| t.spotify_url.as_str()).collect();
+        let new_urls: HashSet<&str> = b.iter().map(|t| t.spotify_url.as_str()).collect();
+
+        let kept = |tracks: &[Track], other: &HashSet<&str>| -> Vec<(usize, Track)> {
+            tracks
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| other.contains(t.spotify_url.as_str()))
+                .map(|(position, t)| (position, t.clone()))
+                .collect()
+        };
+        let kept_old = kept(a, &new_urls);
+        let kept_new: HashMap<&str, (usize, usize)> = kept(b, &old_urls)
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (position, _))| (b[position].spotify_url.as_str(), (rank, position)))
+            .collect();
+
+        let new_ranks: Vec<usize> = kept_old
+            .iter()
+            .map(|(_, t)| kept_new[t.spotify_url.as_str()].0)
+            .collect();
+        let in_order = longest_increasing_subsequence(&new_ranks);
+        let reordered = kept_old
+            .into_iter()
+            .zip(in_order)
+            .filter(|(_, in_order)| !in_order)
+            .map(|((old_position, track), _)| {
+                let (_, new_position) = kept_new[track.spotify_url.as_str()];
+                (old_position, new_position, track)
+            })
+            .collect();
+
+        PlaylistDiff {
+            added: b
+                .iter()
+                .filter(|t| !old_urls.contains(t.spotify_url.as_str()))
+                .cloned()
+                .collect(),
+            removed: a
+                .iter()
+                .filter(|t| !new_urls.contains(t.spotify_url.as_str()))
+                .cloned()
+                .collect(),
+            reordered,
+        }
+    }
+
+    /// Difficulty of every track in playlist order.
+    ///
+    /// The least popular quarter of the playlist is hard and the most popular
+    /// quarter is easy, tracks without popularity have no difficulty.
+    pub fn difficulties(playlist: &Playlist) -> Vec<Option<Difficulty>> {
+        let known: Vec<u8> = playlist
+            .tracks
+            .iter()
+            .filter_map(|track| track.popularity)
+            .collect();
+
+        playlist
+            .tracks
+            .iter()
+            .map(|track| {
+                let popularity = track.popularity?;
+                let below = known.iter().filter(|&&p| p < popularity).count();
+                let equal = known.iter().filter(|&&p| p == popularity).count();
+                // Mid-rank percentile so equal popularities share a difficulty
+                let percentile = (below as f64 + equal as f64 / 2.0) / known.len() as f64;
+
+                Some(if percentile < 0.25 {
+                    Difficulty::Hard
+                } else if percentile >= 0.75 {
+                    Difficulty::Easy
+                } else {
+                    Difficulty::Medium
+                })
+            })
+            .collect()
+    }
+}
+
+/// Marks the values forming the longest strictly increasing subsequence of `values`
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<bool> {
+    // Index of the smallest value ending an increasing run of each length
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; values.len()];
+    for (i, value) in values.iter().enumerate() {
+        let length = tails.partition_point(|&j| values[j] < *value);
+        previous[i] = length.checked_sub(1).map(|l| tails[l]);
+        if length == tails.len() {
+            tails.push(i);
+        } else {
+            tails[length] = i;
+        }
+    }
+
+    let mut marked = vec![false; values.len()];
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        marked[i] = true;
+        current = previous[i];
+    }
+    marked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn track(year: i32) -> Track {
+        Track {
+            title: "Title".to_string(),
+            artist: "Artist".to_string(),
+            year,
+            spotify_url: "https://open.spotify.com/track/abc".to_string(),
+            album_cover_url: None,
+            popularity: None,
+            duration_ms: None,
+            notes: None,
+        }
+    }
+
+    fn track_with_popularity(title: &str, popularity: Option<u8>) -> Track {
+        Track {
+            title: title.to_string(),
+            popularity,
+            duration_ms: None,
+            ..track(2000)
+        }
+    }
+
+    #[test]
+    fn test_playlist_id_only_accepts_uuids() {
+        let uuid = Uuid::new_v4();
+        let id = PlaylistId::try_from(uuid.to_string().as_str()).unwrap();
+        assert_eq!(Uuid::from(&id), uuid);
+        assert_eq!(id.to_string(), uuid.to_string());
+
+        assert_eq!(
+            PlaylistId::try_from("6rqhFgbbKwnb9MLmUQDhG6"),
+            Err(PlaylistIdError::InvalidLength(22))
+        );
+        assert_eq!(PlaylistId::try_from(""), Err(PlaylistIdError::Empty));
+        assert!(matches!(
+            PlaylistId::try_from("zzzzzzzz-zzzz-zzzz-zzzz-zzzzzzzzzzzz"),
+            Err(PlaylistIdError::InvalidUuidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_playlist_id_serde_round_trip() {
+        let id = PlaylistId::new();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, format!("\"{id}\""));
+        assert_eq!(serde_json::from_str::<PlaylistId>(&json).unwrap(), id);
+
+        let err = serde_json::from_str::<PlaylistId>("\"6rqhFgbbKwnb9MLmUQDhG6\"").unwrap_err();
+        assert!(err.to_string().contains("invalid length of 22"));
+    }
+
+    #[test]
+    fn test_has_missing_year() {
+        assert!(track(0).has_missing_year());
+        assert!(track(1899).has_missing_year());
+        assert!(!track(1900).has_missing_year());
+        assert!(!track(1985).has_missing_year());
+    }
+
+    #[test]
+    fn test_formatted_duration() {
+        let with_duration = |duration_ms| Track {
+            duration_ms,
+            ..track(1985)
+        };
+        assert_eq!(
+            with_duration(Some(222_500)).formatted_duration().unwrap(),
+            "3:42"
+        );
+        assert_eq!(
+            with_duration(Some(61_000)).formatted_duration().unwrap(),
+            "1:01"
+        );
+        assert_eq!(
+            with_duration(Some(999)).formatted_duration().unwrap(),
+            "0:00"
+        );
+        assert_eq!(with_duration(None).formatted_duration(), None);
+    }
+
+    #[test]
+    fn test_set_back_text() {
+        let mut playlist = fixtures::playlist("Name", vec![]);
+
+        playlist.set_back_text(Some(" Round 1 ")).unwrap();
+        assert_eq!(playlist.card_back_text(), "Round 1");
+
+        playlist.set_back_text(Some("   ")).unwrap();
+        assert_eq!(playlist.card_back_text(), "Name");
+
+        assert_eq!(
+            playlist.set_back_text(Some("<b>bold</b>")),
+            Err(BackTextError::ContainsHtml)
+        );
+        assert_eq!(
+            playlist.set_back_text(Some(&"a".repeat(101))),
+            Err(BackTextError::TooLong(101))
+        );
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut playlist = fixtures::playlist("Liked Songs", vec![]);
+
+        playlist.rename(" Party Mix ").unwrap();
+        assert_eq!(playlist.name, "Liked Songs");
+        assert_eq!(playlist.display_name(), "Party Mix");
+
+        assert_eq!(playlist.rename("   "), Err(PlaylistNameError::Empty));
+        playlist.rename(&"a".repeat(200)).unwrap();
+        assert_eq!(
+            playlist.rename(&"a".repeat(201)),
+            Err(PlaylistNameError::TooLong(201))
+        );
+    }
+
+    #[test]
+    fn test_set_notes() {
+        let mut track = track(1985);
+
+        track.set_notes(Some(" Number 1 for 10 weeks ")).unwrap();
+        assert_eq!(track.notes.as_deref(), Some("Number 1 for 10 weeks"));
+
+        track.set_notes(Some(&"a".repeat(200))).unwrap();
+        assert_eq!(
+            track.set_notes(Some(&"a".repeat(201))),
+            Err(TrackNotesError::TooLong(201))
+        );
+
+        track.set_notes(None).unwrap();
+        assert_eq!(track.notes, None);
+    }
+
+    #[test]
+    fn test_completeness_score() {
+        let mut playlist = fixtures::playlist("Name", vec![]);
+        assert_eq!(playlist.completeness_score(), 1.0);
+
+        let with_cover = Track {
+            album_cover_url: Some("https://i.scdn.co/image/abc".to_string()),
+            ..track(1999)
+        };
+        playlist.tracks = vec![with_cover.clone(), with_cover];
+        assert_eq!(playlist.completeness_score(), 1.0);
+
+        playlist.tracks = vec![track(1999), track(2001)];
+        assert_eq!(playlist.missing_album_cover_count(), 2);
+        assert_eq!(playlist.completeness_score(), 0.5);
+
+        playlist.tracks = vec![track(0), track(2001)];
+        assert_eq!(playlist.missing_year_count(), 1);
+        assert_eq!(playlist.completeness_score(), 0.25);
+    }
+
+    #[test]
+    fn test_dedup_tracks_keeps_first_occurrence() {
+        let track = |title: &str, url: &str| Track {
+            title: title.to_string(),
+            spotify_url: url.to_string(),
+            ..track(2000)
+        };
+        let mut playlist = fixtures::playlist(
+            "Name",
+            vec![
+                track("b", "https://open.spotify.com/track/b"),
+                track("a", "https://open.spotify.com/track/a"),
+                track("b again", "https://open.spotify.com/track/b"),
+                track("c", "https://open.spotify.com/track/c"),
+            ],
+        );
+
+        assert_eq!(playlist.dedup_tracks(), 1);
+        let titles: Vec<_> = playlist.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_sort_by_difficulty() {
+        let playlist = fixtures::playlist(
+            "Name",
+            vec![
+                track_with_popularity("Hit", Some(90)),
+                track_with_popularity("Unknown", None),
+                track_with_popularity("Obscure", Some(10)),
+            ],
+        );
+
+        let titles: Vec<_> = PlaylistDomainService::sort_by_difficulty(&playlist)
+            .into_iter()
+            .map(|track| track.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Obscure", "Hit", "Unknown"]);
+    }
+
+    #[test]
+    fn test_difficulties() {
+        let playlist = fixtures::playlist(
+            "Name",
+            vec![
+                track_with_popularity("a", Some(10)),
+                track_with_popularity("b", Some(40)),
+                track_with_popularity("c", Some(60)),
+                track_with_popularity("d", Some(90)),
+                track_with_popularity("e", None),
+            ],
+        );
+
+        assert_eq!(
+            PlaylistDomainService::difficulties(&playlist),
+            [
+                Some(Difficulty::Hard),
+                Some(Difficulty::Medium),
+                Some(Difficulty::Medium),
+                Some(Difficulty::Easy),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_playlists() {
+        let track = |id: &str| Track {
+            title: id.to_string(),
+            spotify_url: format!("https://open.spotify.com/track/{id}"),
+            ..track(2000)
+        };
+        let before = vec![track("a"), track("b"), track("c"), track("d"), track("e")];
+        // "b" removed, "d" moved to the front and "f" added
+        let after = vec![track("d"), track("a"), track("c"), track("e"), track("f")];
+
+        let diff = PlaylistDomainService::diff_playlists(&before, &after);
+
+        assert_eq!(diff.added, [track("f")]);
+        assert_eq!(diff.removed, [track("b")]);
+        assert_eq!(diff.reordered, [(3, 0, track("d"))]);
+    }
 }
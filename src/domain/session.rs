@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Formatter;
+use uuid::Uuid;
+
+/// Identifier of an anonymous browser session.
+///
+/// Playlists belong to the session that created them and are only visible within it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SessionId(Uuid);
+
+impl SessionId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for SessionId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for SessionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<Uuid> for SessionId {
+    fn from(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+}
+
+impl From<&SessionId> for Uuid {
+    fn from(value: &SessionId) -> Self {
+        value.0
+    }
+}
+
+/// Who a service call is made on behalf of
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserContext {
+    pub session_id: SessionId,
+}
+
+impl UserContext {
+    pub fn new(session_id: SessionId) -> Self {
+        Self { session_id }
+    }
+}
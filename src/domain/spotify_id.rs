@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::Formatter;
 use std::str::FromStr;
 use thiserror::Error;
@@ -6,8 +7,31 @@ use winnow::combinator::preceded;
 use winnow::token::rest;
 use winnow::{Parser, combinator::alt, token::take_while};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct SpotifyId(String);
+/// Kind of Spotify resource an ID refers to, as named in its URL and URI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SpotifyResourceType {
+    #[default]
+    Playlist,
+    Track,
+    Album,
+}
+
+impl SpotifyResourceType {
+    /// Path segment of the resource in Spotify URLs and URIs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpotifyResourceType::Playlist => "playlist",
+            SpotifyResourceType::Track => "track",
+            SpotifyResourceType::Album => "album",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpotifyId {
+    id: String,
+    resource_type: SpotifyResourceType,
+}
 
 impl SpotifyId {
     /// Parse a Spotify ID from various formats:
@@ -15,31 +39,77 @@ impl SpotifyId {
     /// - URI: spotify:playlist:6rqhFgbbKwnb9MLmUQDhG6
     /// - Raw: 6rqhFgbbKwnb9MLmUQDhG6
     pub fn parse(input: &str) -> Result<Self, SpotifyIdParserError> {
-        let id = spotify_id_parser
+        Self::parse_kind(input, SpotifyResourceType::Playlist)
+    }
+
+    /// Parse a Spotify album ID from the same formats as [`SpotifyId::parse`]:
+    /// - URL: https://open.spotify.com/album/0ETFjACtuP2ADo6LFhL6HN
+    /// - URI: spotify:album:0ETFjACtuP2ADo6LFhL6HN
+    /// - Raw: 0ETFjACtuP2ADo6LFhL6HN
+    pub fn parse_album(input: &str) -> Result<Self, SpotifyIdParserError> {
+        Self::parse_kind(input, SpotifyResourceType::Album)
+    }
+
+    /// Parse a Spotify track ID from the same formats as [`SpotifyId::parse`]:
+    /// - URL: https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC
+    /// - URI: spotify:track:4uLU6hMCjMI75M1A2tKUQC
+    /// - Raw: 4uLU6hMCjMI75M1A2tKUQC
+    pub fn parse_track(input: &str) -> Result<Self, SpotifyIdParserError> {
+        Self::parse_kind(input, SpotifyResourceType::Track)
+    }
+
+    /// Parse a Spotify ID of any resource type, taken from the URL or URI. Raw IDs are taken
+    /// to be playlists, as with [`SpotifyId::parse`].
+    pub fn parse_any_type(input: &str) -> Result<Self, SpotifyIdParserError> {
+        [
+            SpotifyResourceType::Playlist,
+            SpotifyResourceType::Album,
+            SpotifyResourceType::Track,
+        ]
+        .into_iter()
+        .find_map(|resource_type| Self::parse_kind(input, resource_type).ok())
+        .ok_or_else(|| SpotifyIdParserError::InvalidFormat(input.to_string()))
+    }
+
+    /// URLs and URIs of another resource type are rejected, raw IDs are taken to be of `resource_type`
+    fn parse_kind(
+        input: &str,
+        resource_type: SpotifyResourceType,
+    ) -> Result<Self, SpotifyIdParserError> {
+        let id = (|input: &mut &str| spotify_id_parser(input, resource_type))
             .parse(input)
             .map_err(|_| SpotifyIdParserError::InvalidFormat(input.to_string()))?;
-        Ok(Self(id))
+        Ok(Self { id, resource_type })
     }
 
     /// Get the raw Spotify ID string
     pub fn as_str(&self) -> &str {
-        &self.0
+        &self.id
+    }
+
+    /// Kind of resource the ID refers to
+    pub fn resource_type(&self) -> SpotifyResourceType {
+        self.resource_type
     }
 
     /// Get the Spotify URL for this ID
     pub fn as_url(&self) -> String {
-        format!("https://open.spotify.com/playlist/{}", self.0)
+        format!(
+            "https://open.spotify.com/{}/{}",
+            self.resource_type.as_str(),
+            self.id
+        )
     }
 
     /// Get the Spotify URI for this ID
     pub fn as_uri(&self) -> String {
-        format!("spotify:playlist:{}", self.0)
+        format!("spotify:{}:{}", self.resource_type.as_str(), self.id)
     }
 }
 
 impl std::fmt::Display for SpotifyId {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.id)
     }
 }
 
@@ -53,35 +123,90 @@ impl FromStr for SpotifyId {
 
 impl From<SpotifyId> for String {
     fn from(id: SpotifyId) -> Self {
-        id.0
+        id.id
+    }
+}
+
+/// Serializes as the URI, which keeps the resource type, see [`SpotifyIdAsUrl`] for the URL
+impl Serialize for SpotifyId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_uri())
+    }
+}
+
+/// Deserializes from any format accepted by [`SpotifyId::parse_any_type`]
+impl<'de> Deserialize<'de> for SpotifyId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SpotifyIdVisitor;
+
+        impl Visitor<'_> for SpotifyIdVisitor {
+            type Value = SpotifyId;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a Spotify ID, URI or URL")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<SpotifyId, E> {
+                SpotifyId::parse_any_type(value).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(SpotifyIdVisitor)
+    }
+}
+
+/// Serializes as the full Spotify URL instead of the URI, for JSON consumers outside Hitster
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpotifyIdAsUrl(#[serde(serialize_with = "serialize_as_url")] pub SpotifyId);
+
+impl From<SpotifyId> for SpotifyIdAsUrl {
+    fn from(id: SpotifyId) -> Self {
+        Self(id)
     }
 }
 
+/// Serializes a [`SpotifyId`] as its Spotify URL, for use with `#[serde(serialize_with)]`
+pub fn serialize_as_url<S: Serializer>(id: &SpotifyId, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&id.as_url())
+}
+
 /// Custom error type for Spotify ID parsing
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum SpotifyIdParserError {
+    #[error("Invalid Spotify ID format: {0}")]
     InvalidFormat(String),
 }
 
-impl std::fmt::Display for SpotifyIdParserError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            SpotifyIdParserError::InvalidFormat(input) => {
-                write!(f, "Invalid Spotify ID format: {}", input)
-            }
-        }
-    }
+/// Winnow parser for Spotify ID formats of the given resource type
+fn spotify_id_parser(
+    input: &mut &str,
+    resource_type: SpotifyResourceType,
+) -> winnow::Result<String> {
+    alt((
+        |input: &mut &str| parse_url_format(input, resource_type),
+        |input: &mut &str| parse_uri_format(input, resource_type),
+        parse_raw_id,
+    ))
+    .parse_next(input)
 }
 
-/// Winnow parser for Spotify ID formats
-fn spotify_id_parser(input: &mut &str) -> winnow::Result<String> {
-    alt((parse_url_format, parse_uri_format, parse_raw_id)).parse_next(input)
+/// Parse the resource type segment of URLs and URIs: playlist, track or album
+fn parse_resource_type(input: &mut &str) -> winnow::Result<SpotifyResourceType> {
+    alt((
+        "playlist".value(SpotifyResourceType::Playlist),
+        "track".value(SpotifyResourceType::Track),
+        "album".value(SpotifyResourceType::Album),
+    ))
+    .parse_next(input)
 }
 
 /// Parse URL format: http://open.spotify.com/playlist/6rqhFgbbKwnb9MLmUQDhG6
-fn parse_url_format(input: &mut &str) -> winnow::Result<String> {
-    let base_url = preceded(alt(("http://", "https://")), "open.spotify.com/playlist/");
-    let id = preceded(base_url, parse_raw_id).parse_next(input)?;
+fn parse_url_format(input: &mut &str, expected: SpotifyResourceType) -> winnow::Result<String> {
+    let base_url = preceded(
+        alt(("http://", "https://")),
+        preceded("open.spotify.com/", parse_resource_type).verify(|found| *found == expected),
+    );
+    let id = preceded((base_url, "/"), parse_raw_id).parse_next(input)?;
 
     // Consume any trailing query parameters
     let _ = rest.parse_next(input)?;
@@ -90,13 +215,21 @@ fn parse_url_format(input: &mut &str) -> winnow::Result<String> {
 }
 
 /// Parse URI format: spotify:playlist:6rqhFgbbKwnb9MLmUQDhG6
-fn parse_uri_format(input: &mut &str) -> winnow::Result<String> {
-    preceded("spotify:playlist:", parse_raw_id).parse_next(input)
+fn parse_uri_format(input: &mut &str, expected: SpotifyResourceType) -> winnow::Result<String> {
+    preceded(
+        (
+            "spotify:",
+            parse_resource_type.verify(|found| *found == expected),
+            ":",
+        ),
+        parse_raw_id,
+    )
+    .parse_next(input)
 }
 
-/// Parse raw ID format: 6rqhFgbbKwnb9MLmUQDhG6
+/// Parse raw ID format: 6rqhFgbbKwnb9MLmUQDhG6, Spotify IDs are base62 so only ASCII letters and digits
 fn parse_raw_id(input: &mut &str) -> winnow::Result<String> {
-    take_while(1.., |c: char| c.is_alphanumeric())
+    take_while(1.., |c: char| c.is_ascii_alphanumeric())
         .verify(|id: &str| !id.is_empty())
         .map(|id: &str| id.to_string())
         .parse_next(input)
@@ -142,5 +275,136 @@ mod tests {
         assert!(SpotifyId::parse("invalid-url").is_err());
         assert!(SpotifyId::parse("http://invalid.com/playlist/abc").is_err());
         assert!(SpotifyId::parse("spotify:invalid:abc").is_err());
+        assert!(SpotifyId::parse("https://open.spotify.com/album/0ETFjACtuP2ADo6LFhL6HN").is_err());
+        assert_eq!(
+            SpotifyId::parse("invalid-url").unwrap_err().to_string(),
+            "Invalid Spotify ID format: invalid-url"
+        );
+    }
+
+    #[test]
+    fn test_parse_album() {
+        assert_eq!(
+            SpotifyId::parse_album("https://open.spotify.com/album/0ETFjACtuP2ADo6LFhL6HN?si=abc")
+                .unwrap()
+                .as_str(),
+            "0ETFjACtuP2ADo6LFhL6HN"
+        );
+        assert_eq!(
+            SpotifyId::parse_album("spotify:album:0ETFjACtuP2ADo6LFhL6HN")
+                .unwrap()
+                .as_str(),
+            "0ETFjACtuP2ADo6LFhL6HN"
+        );
+        assert!(
+            SpotifyId::parse_album("https://open.spotify.com/playlist/6rqhFgbbKwnb9MLmUQDhG6")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_urls_keep_the_resource_type() {
+        let track = SpotifyId::parse_track("spotify:track:4uLU6hMCjMI75M1A2tKUQC").unwrap();
+        assert_eq!(track.resource_type(), SpotifyResourceType::Track);
+        assert_eq!(
+            track.as_url(),
+            "https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC"
+        );
+        assert_eq!(track.as_uri(), "spotify:track:4uLU6hMCjMI75M1A2tKUQC");
+
+        let album = SpotifyId::parse_album("0ETFjACtuP2ADo6LFhL6HN").unwrap();
+        assert_eq!(
+            album.as_url(),
+            "https://open.spotify.com/album/0ETFjACtuP2ADo6LFhL6HN"
+        );
+        assert_eq!(album.as_uri(), "spotify:album:0ETFjACtuP2ADo6LFhL6HN");
+
+        assert!(SpotifyId::parse_track("spotify:album:0ETFjACtuP2ADo6LFhL6HN").is_err());
+        assert!(SpotifyId::parse("https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC").is_err());
+    }
+
+    #[test]
+    fn test_serialize_as_uri_or_url() {
+        let id = SpotifyId::parse("6rqhFgbbKwnb9MLmUQDhG6").unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&id).unwrap(),
+            "\"spotify:playlist:6rqhFgbbKwnb9MLmUQDhG6\""
+        );
+        assert_eq!(
+            serde_json::to_string(&SpotifyIdAsUrl::from(id)).unwrap(),
+            "\"https://open.spotify.com/playlist/6rqhFgbbKwnb9MLmUQDhG6\""
+        );
+    }
+
+    #[test]
+    fn test_deserialize_parses_id() {
+        let id: SpotifyId =
+            serde_json::from_str("\"https://open.spotify.com/playlist/6rqhFgbbKwnb9MLmUQDhG6\"")
+                .unwrap();
+        assert_eq!(id.as_str(), "6rqhFgbbKwnb9MLmUQDhG6");
+        assert!(serde_json::from_str::<SpotifyId>("\"not a spotify id\"").is_err());
+        assert!(serde_json::from_str::<SpotifyId>("42").is_err());
+    }
+
+    #[test]
+    fn test_serde_keeps_the_resource_type() {
+        for id in [
+            SpotifyId::parse("6rqhFgbbKwnb9MLmUQDhG6").unwrap(),
+            SpotifyId::parse_album("0ETFjACtuP2ADo6LFhL6HN").unwrap(),
+            SpotifyId::parse_track("4uLU6hMCjMI75M1A2tKUQC").unwrap(),
+        ] {
+            let json = serde_json::to_string(&id).unwrap();
+            assert_eq!(serde_json::from_str::<SpotifyId>(&json).unwrap(), id);
+        }
+
+        // Raw IDs stored before the URI was serialized are playlists
+        let id: SpotifyId = serde_json::from_str("\"6rqhFgbbKwnb9MLmUQDhG6\"").unwrap();
+        assert_eq!(id.resource_type(), SpotifyResourceType::Playlist);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_raw_ids_outside_ascii_alphanumerics_are_rejected(
+            before in "[a-zA-Z0-9]{0,11}",
+            invalid in r"[^a-zA-Z0-9]",
+            after in "[a-zA-Z0-9]{0,11}",
+        ) {
+            let input = format!("{before}{invalid}{after}");
+            proptest::prop_assert!(SpotifyId::parse(&input).is_err());
+            proptest::prop_assert!(SpotifyId::parse_album(&input).is_err());
+        }
+
+        #[test]
+        fn test_uris_of_other_types_are_rejected(
+            resource_type in "[a-z]{1,10}",
+            id in "[a-zA-Z0-9]{22}",
+        ) {
+            proptest::prop_assume!(resource_type != "playlist");
+            let uri = format!("spotify:{resource_type}:{id}");
+            proptest::prop_assert!(SpotifyId::parse(&uri).is_err());
+        }
+
+        #[test]
+        fn test_urls_with_path_segments_before_the_type_are_rejected(
+            segment in "[a-z0-9-]{1,10}",
+            id in "[a-zA-Z0-9]{22}",
+        ) {
+            let url = format!("https://open.spotify.com/{segment}/playlist/{id}");
+            proptest::prop_assert!(SpotifyId::parse(&url).is_err());
+        }
+
+        #[test]
+        fn test_long_inputs_fail_without_panicking(input in ".{1001,2000}") {
+            let uri = format!("spotify:playlist:{input}");
+            proptest::prop_assert!(SpotifyId::parse(&input).is_err());
+            proptest::prop_assert!(SpotifyId::parse(&uri).is_err());
+        }
+    }
+
+    #[test]
+    fn test_raw_ids_with_slashes_are_rejected() {
+        assert!(SpotifyId::parse("abc/def").is_err());
+        assert!(SpotifyId::parse("spotify:playlist:abc/def").is_err());
     }
 }
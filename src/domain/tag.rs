@@ -0,0 +1,77 @@
+use serde::Serialize;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+const MAX_TAG_LENGTH: usize = 50;
+
+/// A free-form label used to organize playlists, e.g. `game-night`.
+///
+/// Tags are normalized to a trimmed, lowercase form so `Game Night ` and
+/// `game night` refer to the same tag.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct Tag(String);
+
+impl Tag {
+    pub fn parse(input: &str) -> Result<Self, TagError> {
+        let normalized = input.trim().to_lowercase();
+        if normalized.is_empty() {
+            return Err(TagError::Empty);
+        }
+
+        let length = normalized.chars().count();
+        if length > MAX_TAG_LENGTH {
+            return Err(TagError::TooLong(length));
+        }
+
+        Ok(Self(normalized))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Tag {
+    type Err = TagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl From<Tag> for String {
+    fn from(tag: Tag) -> Self {
+        tag.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, displaydoc::Display, thiserror::Error)]
+pub enum TagError {
+    /// Tag cannot be empty
+    Empty,
+    /// Tag is too long ({0} characters), the maximum is 50
+    TooLong(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_are_normalized() {
+        assert_eq!(Tag::parse("  Game-Night ").unwrap().as_str(), "game-night");
+        assert_eq!(Tag::parse("Game-Night"), Tag::parse("game-night"));
+    }
+
+    #[test]
+    fn test_invalid_tags() {
+        assert_eq!(Tag::parse("   "), Err(TagError::Empty));
+        assert_eq!(Tag::parse(&"a".repeat(51)), Err(TagError::TooLong(51)));
+    }
+}
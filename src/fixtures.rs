@@ -0,0 +1,186 @@
+//! Fixtures shared by the unit tests
+
+use crate::application::playlist_service::PlaylistService;
+use crate::application::{
+    IPdfGenerator, IPlaylistRepository, ISpotifyClient, PdfGenerator, PdfGeneratorConfig, worker,
+};
+use crate::domain::{Playlist, PlaylistId, SessionId, Track};
+use crate::infrastructure::playlist::MockPlaylistRepository;
+use crate::infrastructure::spotify::MockSpotifyClient;
+use crate::infrastructure::{EventStore, JobsRepository, PlaylistRepository, test_pool};
+use crate::web::controllers::jobs::SseLimits;
+use crate::web::server::Services;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Playlist with the given tracks, not imported from Spotify
+pub(crate) fn playlist(name: &str, tracks: Vec<Track>) -> Playlist {
+    Playlist {
+        id: PlaylistId::new(),
+        spotify_id: None,
+        name: name.to_string(),
+        created_at: None,
+        updated_at: None,
+        tracks,
+        back_text: None,
+        spotify_track_count: None,
+        custom_name: None,
+    }
+}
+
+/// "Song {id}" by "Artist" from 1990, with only what every track has
+pub(crate) fn track(id: impl std::fmt::Display) -> Track {
+    Track {
+        title: format!("Song {id}"),
+        artist: "Artist".to_string(),
+        year: 1990,
+        spotify_url: format!("https://open.spotify.com/track/{id}"),
+        album_cover_url: None,
+        popularity: None,
+        duration_ms: None,
+        notes: None,
+    }
+}
+
+/// Tracks "Song 0" up to, but not including, "Song {count}"
+pub(crate) fn tracks(count: usize) -> Vec<Track> {
+    (0..count).map(track).collect()
+}
+
+pub(crate) type TestPlaylistService<SC = MockSpotifyClient, PR = MockPlaylistRepository> =
+    PlaylistService<SC, PR, JobsRepository, PdfGenerator, EventStore>;
+
+/// Service storing playlists in memory, jobs and events still go to SQLite
+pub(crate) async fn playlist_service(
+    spotify_client: MockSpotifyClient,
+) -> (TestPlaylistService, Arc<MockPlaylistRepository>) {
+    let (service, playlist_repository, _) = playlist_service_with(
+        spotify_client,
+        MockPlaylistRepository::new(),
+        test_pool().await,
+        false,
+    )
+    .await;
+    (service, playlist_repository)
+}
+
+/// Service storing everything in SQLite
+pub(crate) async fn sqlite_playlist_service(
+    spotify_client: MockSpotifyClient,
+) -> (
+    TestPlaylistService<MockSpotifyClient, PlaylistRepository>,
+    Arc<PlaylistRepository>,
+    Arc<JobsRepository>,
+) {
+    let pool = test_pool().await;
+    let playlist_repository = PlaylistRepository::new(pool.clone()).await.unwrap();
+    playlist_service_with(spotify_client, playlist_repository, pool, false).await
+}
+
+/// Service wired to the given Spotify client and playlist repository, with
+/// workers that are not started
+pub(crate) async fn playlist_service_with<SC: ISpotifyClient, PR: IPlaylistRepository>(
+    spotify_client: SC,
+    playlist_repository: PR,
+    pool: SqlitePool,
+    dedup_on_import: bool,
+) -> (TestPlaylistService<SC, PR>, Arc<PR>, Arc<JobsRepository>) {
+    let playlist_repository = Arc::new(playlist_repository);
+    let jobs_repository = Arc::new(JobsRepository::new(pool.clone()));
+    let event_store = Arc::new(EventStore::new(pool));
+    let spotify_client = Arc::new(spotify_client);
+
+    let pdf_worker = worker::Worker::new(
+        jobs_repository.clone(),
+        Arc::new(worker::GeneratePlaylistPdfsState {
+            playlist_repository: playlist_repository.clone(),
+            pdf_generator: Arc::new(PdfGenerator::new()),
+            event_store: event_store.clone(),
+            pdf_config: Default::default(),
+        }),
+    );
+    let refetch_worker = worker::Worker::new(
+        jobs_repository.clone(),
+        Arc::new(worker::RefetchPlaylistState {
+            playlist_repository: playlist_repository.clone(),
+            spotify_client: spotify_client.clone(),
+            event_store: event_store.clone(),
+            jobs_repository: jobs_repository.clone(),
+            dedup_on_import,
+        }),
+    );
+
+    let service = PlaylistService::new(
+        playlist_repository.clone(),
+        spotify_client,
+        jobs_repository.clone(),
+        event_store,
+        Arc::new(pdf_worker),
+        Arc::new(refetch_worker),
+        dedup_on_import,
+        1000,
+    );
+    (service, playlist_repository, jobs_repository)
+}
+
+/// Handler state around the given service
+pub(crate) fn services<SC: ISpotifyClient, PR: IPlaylistRepository>(
+    playlist_service: TestPlaylistService<SC, PR>,
+) -> Services<TestPlaylistService<SC, PR>> {
+    Services {
+        playlist_service: Arc::new(playlist_service),
+        prefer_websocket: false,
+        sse_limits: SseLimits::new(10, Duration::from_secs(60)),
+    }
+}
+
+/// Playlist stored in SQLite for a new session, with the state to generate its PDFs
+pub(crate) struct StoredPlaylist<PG: IPdfGenerator> {
+    pub(crate) jobs_repository: Arc<JobsRepository>,
+    pub(crate) state: Arc<worker::GeneratePlaylistPdfsState<PlaylistRepository, PG, EventStore>>,
+    pub(crate) playlist: Playlist,
+    pub(crate) session_id: SessionId,
+}
+
+/// Stores an empty playlist named `name` whose PDFs come from `pdf_generator`
+pub(crate) async fn stored_playlist<PG: IPdfGenerator>(
+    name: &str,
+    pdf_generator: PG,
+) -> StoredPlaylist<PG> {
+    let pool = test_pool().await;
+    let playlist_repository = Arc::new(PlaylistRepository::new(pool.clone()).await.unwrap());
+    let session_id = SessionId::new();
+    let playlist = playlist_repository
+        .create(&session_id, &playlist(name, vec![]))
+        .await
+        .unwrap();
+
+    StoredPlaylist {
+        jobs_repository: Arc::new(JobsRepository::new(pool.clone())),
+        state: Arc::new(worker::GeneratePlaylistPdfsState {
+            playlist_repository,
+            pdf_generator: Arc::new(pdf_generator),
+            event_store: Arc::new(EventStore::new(pool)),
+            pdf_config: PdfGeneratorConfig::default(),
+        }),
+        playlist,
+        session_id,
+    }
+}
+
+/// State refetching playlists from `spotify_client`, with jobs and events in SQLite
+pub(crate) async fn refetch_state<PR: IPlaylistRepository>(
+    playlist_repository: Arc<PR>,
+    spotify_client: MockSpotifyClient,
+    dedup_on_import: bool,
+) -> worker::RefetchPlaylistState<PR, MockSpotifyClient, EventStore, JobsRepository> {
+    let pool = test_pool().await;
+    worker::RefetchPlaylistState {
+        playlist_repository,
+        spotify_client: Arc::new(spotify_client),
+        event_store: Arc::new(EventStore::new(pool.clone())),
+        jobs_repository: Arc::new(JobsRepository::new(pool)),
+        dedup_on_import,
+    }
+}
@@ -10,6 +10,9 @@ pub struct PlaylistEntity {
     pub name: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub back_text: Option<String>,
+    pub spotify_track_count: Option<u32>,
+    pub custom_name: Option<String>,
 }
 
 #[derive(FromRow, Debug, Clone)]
@@ -21,6 +24,9 @@ pub struct TrackEntity {
     pub year: i32,
     pub spotify_url: String,
     pub album_cover_url: Option<String>,
+    pub popularity: Option<u8>,
+    pub duration_ms: Option<u32>,
+    pub notes: Option<String>,
     pub position: i32,
 }
 
@@ -32,6 +38,8 @@ pub struct JobEntity {
     pub completed_at: Option<DateTime<Utc>>,
     pub payload: serde_json::Value,
     pub result: Option<serde_json::Value>,
+    pub error_message: Option<String>,
+    pub progress: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, sqlx::Type)]
@@ -41,21 +49,30 @@ pub enum JobStatusEntity {
     Pending,
     #[sqlx(rename = "processing")]
     Processing,
+    #[sqlx(rename = "retrying")]
+    Retrying,
     #[sqlx(rename = "completed")]
     Completed,
     #[sqlx(rename = "failed")]
     Failed,
+    #[sqlx(rename = "cancelled")]
+    Cancelled,
 }
 
 impl From<PlaylistEntity> for domain::Playlist {
     fn from(entity: PlaylistEntity) -> Self {
         Self {
             id: entity.id.into(),
-            spotify_id: entity.spotify_id.and_then(|s| s.parse().ok()),
+            spotify_id: entity
+                .spotify_id
+                .and_then(|s| domain::SpotifyId::parse_any_type(&s).ok()),
             name: entity.name,
             tracks: Vec::new(), // Tracks will be loaded separately
             created_at: Some(entity.created_at),
             updated_at: entity.updated_at,
+            back_text: entity.back_text,
+            spotify_track_count: entity.spotify_track_count,
+            custom_name: entity.custom_name,
         }
     }
 }
@@ -68,6 +85,9 @@ impl From<TrackEntity> for domain::Track {
             year: entity.year,
             spotify_url: entity.spotify_url,
             album_cover_url: entity.album_cover_url,
+            popularity: entity.popularity,
+            duration_ms: entity.duration_ms,
+            notes: entity.notes,
         }
     }
 }
@@ -92,6 +112,10 @@ impl From<JobEntity> for domain::Job {
             completed_at: entity.completed_at,
             payload: entity.payload,
             result: entity.result,
+            error_message: entity.error_message,
+            progress: entity
+                .progress
+                .and_then(|progress| serde_json::from_value(progress).ok()),
         }
     }
 }
@@ -101,8 +125,10 @@ impl From<JobStatusEntity> for domain::JobStatus {
         match status {
             JobStatusEntity::Pending => domain::JobStatus::Pending,
             JobStatusEntity::Processing => domain::JobStatus::Processing,
+            JobStatusEntity::Retrying => domain::JobStatus::Retrying,
             JobStatusEntity::Completed => domain::JobStatus::Completed,
             JobStatusEntity::Failed => domain::JobStatus::Failed,
+            JobStatusEntity::Cancelled => domain::JobStatus::Cancelled,
         }
     }
 }
@@ -118,6 +144,10 @@ impl From<domain::Job> for JobEntity {
             completed_at: job.completed_at,
             payload: job.payload,
             result: job.result,
+            error_message: job.error_message,
+            progress: job
+                .progress
+                .and_then(|progress| serde_json::to_value(progress).ok()),
         }
     }
 }
@@ -127,8 +157,10 @@ impl From<domain::JobStatus> for JobStatusEntity {
         match status {
             domain::JobStatus::Pending => JobStatusEntity::Pending,
             domain::JobStatus::Processing => JobStatusEntity::Processing,
+            domain::JobStatus::Retrying => JobStatusEntity::Retrying,
             domain::JobStatus::Completed => JobStatusEntity::Completed,
             domain::JobStatus::Failed => JobStatusEntity::Failed,
+            domain::JobStatus::Cancelled => JobStatusEntity::Cancelled,
         }
     }
 }
@@ -143,6 +175,9 @@ impl From<domain::Track> for TrackEntity {
             year: track.year,
             spotify_url: track.spotify_url,
             album_cover_url: track.album_cover_url,
+            popularity: track.popularity,
+            duration_ms: track.duration_ms,
+            notes: track.notes,
             position: 0, // Will be set when saving to database
         }
     }
@@ -0,0 +1,87 @@
+use crate::application::IEventStore;
+use crate::domain::DomainEvent;
+use anyhow::Context;
+use sqlx::{Pool, Sqlite};
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct EventStore {
+    pool: Pool<Sqlite>,
+}
+
+impl EventStore {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+impl IEventStore for EventStore {
+    async fn append(&self, event: DomainEvent) -> anyhow::Result<()> {
+        let payload = serde_json::to_string(&event)
+            .with_context(|| format!("while serializing {} event", event.event_type()))?;
+
+        sqlx::query(
+            "INSERT INTO events (id, event_type, playlist_id, payload) VALUES (?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(event.event_type())
+        .bind(event.playlist_id().to_string())
+        .bind(payload)
+        .execute(&self.pool)
+        .await
+        .with_context(|| {
+            format!(
+                "while storing {} event of playlist {}",
+                event.event_type(),
+                event.playlist_id()
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{JobId, PlaylistId};
+    use crate::infrastructure::test_pool;
+
+    #[tokio::test]
+    async fn test_append_stores_events_of_the_playlist() {
+        let pool = test_pool().await;
+        let store = EventStore::new(pool.clone());
+        let playlist_id = PlaylistId::new();
+
+        store
+            .append(DomainEvent::PlaylistCreated {
+                playlist_id: playlist_id.clone(),
+                spotify_id: None,
+                track_count: 3,
+            })
+            .await
+            .unwrap();
+        store
+            .append(DomainEvent::PdfGenerationCompleted {
+                playlist_id: playlist_id.clone(),
+                job_id: JobId::new(),
+                duration_ms: 120,
+            })
+            .await
+            .unwrap();
+
+        let events: Vec<(String, String)> = sqlx::query_as(
+            "SELECT event_type, payload FROM events WHERE playlist_id = ? ORDER BY rowid",
+        )
+        .bind(playlist_id.to_string())
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, "playlist_created");
+        assert_eq!(events[1].0, "pdf_generation_completed");
+        let payload: serde_json::Value = serde_json::from_str(&events[0].1).unwrap();
+        assert_eq!(payload["type"], "playlist_created");
+        assert_eq!(payload["track_count"], 3);
+    }
+}
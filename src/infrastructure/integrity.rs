@@ -0,0 +1,73 @@
+//! Startup checks for databases corrupted by crashes during writes
+
+use anyhow::{Context, bail};
+use sqlx::{Row, SqlitePool};
+use tracing::{error, info};
+
+/// Runs `PRAGMA integrity_check` and `PRAGMA foreign_key_check`, logging every
+/// violation and failing if there is any
+pub async fn check_database_integrity(pool: &SqlitePool) -> anyhow::Result<()> {
+    let mut violations = 0;
+
+    let integrity = sqlx::query("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await
+        .context("while checking database integrity")?;
+    for row in integrity {
+        let message: String = row.try_get(0)?;
+        if message != "ok" {
+            error!("Database integrity check failed: {}", message);
+            violations += 1;
+        }
+    }
+
+    let foreign_keys = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(pool)
+        .await
+        .context("while checking database foreign keys")?;
+    for row in foreign_keys {
+        let table: String = row.try_get("table")?;
+        let rowid: Option<i64> = row.try_get("rowid")?;
+        let parent: String = row.try_get("parent")?;
+        error!(
+            "Row {:?} of table {} references a missing row of {}",
+            rowid, table, parent
+        );
+        violations += 1;
+    }
+
+    if violations > 0 {
+        bail!("Database has {violations} integrity violations, refusing to start");
+    }
+    info!("Database integrity check passed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_pool;
+
+    #[tokio::test]
+    async fn test_migrated_database_passes() {
+        let pool = test_pool().await;
+        check_database_integrity(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dangling_foreign_key_fails() {
+        let pool = test_pool().await;
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO playlist_tags (playlist_id, tag_id) VALUES (X'00', X'01')")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        assert!(check_database_integrity(&pool).await.is_err());
+    }
+}
@@ -1,7 +1,8 @@
-use crate::application::IJobsRepository;
+use crate::application::{IJobsRepository, JobFilter, JobsPage, NamedJobsPage, Pagination};
 use crate::domain;
-use crate::infrastructure::entities::JobEntity;
-use sqlx::{Pool, Sqlite};
+use crate::infrastructure::entities::{JobEntity, JobStatusEntity};
+use anyhow::Context;
+use sqlx::{FromRow, Pool, QueryBuilder, Row, Sqlite};
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -13,6 +14,49 @@ impl JobsRepository {
     pub fn new(pool: Pool<Sqlite>) -> Self {
         Self { pool }
     }
+
+    async fn count(&self, filter: &JobFilter) -> anyhow::Result<u64> {
+        let mut count_query = QueryBuilder::new("SELECT COUNT(*) FROM jobs");
+        push_job_filter(&mut count_query, filter);
+        let total: i64 = count_query
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .context("while counting jobs")?;
+        Ok(total as u64)
+    }
+}
+
+/// Appends the `WHERE` clause matching `filter` to `query`
+fn push_job_filter<'a>(query: &mut QueryBuilder<'a, Sqlite>, filter: &'a JobFilter) {
+    query.push(" WHERE 1 = 1");
+    if let Some(from) = filter.from {
+        query
+            .push(" AND datetime(created_at) >= datetime(")
+            .push_bind(from)
+            .push(")");
+    }
+    if let Some(to) = filter.to {
+        query
+            .push(" AND datetime(created_at) <= datetime(")
+            .push_bind(to)
+            .push(")");
+    }
+    if let Some(status) = &filter.status {
+        query
+            .push(" AND status = ")
+            .push_bind(JobStatusEntity::from(status.clone()));
+    }
+    if let Some(job_type) = &filter.filter_by_type {
+        query
+            .push(" AND json_extract(payload, '$.type') = ")
+            .push_bind(job_type);
+    }
+    if let Some(session_id) = &filter.session_id {
+        query
+            .push(" AND json_extract(payload, '$.session_id') = ")
+            .push_bind(session_id.to_string());
+    }
 }
 
 impl IJobsRepository for JobsRepository {
@@ -20,27 +64,30 @@ impl IJobsRepository for JobsRepository {
         let entity: JobEntity = job.clone().into();
 
         sqlx::query(
-            "INSERT INTO jobs (id, status, created_at, payload, result) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO jobs (id, status, created_at, payload, result, error_message) VALUES (?, ?, ?, ?, ?, ?)",
         )
         .bind(entity.id)
         .bind(entity.status)
         .bind(entity.created_at)
         .bind(entity.payload)
         .bind(entity.result)
+        .bind(entity.error_message)
         .execute(&self.pool)
-        .await?;
+        .await
+        .with_context(|| format!("while creating job {}", job.id))?;
 
         Ok(job)
     }
 
-    async fn get(&self, id: &domain::JobId) -> anyhow::Result<Option<domain::Job>> {
-        let id: Uuid = id.clone().into();
+    async fn get(&self, job_id: &domain::JobId) -> anyhow::Result<Option<domain::Job>> {
+        let id: Uuid = job_id.clone().into();
         let job_entity = sqlx::query_as::<_, JobEntity>(
-            "SELECT id, status, created_at, completed_at, payload, result FROM jobs WHERE id = ?",
+            "SELECT id, status, created_at, completed_at, payload, result, error_message, progress FROM jobs WHERE id = ?",
         )
         .bind(id)
         .fetch_optional(&self.pool)
-        .await?;
+        .await
+        .with_context(|| format!("while loading job {job_id}"))?;
 
         Ok(job_entity.map(domain::Job::from))
     }
@@ -49,19 +96,72 @@ impl IJobsRepository for JobsRepository {
         let entity: JobEntity = job.clone().into();
 
         sqlx::query(
-            "UPDATE jobs SET status = ?, completed_at = ?, payload = ?, result = ? WHERE id = ?",
+            "UPDATE jobs SET status = ?, completed_at = ?, payload = ?, result = ?, error_message = ? WHERE id = ?",
         )
         .bind(entity.status)
         .bind(entity.completed_at)
         .bind(entity.payload)
         .bind(entity.result)
+        .bind(entity.error_message)
         .bind(entity.id)
         .execute(&self.pool)
-        .await?;
+        .await
+        .with_context(|| format!("while updating job {}", job.id))?;
 
         Ok(job)
     }
 
+    async fn update_if_status(
+        &self,
+        job: domain::Job,
+        expected: &[domain::JobStatus],
+    ) -> anyhow::Result<bool> {
+        let entity: JobEntity = job.clone().into();
+
+        let mut query = QueryBuilder::new("UPDATE jobs SET status = ");
+        query
+            .push_bind(entity.status)
+            .push(", completed_at = ")
+            .push_bind(entity.completed_at)
+            .push(", payload = ")
+            .push_bind(entity.payload)
+            .push(", result = ")
+            .push_bind(entity.result)
+            .push(", error_message = ")
+            .push_bind(entity.error_message)
+            .push(" WHERE id = ")
+            .push_bind(entity.id)
+            .push(" AND status IN (");
+        let mut statuses = query.separated(", ");
+        for status in expected {
+            statuses.push_bind(JobStatusEntity::from(status.clone()));
+        }
+        query.push(")");
+
+        let result = query
+            .build()
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("while updating job {}", job.id))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn cancel(&self, job_id: &domain::JobId) -> anyhow::Result<bool> {
+        let id: Uuid = job_id.clone().into();
+        let result = sqlx::query(
+            "UPDATE jobs SET status = 'cancelled', completed_at = ?
+             WHERE id = ? AND status NOT IN ('completed', 'failed', 'cancelled')",
+        )
+        .bind(chrono::Utc::now())
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("while cancelling job {job_id}"))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
     async fn get_by_playlist_id(
         &self,
         playlist_id: &crate::domain::PlaylistId,
@@ -69,14 +169,663 @@ impl IJobsRepository for JobsRepository {
         let playlist_id_str = playlist_id.to_string();
 
         let job_entities = sqlx::query_as::<_, JobEntity>(
-            "SELECT id, status, created_at, completed_at, payload, result FROM jobs 
+            "SELECT id, status, created_at, completed_at, payload, result, error_message, progress FROM jobs 
              WHERE json_extract(payload, '$.playlist_id') = ? 
              ORDER BY created_at DESC",
         )
         .bind(playlist_id_str)
         .fetch_all(&self.pool)
-        .await?;
+        .await
+        .with_context(|| format!("while loading jobs of playlist {playlist_id}"))?;
 
         Ok(job_entities.into_iter().map(domain::Job::from).collect())
     }
+
+    async fn get_by_playlist_id_since(
+        &self,
+        playlist_id: &crate::domain::PlaylistId,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<domain::Job>> {
+        // julianday keeps the fractional seconds that datetime would drop
+        let job_entities = sqlx::query_as::<_, JobEntity>(
+            "SELECT id, status, created_at, completed_at, payload, result, error_message, progress FROM jobs
+             WHERE json_extract(payload, '$.playlist_id') = ?
+             AND julianday(created_at) >= julianday(?)
+             ORDER BY created_at DESC",
+        )
+        .bind(playlist_id.to_string())
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("while loading jobs of playlist {playlist_id} since {since}"))?;
+
+        Ok(job_entities.into_iter().map(domain::Job::from).collect())
+    }
+
+    async fn get_latest_by_type(
+        &self,
+        playlist_id: &domain::PlaylistId,
+        job_type: domain::JobType,
+    ) -> anyhow::Result<Option<domain::Job>> {
+        let job_entity = sqlx::query_as::<_, JobEntity>(
+            "SELECT id, status, created_at, completed_at, payload, result, error_message, progress FROM jobs
+             WHERE json_extract(payload, '$.playlist_id') = ? AND json_extract(payload, '$.type') = ?
+             ORDER BY created_at DESC
+             LIMIT 1",
+        )
+        .bind(playlist_id.to_string())
+        .bind(job_type.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .with_context(|| format!("while loading latest {job_type:?} job of playlist {playlist_id}"))?;
+
+        Ok(job_entity.map(domain::Job::from))
+    }
+
+    async fn list(&self, filter: &JobFilter, pagination: Pagination) -> anyhow::Result<JobsPage> {
+        let total = self.count(filter).await?;
+
+        let mut query = QueryBuilder::new(
+            "SELECT id, status, created_at, completed_at, payload, result, error_message, progress FROM jobs",
+        );
+        push_job_filter(&mut query, filter);
+        query
+            .push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(pagination.per_page)
+            .push(" OFFSET ")
+            .push_bind(pagination.offset());
+        let job_entities = query
+            .build_query_as::<JobEntity>()
+            .fetch_all(&self.pool)
+            .await
+            .context("while listing jobs")?;
+
+        Ok(JobsPage {
+            jobs: job_entities.into_iter().map(domain::Job::from).collect(),
+            total,
+        })
+    }
+
+    async fn list_with_playlist_names(
+        &self,
+        filter: &JobFilter,
+        pagination: Pagination,
+    ) -> anyhow::Result<NamedJobsPage> {
+        let total = self.count(filter).await?;
+
+        // Payloads hold the playlist ID as a hyphenated string, playlists store it as a blob
+        let mut query = QueryBuilder::new(
+            "SELECT j.id, j.status, j.created_at, j.completed_at, j.payload, j.result, j.error_message, j.progress,
+                    COALESCE(p.custom_name, p.name) AS playlist_name
+             FROM (SELECT id, status, created_at, completed_at, payload, result, error_message, progress FROM jobs",
+        );
+        push_job_filter(&mut query, filter);
+        query
+            .push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(pagination.per_page)
+            .push(" OFFSET ")
+            .push_bind(pagination.offset())
+            .push(
+                ") j LEFT JOIN playlists p
+                 ON p.id = unhex(replace(json_extract(j.payload, '$.playlist_id'), '-', ''))
+                 ORDER BY j.created_at DESC",
+            );
+        let rows = query
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .context("while listing jobs with their playlist names")?;
+
+        let jobs = rows
+            .iter()
+            .map(|row| {
+                let job = domain::Job::from(JobEntity::from_row(row)?);
+                Ok((job, row.try_get("playlist_name")?))
+            })
+            .collect::<Result<_, sqlx::Error>>()
+            .context("while reading jobs with their playlist names")?;
+        Ok(NamedJobsPage { jobs, total })
+    }
+
+    async fn get_pending_count(&self) -> anyhow::Result<u64> {
+        self.count_by_status(&domain::JobStatus::Pending).await
+    }
+
+    async fn count_by_status(&self, status: &domain::JobStatus) -> anyhow::Result<u64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE status = ?")
+            .bind(JobStatusEntity::from(status.clone()))
+            .fetch_one(&self.pool)
+            .await
+            .with_context(|| format!("while counting {status} jobs"))?;
+
+        Ok(count as u64)
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .context("while pinging the database")?;
+        Ok(())
+    }
+
+    async fn get_pending_by_type(
+        &self,
+        job_type: domain::JobType,
+    ) -> anyhow::Result<Vec<domain::Job>> {
+        let job_entities = sqlx::query_as::<_, JobEntity>(
+            "SELECT id, status, created_at, completed_at, payload, result, error_message, progress FROM jobs
+             WHERE status = ? AND json_extract(payload, '$.type') = ?
+             ORDER BY created_at ASC",
+        )
+        .bind(JobStatusEntity::Pending)
+        .bind(job_type.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("while loading pending {job_type:?} jobs"))?;
+
+        Ok(job_entities.into_iter().map(domain::Job::from).collect())
+    }
+
+    async fn reset_processing(&self) -> anyhow::Result<u64> {
+        let result = sqlx::query("UPDATE jobs SET status = ? WHERE status IN (?, ?)")
+            .bind(JobStatusEntity::Pending)
+            .bind(JobStatusEntity::Processing)
+            .bind(JobStatusEntity::Retrying)
+            .execute(&self.pool)
+            .await
+            .context("while resetting processing jobs")?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_older_than(
+        &self,
+        cutoff: chrono::DateTime<chrono::Utc>,
+        keep_statuses: &[domain::JobStatus],
+    ) -> anyhow::Result<u64> {
+        let mut query = QueryBuilder::new(
+            "DELETE FROM jobs WHERE completed_at IS NOT NULL AND datetime(completed_at) < datetime(",
+        );
+        query.push_bind(cutoff).push(")");
+        if !keep_statuses.is_empty() {
+            query.push(" AND status NOT IN (");
+            let mut statuses = query.separated(", ");
+            for status in keep_statuses {
+                statuses.push_bind(JobStatusEntity::from(status.clone()));
+            }
+            query.push(")");
+        }
+
+        let result = query
+            .build()
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("while deleting jobs completed before {cutoff}"))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn claim_stale(
+        &self,
+        job_id: &domain::JobId,
+        stale_before: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let id: Uuid = job_id.clone().into();
+        let result = sqlx::query(
+            "UPDATE jobs SET requeued_at = ?
+             WHERE id = ? AND status IN ('pending', 'processing')
+             AND datetime(COALESCE(requeued_at, created_at)) < datetime(?)",
+        )
+        .bind(chrono::Utc::now())
+        .bind(id)
+        .bind(stale_before)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("while claiming stale job {job_id}"))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn set_progress(
+        &self,
+        job_id: &domain::JobId,
+        progress: domain::JobProgress,
+    ) -> anyhow::Result<()> {
+        let id: Uuid = job_id.clone().into();
+        sqlx::query("UPDATE jobs SET progress = ? WHERE id = ?")
+            .bind(serde_json::to_value(progress)?)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("while storing progress of job {job_id}"))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Job, JobId, JobStatus, JobType, SessionId};
+    use crate::fixtures;
+    use crate::infrastructure::test_pool;
+
+    fn job(job_type: JobType, status: JobStatus) -> Job {
+        let mut job = Job::new(serde_json::json!({
+            "type": job_type,
+            "playlist_id": uuid::Uuid::new_v4(),
+        }));
+        job.status = status;
+        job
+    }
+
+    #[tokio::test]
+    async fn test_get_and_get_by_playlist_id_round_trip_every_column() {
+        let repository = JobsRepository::new(test_pool().await);
+        let mut stored = job(JobType::GeneratePlaylistPdfs, JobStatus::Pending);
+        let playlist_id = stored.playlist_id().unwrap();
+        repository.create(stored.clone()).await.unwrap();
+        stored.status = JobStatus::Failed;
+        stored.completed_at = Some(chrono::Utc::now());
+        stored.result = Some(serde_json::json!({"front": "front.pdf"}));
+        stored.error_message = Some("out of paper".to_string());
+        repository.update(stored.clone()).await.unwrap();
+        repository
+            .create(job(JobType::GeneratePlaylistPdfs, JobStatus::Pending))
+            .await
+            .unwrap();
+
+        let loaded = repository.get(&stored.id).await.unwrap().unwrap();
+        assert_eq!(loaded.status, stored.status);
+        assert_eq!(loaded.payload, stored.payload);
+        assert_eq!(loaded.result, stored.result);
+        assert_eq!(loaded.error_message, stored.error_message);
+        assert_eq!(
+            loaded.completed_at.map(|t| t.timestamp()),
+            stored.completed_at.map(|t| t.timestamp())
+        );
+        assert!(repository.get(&JobId::new()).await.unwrap().is_none());
+
+        let by_playlist = repository.get_by_playlist_id(&playlist_id).await.unwrap();
+        assert_eq!(by_playlist.len(), 1);
+        assert_eq!(by_playlist[0].id, stored.id);
+    }
+
+    #[tokio::test]
+    async fn test_update_if_status_leaves_cancelled_jobs_alone() {
+        let repository = JobsRepository::new(test_pool().await);
+        let mut stored = job(JobType::GeneratePlaylistPdfs, JobStatus::Pending);
+        repository.create(stored.clone()).await.unwrap();
+
+        stored.status = JobStatus::Processing;
+        let expected = [JobStatus::Pending, JobStatus::Retrying];
+        assert!(
+            repository
+                .update_if_status(stored.clone(), &expected)
+                .await
+                .unwrap()
+        );
+        // Already processing, a second worker cannot start it too
+        assert!(
+            !repository
+                .update_if_status(stored.clone(), &expected)
+                .await
+                .unwrap()
+        );
+
+        let mut cancelled = stored.clone();
+        cancelled.status = JobStatus::Cancelled;
+        repository.update(cancelled).await.unwrap();
+
+        stored.status = JobStatus::Completed;
+        assert!(
+            !repository
+                .update_if_status(stored.clone(), &[JobStatus::Processing])
+                .await
+                .unwrap()
+        );
+        let loaded = repository.get(&stored.id).await.unwrap().unwrap();
+        assert_eq!(loaded.status, JobStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_list_filters_by_type_and_status() {
+        let repository = JobsRepository::new(test_pool().await);
+        let failed_pdfs = repository
+            .create(job(JobType::GeneratePlaylistPdfs, JobStatus::Failed))
+            .await
+            .unwrap();
+        repository
+            .create(job(JobType::GeneratePlaylistPdfs, JobStatus::Completed))
+            .await
+            .unwrap();
+        repository
+            .create(job(JobType::RefetchPlaylist, JobStatus::Failed))
+            .await
+            .unwrap();
+        let all = Pagination {
+            page: 1,
+            per_page: 10,
+        };
+
+        let pdf_jobs = repository
+            .list(
+                &JobFilter {
+                    filter_by_type: Some(JobType::GeneratePlaylistPdfs.as_str().to_string()),
+                    ..Default::default()
+                },
+                all,
+            )
+            .await
+            .unwrap();
+        assert_eq!(pdf_jobs.total, 2);
+        assert!(
+            pdf_jobs
+                .jobs
+                .iter()
+                .all(|job| job.job_type() == Some(JobType::GeneratePlaylistPdfs))
+        );
+
+        let failed_pdf_jobs = repository
+            .list(
+                &JobFilter {
+                    status: Some(JobStatus::Failed),
+                    filter_by_type: Some(JobType::GeneratePlaylistPdfs.as_str().to_string()),
+                    ..Default::default()
+                },
+                all,
+            )
+            .await
+            .unwrap();
+        assert_eq!(failed_pdf_jobs.total, 1);
+        assert_eq!(failed_pdf_jobs.jobs[0].id, failed_pdfs.id);
+
+        let yesterday = chrono::Utc::now() - chrono::Duration::days(1);
+        let older_jobs = repository
+            .list(
+                &JobFilter {
+                    to: Some(yesterday),
+                    ..Default::default()
+                },
+                all,
+            )
+            .await
+            .unwrap();
+        assert_eq!(older_jobs.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_list_filters_by_session() {
+        let repository = JobsRepository::new(test_pool().await);
+        let session_id = SessionId::new();
+        let mut own = job(JobType::RefetchPlaylist, JobStatus::Pending);
+        own.payload["session_id"] = serde_json::to_value(&session_id).unwrap();
+        let own = repository.create(own).await.unwrap();
+        let mut other = job(JobType::RefetchPlaylist, JobStatus::Pending);
+        other.payload["session_id"] = serde_json::to_value(SessionId::new()).unwrap();
+        repository.create(other).await.unwrap();
+
+        let page = repository
+            .list(
+                &JobFilter {
+                    session_id: Some(session_id),
+                    ..Default::default()
+                },
+                Pagination {
+                    page: 1,
+                    per_page: 10,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.jobs[0].id, own.id);
+    }
+
+    #[tokio::test]
+    async fn test_list_with_playlist_names_joins_the_playlist() {
+        use crate::application::IPlaylistRepository;
+        use crate::infrastructure::PlaylistRepository;
+
+        let pool = test_pool().await;
+        let repository = JobsRepository::new(pool.clone());
+        let playlist = PlaylistRepository::new(pool)
+            .await
+            .unwrap()
+            .create(&SessionId::new(), &fixtures::playlist("Named", vec![]))
+            .await
+            .unwrap();
+        let named = repository
+            .create(Job::new(serde_json::json!({
+                "type": JobType::RefetchPlaylist,
+                "playlist_id": playlist.id,
+            })))
+            .await
+            .unwrap();
+        let orphaned = repository
+            .create(job(JobType::RefetchPlaylist, JobStatus::Pending))
+            .await
+            .unwrap();
+
+        let page = repository
+            .list_with_playlist_names(
+                &JobFilter::default(),
+                Pagination {
+                    page: 1,
+                    per_page: 10,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+        let name_of = |id: &JobId| {
+            page.jobs
+                .iter()
+                .find(|(job, _)| &job.id == id)
+                .and_then(|(_, name)| name.clone())
+        };
+        assert_eq!(name_of(&named.id).as_deref(), Some("Named"));
+        assert_eq!(name_of(&orphaned.id), None);
+    }
+
+    #[tokio::test]
+    async fn test_list_paginates() {
+        let repository = JobsRepository::new(test_pool().await);
+        for _ in 0..3 {
+            repository
+                .create(job(JobType::RefetchPlaylist, JobStatus::Completed))
+                .await
+                .unwrap();
+        }
+
+        let page = repository
+            .list(
+                &JobFilter::default(),
+                Pagination {
+                    page: 2,
+                    per_page: 2,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.jobs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_by_playlist_id_since() {
+        let repository = JobsRepository::new(test_pool().await);
+        let playlist_id = crate::domain::PlaylistId::new();
+        let job_at = |created_at| {
+            let mut job = Job::new(serde_json::json!({
+                "type": JobType::GeneratePlaylistPdfs,
+                "playlist_id": playlist_id,
+            }));
+            job.created_at = created_at;
+            job
+        };
+        let now = chrono::Utc::now();
+        repository
+            .create(job_at(now - chrono::Duration::hours(1)))
+            .await
+            .unwrap();
+        let recent = repository
+            .create(job_at(now - chrono::Duration::milliseconds(10)))
+            .await
+            .unwrap();
+        let newest = repository.create(job_at(now)).await.unwrap();
+        repository
+            .create(job(JobType::GeneratePlaylistPdfs, JobStatus::Pending))
+            .await
+            .unwrap();
+
+        let since = repository
+            .get_by_playlist_id_since(&playlist_id, now - chrono::Duration::milliseconds(10))
+            .await
+            .unwrap();
+        let ids: Vec<_> = since.into_iter().map(|job| job.id).collect();
+        assert_eq!(ids, [newest.id, recent.id]);
+    }
+
+    #[tokio::test]
+    async fn test_ping_fails_once_the_pool_is_closed() {
+        let pool = test_pool().await;
+        let repository = JobsRepository::new(pool.clone());
+        repository.ping().await.unwrap();
+
+        pool.close().await;
+        let err = repository.ping().await.unwrap_err();
+        assert_eq!(err.to_string(), "while pinging the database");
+    }
+
+    #[tokio::test]
+    async fn test_get_pending_count() {
+        let repository = JobsRepository::new(test_pool().await);
+        let mut jobs = Vec::new();
+        for _ in 0..3 {
+            jobs.push(
+                repository
+                    .create(job(JobType::GeneratePlaylistPdfs, JobStatus::Pending))
+                    .await
+                    .unwrap(),
+            );
+        }
+        repository
+            .create(job(JobType::RefetchPlaylist, JobStatus::Completed))
+            .await
+            .unwrap();
+        assert_eq!(repository.get_pending_count().await.unwrap(), 3);
+
+        let mut completed = jobs.remove(0);
+        completed.status = JobStatus::Completed;
+        repository.update(completed).await.unwrap();
+        assert_eq!(repository.get_pending_count().await.unwrap(), 2);
+        assert_eq!(
+            repository
+                .count_by_status(&JobStatus::Completed)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            repository
+                .count_by_status(&JobStatus::Failed)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reset_processing_makes_jobs_pending_again() {
+        let repository = JobsRepository::new(test_pool().await);
+        let interrupted = repository
+            .create(job(JobType::GeneratePlaylistPdfs, JobStatus::Processing))
+            .await
+            .unwrap();
+        let waiting = repository
+            .create(job(JobType::GeneratePlaylistPdfs, JobStatus::Pending))
+            .await
+            .unwrap();
+        let retrying = repository
+            .create(job(JobType::GeneratePlaylistPdfs, JobStatus::Retrying))
+            .await
+            .unwrap();
+        for (job_type, status) in [
+            (JobType::RefetchPlaylist, JobStatus::Pending),
+            (JobType::GeneratePlaylistPdfs, JobStatus::Completed),
+        ] {
+            repository.create(job(job_type, status)).await.unwrap();
+        }
+
+        assert_eq!(repository.reset_processing().await.unwrap(), 2);
+
+        let pending: Vec<_> = repository
+            .get_pending_by_type(JobType::GeneratePlaylistPdfs)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|job| job.id)
+            .collect();
+        assert_eq!(pending.len(), 3);
+        assert!(pending.contains(&interrupted.id));
+        assert!(pending.contains(&retrying.id));
+        assert!(pending.contains(&waiting.id));
+    }
+
+    #[tokio::test]
+    async fn test_delete_older_than_keeps_recent_and_in_flight_jobs() {
+        let repository = JobsRepository::new(test_pool().await);
+        let long_ago = chrono::Utc::now() - chrono::Duration::days(40);
+        for _ in 0..5 {
+            let mut old = job(JobType::GeneratePlaylistPdfs, JobStatus::Completed);
+            old.created_at = long_ago;
+            old.completed_at = Some(long_ago);
+            repository.create(old.clone()).await.unwrap();
+            repository.update(old).await.unwrap();
+        }
+        let mut recent = job(JobType::GeneratePlaylistPdfs, JobStatus::Failed);
+        recent.completed_at = Some(chrono::Utc::now());
+        let recent = repository.create(recent.clone()).await.unwrap();
+        repository.update(recent.clone()).await.unwrap();
+        let mut in_flight = Vec::new();
+        for status in [JobStatus::Pending, JobStatus::Processing] {
+            let mut job = job(JobType::RefetchPlaylist, status);
+            job.created_at = long_ago;
+            in_flight.push(repository.create(job).await.unwrap());
+        }
+
+        let deleted = repository
+            .delete_older_than(
+                chrono::Utc::now() - chrono::Duration::days(30),
+                &[JobStatus::Pending, JobStatus::Processing],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, 5);
+        assert!(repository.get(&recent.id).await.unwrap().is_some());
+        for job in in_flight {
+            assert!(repository.get(&job.id).await.unwrap().is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_progress_is_kept_by_updates() {
+        let repository = JobsRepository::new(test_pool().await);
+        let mut stored = job(JobType::RefetchPlaylist, JobStatus::Processing);
+        repository.create(stored.clone()).await.unwrap();
+
+        let progress = domain::JobProgress {
+            done: 100,
+            total: 250,
+        };
+        repository.set_progress(&stored.id, progress).await.unwrap();
+        stored.status = JobStatus::Retrying;
+        repository.update(stored.clone()).await.unwrap();
+
+        let loaded = repository.get(&stored.id).await.unwrap().unwrap();
+        assert_eq!(loaded.progress, Some(progress));
+    }
 }
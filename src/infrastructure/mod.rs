@@ -1,8 +1,32 @@
 mod entities;
+pub mod events;
+mod integrity;
 pub mod jobs;
 pub mod playlist;
+mod pool_metrics;
 pub mod spotify;
 
+pub use events::EventStore;
+pub use integrity::check_database_integrity;
 pub use jobs::JobsRepository;
 pub use playlist::PlaylistRepository;
+pub use pool_metrics::{PoolStats, spawn_pool_metrics};
 pub use spotify::SpotifyClient;
+
+#[cfg(test)]
+pub(crate) async fn test_pool() -> sqlx::SqlitePool {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("Failed to open in-memory database");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    pool
+}
@@ -0,0 +1,337 @@
+//! In-memory [`IPlaylistRepository`] for tests that do not need SQLite
+
+use crate::application::{IPlaylistRepository, Pagination, PlaylistOrder, PlaylistsPage};
+use crate::domain::{Job, JobId, JobStatus, Pdf, Playlist, PlaylistId, SessionId, SpotifyId, Tag};
+use anyhow::bail;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Keeps playlists in memory, scoped to their session like [`super::PlaylistRepository`].
+///
+/// Jobs live in the jobs repository, `get_jobs` only finds those added with [`Self::add_job`]
+/// and `delete` leaves them alone. Clones share their storage, so tests can inspect what the code under test stored.
+#[derive(Clone, Default)]
+pub(crate) struct MockPlaylistRepository {
+    playlists: Arc<Mutex<HashMap<PlaylistId, Playlist>>>,
+    /// Session each playlist belongs to
+    sessions: Arc<Mutex<HashMap<PlaylistId, SessionId>>>,
+    tags: Arc<Mutex<HashMap<PlaylistId, Vec<Tag>>>>,
+    pdfs: Arc<Mutex<HashMap<JobId, [Pdf; 2]>>>,
+    jobs: Arc<Mutex<Vec<Job>>>,
+    /// Fails every call while set, like a database that went away
+    should_fail: Arc<AtomicBool>,
+}
+
+impl MockPlaylistRepository {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the job visible to `get_jobs`, as if it was stored in the jobs repository
+    pub(crate) fn add_job(&self, job: Job) {
+        self.jobs.lock().unwrap().push(job);
+    }
+
+    /// Makes every following call fail until it is reset
+    pub(crate) fn set_should_fail(&self, should_fail: bool) {
+        self.should_fail.store(should_fail, Ordering::SeqCst);
+    }
+
+    fn check(&self) -> anyhow::Result<()> {
+        if self.should_fail.load(Ordering::SeqCst) {
+            bail!("playlist repository is failing on purpose");
+        }
+        Ok(())
+    }
+
+    /// Playlists of the session matching `filter`, newest first
+    fn session_playlists(
+        &self,
+        session_id: &SessionId,
+        filter: impl Fn(&Playlist) -> bool,
+    ) -> Vec<Playlist> {
+        let playlists = self.playlists.lock().unwrap();
+        let sessions = self.sessions.lock().unwrap();
+        let mut matching: Vec<Playlist> = playlists
+            .values()
+            .filter(|playlist| sessions.get(&playlist.id) == Some(session_id))
+            .filter(|playlist| filter(playlist))
+            .cloned()
+            .collect();
+        matching.sort_by_key(|playlist| (playlist.created_at, Uuid::from(&playlist.id)));
+        matching.reverse();
+        matching
+    }
+
+    fn page(playlists: Vec<Playlist>, pagination: Pagination) -> PlaylistsPage {
+        PlaylistsPage {
+            total: playlists.len() as u64,
+            playlists: playlists
+                .into_iter()
+                .skip(pagination.offset() as usize)
+                .take(pagination.per_page as usize)
+                .collect(),
+        }
+    }
+}
+
+impl IPlaylistRepository for MockPlaylistRepository {
+    async fn create(
+        &self,
+        session_id: &SessionId,
+        playlist: &Playlist,
+    ) -> anyhow::Result<Playlist> {
+        let (stored, _) = self.upsert(session_id, playlist).await?;
+        Ok(stored)
+    }
+
+    async fn upsert(
+        &self,
+        session_id: &SessionId,
+        playlist: &Playlist,
+    ) -> anyhow::Result<(Playlist, bool)> {
+        self.check()?;
+        if let Some(spotify_id) = &playlist.spotify_id
+            && let Some(existing) = self.get_by_spotify_id(session_id, spotify_id).await?
+        {
+            return Ok((existing, false));
+        }
+
+        let stored = Playlist {
+            created_at: playlist.created_at.or_else(|| Some(Utc::now())),
+            ..playlist.clone()
+        };
+        self.playlists
+            .lock()
+            .unwrap()
+            .insert(playlist.id.clone(), stored);
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(playlist.id.clone(), session_id.clone());
+        Ok((playlist.clone(), true))
+    }
+
+    async fn get(
+        &self,
+        session_id: &SessionId,
+        id: &PlaylistId,
+    ) -> anyhow::Result<Option<Playlist>> {
+        self.check()?;
+        Ok(self
+            .session_playlists(session_id, |playlist| &playlist.id == id)
+            .pop())
+    }
+
+    async fn get_by_spotify_id(
+        &self,
+        session_id: &SessionId,
+        spotify_id: &SpotifyId,
+    ) -> anyhow::Result<Option<Playlist>> {
+        self.check()?;
+        Ok(self
+            .session_playlists(session_id, |playlist| {
+                playlist.spotify_id.as_ref() == Some(spotify_id)
+            })
+            .pop())
+    }
+
+    async fn list(
+        &self,
+        session_id: &SessionId,
+        pagination: Pagination,
+        order: PlaylistOrder,
+    ) -> anyhow::Result<PlaylistsPage> {
+        self.check()?;
+        let mut playlists = self.session_playlists(session_id, |_| true);
+        if order == PlaylistOrder::Updated {
+            playlists.sort_by_key(|playlist| {
+                std::cmp::Reverse(playlist.updated_at.or(playlist.created_at))
+            });
+        }
+        Ok(Self::page(playlists, pagination))
+    }
+
+    async fn list_after(
+        &self,
+        session_id: &SessionId,
+        after: Option<&PlaylistId>,
+        limit: u32,
+    ) -> anyhow::Result<Vec<Playlist>> {
+        self.check()?;
+        let playlists = self.session_playlists(session_id, |_| true);
+        let start = match after {
+            Some(after) => match playlists.iter().position(|playlist| &playlist.id == after) {
+                Some(position) => position + 1,
+                None => return Ok(Vec::new()),
+            },
+            None => 0,
+        };
+        Ok(playlists
+            .into_iter()
+            .skip(start)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn search_by_name(
+        &self,
+        session_id: &SessionId,
+        query: &str,
+    ) -> anyhow::Result<Vec<Playlist>> {
+        self.check()?;
+        let query = query.to_ascii_lowercase();
+        Ok(self.session_playlists(session_id, |playlist| {
+            playlist
+                .display_name()
+                .to_ascii_lowercase()
+                .contains(&query)
+        }))
+    }
+
+    async fn delete(&self, session_id: &SessionId, id: &PlaylistId) -> anyhow::Result<bool> {
+        self.check()?;
+        if self.get(session_id, id).await?.is_none() {
+            return Ok(false);
+        }
+        self.playlists.lock().unwrap().remove(id);
+        self.sessions.lock().unwrap().remove(id);
+        self.tags.lock().unwrap().remove(id);
+        Ok(true)
+    }
+
+    async fn get_jobs(&self, playlist_id: &PlaylistId) -> anyhow::Result<Option<Vec<Job>>> {
+        self.check()?;
+        let mut jobs: Vec<Job> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|job| job.playlist_id().as_ref() == Some(playlist_id))
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.created_at));
+        Ok((!jobs.is_empty()).then_some(jobs))
+    }
+
+    /// Judges staleness by when the jobs were added, they are never marked as queued again
+    async fn get_playlists_with_stale_jobs(
+        &self,
+        older_than: chrono::Duration,
+    ) -> anyhow::Result<Vec<PlaylistId>> {
+        self.check()?;
+        let cutoff = Utc::now() - older_than;
+        let mut playlist_ids: Vec<PlaylistId> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|job| matches!(job.status, JobStatus::Pending | JobStatus::Processing))
+            .filter(|job| job.created_at < cutoff)
+            .filter_map(Job::playlist_id)
+            .collect();
+        playlist_ids.dedup();
+        Ok(playlist_ids)
+    }
+
+    async fn save_pdfs(&self, job_id: &JobId, pdfs: &[Pdf; 2]) -> anyhow::Result<()> {
+        self.check()?;
+        self.pdfs
+            .lock()
+            .unwrap()
+            .insert(job_id.clone(), pdfs.clone());
+        Ok(())
+    }
+
+    async fn get_pdfs(&self, job_id: &JobId) -> anyhow::Result<Option<[Pdf; 2]>> {
+        self.check()?;
+        Ok(self.pdfs.lock().unwrap().get(job_id).cloned())
+    }
+
+    async fn update(&self, playlist: &Playlist) -> anyhow::Result<Playlist> {
+        self.check()?;
+        let updated = Playlist {
+            updated_at: Some(Utc::now()),
+            ..playlist.clone()
+        };
+        if let Some(stored) = self.playlists.lock().unwrap().get_mut(&playlist.id) {
+            *stored = Playlist {
+                created_at: stored.created_at,
+                ..updated.clone()
+            };
+        }
+        Ok(updated)
+    }
+
+    async fn add_tag(&self, playlist_id: &PlaylistId, tag: &Tag) -> anyhow::Result<()> {
+        self.check()?;
+        let mut tags = self.tags.lock().unwrap();
+        let tags = tags.entry(playlist_id.clone()).or_default();
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+        Ok(())
+    }
+
+    async fn remove_tag(&self, playlist_id: &PlaylistId, tag: &Tag) -> anyhow::Result<()> {
+        self.check()?;
+        if let Some(tags) = self.tags.lock().unwrap().get_mut(playlist_id) {
+            tags.retain(|t| t != tag);
+        }
+        Ok(())
+    }
+
+    async fn get_tags(
+        &self,
+        session_id: &SessionId,
+        playlist_id: &PlaylistId,
+    ) -> anyhow::Result<Vec<Tag>> {
+        self.check()?;
+        if self.sessions.lock().unwrap().get(playlist_id) != Some(session_id) {
+            return Ok(Vec::new());
+        }
+        let mut tags = self
+            .tags
+            .lock()
+            .unwrap()
+            .get(playlist_id)
+            .cloned()
+            .unwrap_or_default();
+        tags.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        Ok(tags)
+    }
+
+    async fn list_by_tag(
+        &self,
+        session_id: &SessionId,
+        tag: &Tag,
+        pagination: Pagination,
+    ) -> anyhow::Result<PlaylistsPage> {
+        self.check()?;
+        let tags = self.tags.lock().unwrap().clone();
+        let playlists = self.session_playlists(session_id, |playlist| {
+            tags.get(&playlist.id)
+                .is_some_and(|tags| tags.contains(tag))
+        });
+        Ok(Self::page(playlists, pagination))
+    }
+
+    async fn count_playlists(&self) -> anyhow::Result<u64> {
+        self.check()?;
+        Ok(self.playlists.lock().unwrap().len() as u64)
+    }
+
+    async fn count_tracks(&self) -> anyhow::Result<u64> {
+        self.check()?;
+        Ok(self
+            .playlists
+            .lock()
+            .unwrap()
+            .values()
+            .map(|playlist| playlist.track_count() as u64)
+            .sum())
+    }
+}
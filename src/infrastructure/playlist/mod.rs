@@ -1,7 +1,19 @@
-use crate::application::IPlaylistRepository;
-use crate::domain::{Job, Playlist, PlaylistId, SpotifyId};
+#[cfg(test)]
+pub(crate) mod mock;
+
+use crate::application::{IPlaylistRepository, Pagination, PlaylistOrder, PlaylistsPage};
+use crate::domain::{Job, JobId, Pdf, Playlist, PlaylistId, SessionId, SpotifyId, Tag};
 use crate::infrastructure::entities::{JobEntity, PlaylistEntity, TrackEntity};
+use anyhow::Context;
+use chrono::Utc;
 use sqlx::{Pool, Sqlite, types::Uuid};
+use tracing::warn;
+
+#[cfg(test)]
+pub(crate) use mock::MockPlaylistRepository;
+
+/// `side` of the PDFs in `[front, back]` arrays
+const PDF_SIDES: [&str; 2] = ["front", "back"];
 
 #[derive(Clone)]
 pub struct PlaylistRepository {
@@ -12,31 +24,140 @@ impl PlaylistRepository {
     pub async fn new(pool: Pool<Sqlite>) -> anyhow::Result<Self> {
         Ok(Self { pool })
     }
+
+    async fn get_tracks(&self, playlist_id: Uuid) -> anyhow::Result<Vec<TrackEntity>> {
+        let tracks = sqlx::query_as::<_, TrackEntity>(
+            "SELECT id, playlist_id, title, artist, year, spotify_url, album_cover_url, popularity, duration_ms, notes, position FROM tracks WHERE playlist_id = ? ORDER BY position"
+        )
+        .bind(playlist_id)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("while loading tracks of playlist {playlist_id}"))?;
+
+        // Loading still succeeds so playlists with broken positions stay usable
+        if let Err(violations) = validate_track_positions(&tracks) {
+            warn!(
+                "Tracks of playlist {} have invalid positions: {}",
+                playlist_id,
+                violations.join(", ")
+            );
+        }
+
+        Ok(tracks)
+    }
+
+    /// Loads the tracks of every playlist entity
+    async fn with_tracks(&self, entities: Vec<PlaylistEntity>) -> anyhow::Result<Vec<Playlist>> {
+        let mut playlists = Vec::with_capacity(entities.len());
+        for playlist in entities {
+            let tracks = self.get_tracks(playlist.id).await?;
+            playlists.push(Playlist::from((playlist, tracks)));
+        }
+
+        Ok(playlists)
+    }
+}
+
+/// Checks that track positions are unique and run from 0 without gaps
+fn validate_track_positions(tracks: &[TrackEntity]) -> Result<(), Vec<String>> {
+    let mut positions: Vec<i32> = tracks.iter().map(|track| track.position).collect();
+    positions.sort_unstable();
+
+    let mut violations = Vec::new();
+    if let Some(&first) = positions.first()
+        && first != 0
+    {
+        violations.push(format!("positions start at {first} instead of 0"));
+    }
+    for pair in positions.windows(2) {
+        if pair[0] == pair[1] {
+            violations.push(format!("duplicate position {}", pair[0]));
+        } else if pair[1] != pair[0] + 1 {
+            violations.push(format!("gap between positions {} and {}", pair[0], pair[1]));
+        }
+    }
+    if let Some(&last) = positions.last()
+        && i64::from(last) + 1 != tracks.len() as i64
+    {
+        violations.push(format!(
+            "{} tracks but the last position is {last}",
+            tracks.len()
+        ));
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
 }
 
 impl IPlaylistRepository for PlaylistRepository {
-    async fn create(&self, playlist: &Playlist) -> anyhow::Result<Playlist> {
-        let mut tx = self.pool.begin().await?;
+    async fn create(
+        &self,
+        session_id: &SessionId,
+        playlist: &Playlist,
+    ) -> anyhow::Result<Playlist> {
+        let (stored, _) = self.upsert(session_id, playlist).await?;
+        Ok(stored)
+    }
+
+    async fn upsert(
+        &self,
+        session_id: &SessionId,
+        playlist: &Playlist,
+    ) -> anyhow::Result<(Playlist, bool)> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("while starting transaction to create playlist")?;
 
-        let playlist_id_uuid: Uuid = playlist.id.clone().into();
-        let spotify_id_str = playlist.spotify_id.as_ref().map(|s| s.to_string());
+        let playlist_id_uuid = Uuid::from(&playlist.id);
+        let spotify_id_str = playlist.spotify_id.as_ref().map(|s| s.as_uri());
         let playlist_name = &playlist.name;
+        let back_text = &playlist.back_text;
+        let spotify_track_count = playlist.spotify_track_count;
+        let custom_name = &playlist.custom_name;
+        let session_id_str = session_id.to_string();
 
-        sqlx::query!(
-            "INSERT INTO playlists (id, spotify_id, name) VALUES (?, ?, ?)",
+        let inserted = sqlx::query!(
+            "INSERT INTO playlists (id, spotify_id, name, back_text, spotify_track_count, custom_name, session_id) VALUES (?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT (session_id, spotify_id) DO NOTHING",
             playlist_id_uuid,
             spotify_id_str,
-            playlist_name
+            playlist_name,
+            back_text,
+            spotify_track_count,
+            custom_name,
+            session_id_str
         )
         .execute(&mut *tx)
-        .await?;
+        .await
+        .with_context(|| format!("while creating playlist {}", playlist.id))?
+        .rows_affected()
+            > 0;
+
+        // A concurrent import of the same Spotify playlist got there first
+        if !inserted && let Some(spotify_id) = &playlist.spotify_id {
+            tx.rollback().await.with_context(|| {
+                format!("while rolling back creation of playlist {}", playlist.id)
+            })?;
+            let existing = self
+                .get_by_spotify_id(session_id, spotify_id)
+                .await?
+                .with_context(|| {
+                    format!("while loading existing copy of Spotify playlist {spotify_id}")
+                })?;
+            return Ok((existing, false));
+        }
 
         for (position, track) in playlist.tracks.iter().enumerate() {
             let track_id = Uuid::new_v4();
             let track_position = position as i32;
 
             sqlx::query!(
-                "INSERT INTO tracks (id, playlist_id, title, artist, year, spotify_url, album_cover_url, position) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                "INSERT INTO tracks (id, playlist_id, title, artist, year, spotify_url, album_cover_url, popularity, duration_ms, notes, position) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 track_id,
                 playlist_id_uuid,
                 track.title,
@@ -44,73 +165,233 @@ impl IPlaylistRepository for PlaylistRepository {
                 track.year,
                 track.spotify_url,
                 track.album_cover_url,
+                track.popularity,
+                track.duration_ms,
+                track.notes,
                 track_position
             )
             .execute(&mut *tx)
-            .await?;
+            .await
+            .with_context(|| format!("while inserting track {position} of playlist {}", playlist.id))?;
         }
 
-        tx.commit().await?;
-        Ok(playlist.clone())
+        tx.commit()
+            .await
+            .with_context(|| format!("while committing creation of playlist {}", playlist.id))?;
+        Ok((playlist.clone(), true))
     }
 
-    async fn get(&self, id: &PlaylistId) -> anyhow::Result<Option<Playlist>> {
+    async fn get(
+        &self,
+        session_id: &SessionId,
+        id: &PlaylistId,
+    ) -> anyhow::Result<Option<Playlist>> {
         let playlist_entity = sqlx::query_as::<_, PlaylistEntity>(
-            "SELECT id, spotify_id, name, created_at, updated_at FROM playlists WHERE id = ?",
+            "SELECT id, spotify_id, name, created_at, updated_at, back_text, spotify_track_count, custom_name FROM playlists WHERE id = ? AND session_id = ?",
         )
-        .bind(Uuid::from(id.clone()))
+        .bind(Uuid::from(id))
+        .bind(session_id.to_string())
         .fetch_optional(&self.pool)
-        .await?;
+        .await
+        .with_context(|| format!("while loading playlist {id}"))?;
 
         match playlist_entity {
             Some(playlist) => {
-                let tracks = sqlx::query_as::<_, TrackEntity>(
-                    "SELECT id, playlist_id, title, artist, year, spotify_url, album_cover_url, position FROM tracks WHERE playlist_id = ? ORDER BY position"
-                )
-                .bind(Uuid::from(id.clone()))
-                .fetch_all(&self.pool)
-                .await?;
-
+                let tracks = self.get_tracks(playlist.id).await?;
                 Ok(Some(Playlist::from((playlist, tracks))))
             }
             None => Ok(None),
         }
     }
 
-    async fn get_by_spotify_id(&self, spotify_id: &SpotifyId) -> anyhow::Result<Option<Playlist>> {
+    async fn get_by_spotify_id(
+        &self,
+        session_id: &SessionId,
+        spotify_id: &SpotifyId,
+    ) -> anyhow::Result<Option<Playlist>> {
         let playlist_entity = sqlx::query_as::<_, PlaylistEntity>(
-            "SELECT id, spotify_id, name, created_at, updated_at FROM playlists WHERE spotify_id = ?"
+            "SELECT id, spotify_id, name, created_at, updated_at, back_text, spotify_track_count, custom_name FROM playlists WHERE spotify_id = ? AND session_id = ?"
         )
-        .bind(spotify_id.to_string())
+        .bind(spotify_id.as_uri())
+        .bind(session_id.to_string())
         .fetch_optional(&self.pool)
-        .await?;
+        .await
+        .with_context(|| format!("while loading playlist with Spotify ID {spotify_id}"))?;
 
         match playlist_entity {
             Some(playlist) => {
-                let tracks = sqlx::query_as::<_, TrackEntity>(
-                    "SELECT id, playlist_id, title, artist, year, spotify_url, album_cover_url, position FROM tracks WHERE playlist_id = ? ORDER BY position"
-                )
-                .bind(playlist.id)
-                .fetch_all(&self.pool)
-                .await?;
-
+                let tracks = self.get_tracks(playlist.id).await?;
                 Ok(Some(Playlist::from((playlist, tracks))))
             }
             None => Ok(None),
         }
     }
 
+    async fn list(
+        &self,
+        session_id: &SessionId,
+        pagination: Pagination,
+        order: PlaylistOrder,
+    ) -> anyhow::Result<PlaylistsPage> {
+        let order_by = match order {
+            PlaylistOrder::Created => "created_at",
+            PlaylistOrder::Updated => "COALESCE(updated_at, created_at)",
+        };
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM playlists WHERE session_id = ?")
+            .bind(session_id.to_string())
+            .fetch_one(&self.pool)
+            .await
+            .with_context(|| format!("while counting playlists of session {session_id}"))?;
+
+        let playlist_entities = sqlx::query_as::<_, PlaylistEntity>(&format!(
+            "SELECT id, spotify_id, name, created_at, updated_at, back_text, spotify_track_count, custom_name FROM playlists
+             WHERE session_id = ?
+             ORDER BY {order_by} DESC
+             LIMIT ? OFFSET ?"
+        ))
+        .bind(session_id.to_string())
+        .bind(pagination.per_page)
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("while loading playlists of session {session_id}"))?;
+
+        Ok(PlaylistsPage {
+            playlists: self.with_tracks(playlist_entities).await?,
+            total: total as u64,
+        })
+    }
+
+    async fn list_after(
+        &self,
+        session_id: &SessionId,
+        after: Option<&PlaylistId>,
+        limit: u32,
+    ) -> anyhow::Result<Vec<Playlist>> {
+        // Playlists created in the same second are ordered by ID so the cursor skips none of them
+        let playlist_entities = match after {
+            Some(after) => sqlx::query_as::<_, PlaylistEntity>(
+                "SELECT id, spotify_id, name, created_at, updated_at, back_text, spotify_track_count, custom_name FROM playlists
+                 WHERE session_id = ?
+                   AND (created_at, id) < (SELECT created_at, id FROM playlists WHERE id = ? AND session_id = ?)
+                 ORDER BY created_at DESC, id DESC
+                 LIMIT ?",
+            )
+            .bind(session_id.to_string())
+            .bind(Uuid::from(after))
+            .bind(session_id.to_string())
+            .bind(limit),
+            None => sqlx::query_as::<_, PlaylistEntity>(
+                "SELECT id, spotify_id, name, created_at, updated_at, back_text, spotify_track_count, custom_name FROM playlists
+                 WHERE session_id = ?
+                 ORDER BY created_at DESC, id DESC
+                 LIMIT ?",
+            )
+            .bind(session_id.to_string())
+            .bind(limit),
+        }
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("while loading playlists of session {session_id}"))?;
+
+        self.with_tracks(playlist_entities).await
+    }
+
+    async fn search_by_name(
+        &self,
+        session_id: &SessionId,
+        query: &str,
+    ) -> anyhow::Result<Vec<Playlist>> {
+        // `%` and `_` in the query match literally
+        let pattern = format!(
+            "%{}%",
+            query
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let playlist_entities = sqlx::query_as::<_, PlaylistEntity>(
+            "SELECT id, spotify_id, name, created_at, updated_at, back_text, spotify_track_count, custom_name FROM playlists
+             WHERE session_id = ? AND COALESCE(custom_name, name) LIKE ? ESCAPE '\\'
+             ORDER BY created_at DESC, id DESC",
+        )
+        .bind(session_id.to_string())
+        .bind(pattern)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("while searching playlists of session {session_id}"))?;
+
+        self.with_tracks(playlist_entities).await
+    }
+
+    async fn delete(&self, session_id: &SessionId, id: &PlaylistId) -> anyhow::Result<bool> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .with_context(|| format!("while starting deletion of playlist {id}"))?;
+
+        let exists = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM playlists WHERE id = ? AND session_id = ?",
+        )
+        .bind(Uuid::from(id))
+        .bind(session_id.to_string())
+        .fetch_one(&mut *tx)
+        .await
+        .with_context(|| format!("while loading playlist {id}"))?
+            > 0;
+        if !exists {
+            return Ok(false);
+        }
+
+        // Children first so no foreign key is violated along the way
+        for (table, query) in [
+            ("tracks", "DELETE FROM tracks WHERE playlist_id = ?"),
+            ("tags", "DELETE FROM playlist_tags WHERE playlist_id = ?"),
+            ("playlist", "DELETE FROM playlists WHERE id = ?"),
+        ] {
+            sqlx::query(query)
+                .bind(Uuid::from(id))
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("while deleting {table} of playlist {id}"))?;
+        }
+        for (table, query) in [
+            (
+                "PDFs",
+                "DELETE FROM pdfs WHERE job_id IN \
+                 (SELECT id FROM jobs WHERE json_extract(payload, '$.playlist_id') = ?)",
+            ),
+            (
+                "jobs",
+                "DELETE FROM jobs WHERE json_extract(payload, '$.playlist_id') = ?",
+            ),
+        ] {
+            sqlx::query(query)
+                .bind(id.to_string())
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("while deleting {table} of playlist {id}"))?;
+        }
+
+        tx.commit()
+            .await
+            .with_context(|| format!("while committing deletion of playlist {id}"))?;
+        Ok(true)
+    }
+
     async fn get_jobs(&self, playlist_id: &PlaylistId) -> anyhow::Result<Option<Vec<Job>>> {
         let playlist_id_str = playlist_id.to_string();
 
         let job_entities = sqlx::query_as::<_, JobEntity>(
-            "SELECT id, status, created_at, completed_at, payload, result FROM jobs 
+            "SELECT id, status, created_at, completed_at, payload, result, error_message, progress FROM jobs 
              WHERE json_extract(payload, '$.playlist_id') = ? 
              ORDER BY created_at DESC",
         )
         .bind(playlist_id_str)
         .fetch_all(&self.pool)
-        .await?;
+        .await
+        .with_context(|| format!("while loading jobs of playlist {playlist_id}"))?;
 
         if job_entities.is_empty() {
             return Ok(None);
@@ -120,29 +401,113 @@ impl IPlaylistRepository for PlaylistRepository {
         Ok(Some(jobs))
     }
 
+    async fn get_playlists_with_stale_jobs(
+        &self,
+        older_than: chrono::Duration,
+    ) -> anyhow::Result<Vec<PlaylistId>> {
+        let cutoff = Utc::now() - older_than;
+        let playlist_ids: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT json_extract(payload, '$.playlist_id') FROM jobs
+             WHERE status IN ('pending', 'processing')
+             AND datetime(COALESCE(requeued_at, created_at)) < datetime(?)
+             AND json_extract(payload, '$.playlist_id') IS NOT NULL",
+        )
+        .bind(cutoff)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("while loading playlists with jobs stale since {cutoff}"))?;
+
+        Ok(playlist_ids
+            .iter()
+            .filter_map(|id| match PlaylistId::try_from(id.as_str()) {
+                Ok(id) => Some(id),
+                Err(e) => {
+                    warn!(
+                        "Skipping job payload with invalid playlist ID {}: {}",
+                        id, e
+                    );
+                    None
+                }
+            })
+            .collect())
+    }
+
+    async fn save_pdfs(&self, job_id: &JobId, pdfs: &[Pdf; 2]) -> anyhow::Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .with_context(|| format!("while starting to store PDFs of job {job_id}"))?;
+
+        for (side, pdf) in PDF_SIDES.iter().zip(pdfs) {
+            sqlx::query("INSERT INTO pdfs (id, job_id, side, data) VALUES (?, ?, ?, ?)")
+                .bind(Uuid::new_v4())
+                .bind(Uuid::from(job_id.clone()))
+                .bind(side)
+                .bind(pdf.as_ref())
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("while storing {side} PDF of job {job_id}"))?;
+        }
+
+        tx.commit()
+            .await
+            .with_context(|| format!("while committing PDFs of job {job_id}"))
+    }
+
+    async fn get_pdfs(&self, job_id: &JobId) -> anyhow::Result<Option<[Pdf; 2]>> {
+        let mut rows: Vec<(String, Vec<u8>)> =
+            sqlx::query_as("SELECT side, data FROM pdfs WHERE job_id = ?")
+                .bind(Uuid::from(job_id.clone()))
+                .fetch_all(&self.pool)
+                .await
+                .with_context(|| format!("while loading PDFs of job {job_id}"))?;
+
+        let mut take = |side: &str| {
+            let index = rows.iter().position(|(s, _)| s == side)?;
+            Some(Pdf::from(rows.swap_remove(index).1))
+        };
+        let [front, back] = PDF_SIDES;
+        Ok(take(front)
+            .zip(take(back))
+            .map(|(front, back)| [front, back]))
+    }
+
     async fn update(&self, playlist: &Playlist) -> anyhow::Result<Playlist> {
-        let mut tx = self.pool.begin().await?;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("while starting transaction to update playlist")?;
 
-        let playlist_id_uuid: Uuid = playlist.id.clone().into();
-        let spotify_id_str = playlist.spotify_id.as_ref().map(|s| s.to_string());
+        let playlist_id_uuid = Uuid::from(&playlist.id);
+        let spotify_id_str = playlist.spotify_id.as_ref().map(|s| s.as_uri());
         let playlist_name = &playlist.name;
-        let updated_at = playlist.updated_at;
+        let updated_at = Utc::now();
+        let back_text = &playlist.back_text;
+        let spotify_track_count = playlist.spotify_track_count;
+        let custom_name = &playlist.custom_name;
 
         // Update playlist
         sqlx::query!(
-            "UPDATE playlists SET spotify_id = ?, name = ?, updated_at = ? WHERE id = ?",
+            "UPDATE playlists SET spotify_id = ?, name = ?, updated_at = ?, back_text = ?, spotify_track_count = ?, custom_name = ? WHERE id = ?",
             spotify_id_str,
             playlist_name,
             updated_at,
+            back_text,
+            spotify_track_count,
+            custom_name,
             playlist_id_uuid
         )
         .execute(&mut *tx)
-        .await?;
+        .await
+        .with_context(|| format!("while updating playlist {}", playlist.id))?;
 
         // Delete existing tracks (we'll reinsert them)
         sqlx::query!("DELETE FROM tracks WHERE playlist_id = ?", playlist_id_uuid)
             .execute(&mut *tx)
-            .await?;
+            .await
+            .with_context(|| format!("while deleting tracks of playlist {}", playlist.id))?;
 
         // Insert updated tracks
         for (position, track) in playlist.tracks.iter().enumerate() {
@@ -150,7 +515,7 @@ impl IPlaylistRepository for PlaylistRepository {
             let track_position = position as i32;
 
             sqlx::query!(
-                "INSERT INTO tracks (id, playlist_id, title, artist, year, spotify_url, album_cover_url, position) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                "INSERT INTO tracks (id, playlist_id, title, artist, year, spotify_url, album_cover_url, popularity, duration_ms, notes, position) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 track_id,
                 playlist_id_uuid,
                 track.title,
@@ -158,13 +523,711 @@ impl IPlaylistRepository for PlaylistRepository {
                 track.year,
                 track.spotify_url,
                 track.album_cover_url,
+                track.popularity,
+                track.duration_ms,
+                track.notes,
                 track_position
             )
             .execute(&mut *tx)
-            .await?;
+            .await
+            .with_context(|| format!("while inserting track {position} of playlist {}", playlist.id))?;
         }
 
-        tx.commit().await?;
-        Ok(playlist.clone())
+        tx.commit()
+            .await
+            .with_context(|| format!("while committing update of playlist {}", playlist.id))?;
+        Ok(Playlist {
+            updated_at: Some(updated_at),
+            ..playlist.clone()
+        })
+    }
+
+    async fn add_tag(&self, playlist_id: &PlaylistId, tag: &Tag) -> anyhow::Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("while starting transaction to tag playlist")?;
+
+        let playlist_id_uuid = Uuid::from(playlist_id);
+        let new_tag_id = Uuid::new_v4();
+        let tag_name = tag.as_str();
+
+        sqlx::query!(
+            "INSERT OR IGNORE INTO tags (id, name) VALUES (?, ?)",
+            new_tag_id,
+            tag_name
+        )
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("while creating tag '{tag}'"))?;
+
+        sqlx::query!(
+            "INSERT OR IGNORE INTO playlist_tags (playlist_id, tag_id) SELECT ?, id FROM tags WHERE name = ?",
+            playlist_id_uuid,
+            tag_name
+        )
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("while tagging playlist {playlist_id} with '{tag}'"))?;
+
+        tx.commit()
+            .await
+            .with_context(|| format!("while committing tag '{tag}' of playlist {playlist_id}"))?;
+        Ok(())
+    }
+
+    async fn remove_tag(&self, playlist_id: &PlaylistId, tag: &Tag) -> anyhow::Result<()> {
+        let playlist_id_uuid = Uuid::from(playlist_id);
+        let tag_name = tag.as_str();
+
+        sqlx::query!(
+            "DELETE FROM playlist_tags WHERE playlist_id = ? AND tag_id IN (SELECT id FROM tags WHERE name = ?)",
+            playlist_id_uuid,
+            tag_name
+        )
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("while removing tag '{tag}' from playlist {playlist_id}"))?;
+
+        Ok(())
+    }
+
+    async fn get_tags(
+        &self,
+        session_id: &SessionId,
+        playlist_id: &PlaylistId,
+    ) -> anyhow::Result<Vec<Tag>> {
+        let names = sqlx::query_scalar::<_, String>(
+            "SELECT t.name FROM tags t
+             JOIN playlist_tags pt ON pt.tag_id = t.id
+             JOIN playlists p ON p.id = pt.playlist_id
+             WHERE pt.playlist_id = ? AND p.session_id = ?
+             ORDER BY t.name",
+        )
+        .bind(Uuid::from(playlist_id))
+        .bind(session_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("while loading tags of playlist {playlist_id}"))?;
+
+        names.iter().map(|name| Ok(Tag::parse(name)?)).collect()
+    }
+
+    async fn list_by_tag(
+        &self,
+        session_id: &SessionId,
+        tag: &Tag,
+        pagination: Pagination,
+    ) -> anyhow::Result<PlaylistsPage> {
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM playlists p
+             JOIN playlist_tags pt ON pt.playlist_id = p.id
+             JOIN tags t ON t.id = pt.tag_id
+             WHERE t.name = ? AND p.session_id = ?",
+        )
+        .bind(tag.as_str())
+        .bind(session_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .with_context(|| format!("while counting playlists tagged '{tag}'"))?;
+
+        let playlist_entities = sqlx::query_as::<_, PlaylistEntity>(
+            "SELECT p.id, p.spotify_id, p.name, p.created_at, p.updated_at, p.back_text, p.spotify_track_count, p.custom_name FROM playlists p
+             JOIN playlist_tags pt ON pt.playlist_id = p.id
+             JOIN tags t ON t.id = pt.tag_id
+             WHERE t.name = ? AND p.session_id = ?
+             ORDER BY p.created_at DESC
+             LIMIT ? OFFSET ?",
+        )
+        .bind(tag.as_str())
+        .bind(session_id.to_string())
+        .bind(pagination.per_page)
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("while loading playlists tagged '{tag}'"))?;
+
+        Ok(PlaylistsPage {
+            playlists: self.with_tracks(playlist_entities).await?,
+            total: total as u64,
+        })
+    }
+
+    async fn count_playlists(&self) -> anyhow::Result<u64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM playlists")
+            .fetch_one(&self.pool)
+            .await
+            .context("while counting playlists")?;
+        Ok(count as u64)
+    }
+
+    async fn count_tracks(&self) -> anyhow::Result<u64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tracks")
+            .fetch_one(&self.pool)
+            .await
+            .context("while counting tracks")?;
+        Ok(count as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Track;
+    use crate::fixtures::playlist;
+    use crate::infrastructure::test_pool;
+
+    const ALL: Pagination = Pagination {
+        page: 1,
+        per_page: 100,
+    };
+
+    fn track_at(position: i32) -> TrackEntity {
+        TrackEntity {
+            id: Uuid::new_v4(),
+            playlist_id: Uuid::new_v4(),
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            year: 2000,
+            spotify_url: format!("https://open.spotify.com/track/{position}"),
+            album_cover_url: None,
+            popularity: None,
+            duration_ms: None,
+            notes: None,
+            position,
+        }
+    }
+
+    #[test]
+    fn test_validate_track_positions() {
+        let positions = |positions: &[i32]| {
+            validate_track_positions(&positions.iter().copied().map(track_at).collect::<Vec<_>>())
+        };
+
+        assert_eq!(positions(&[]), Ok(()));
+        assert_eq!(positions(&[0, 1, 2]), Ok(()));
+        assert_eq!(
+            positions(&[0, 1, 3]),
+            Err(vec![
+                "gap between positions 1 and 3".to_string(),
+                "3 tracks but the last position is 3".to_string()
+            ])
+        );
+        assert_eq!(
+            positions(&[0, 1, 1]),
+            Err(vec![
+                "duplicate position 1".to_string(),
+                "3 tracks but the last position is 1".to_string()
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unique_positions_migration_renumbers_duplicates() {
+        let pool = test_pool().await;
+        let repository = PlaylistRepository::new(pool.clone()).await.unwrap();
+        let session_id = SessionId::new();
+        let stored = repository
+            .create(&session_id, &playlist("Duplicates", vec![]))
+            .await
+            .unwrap();
+        // As left behind by imports before the index existed
+        sqlx::raw_sql("DROP INDEX idx_tracks_playlist_id_position")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (title, position) in [("a", 0), ("b", 1), ("c", 1), ("d", 2)] {
+            sqlx::query(
+                "INSERT INTO tracks (id, playlist_id, title, artist, year, spotify_url, position)
+                 VALUES (?, ?, ?, 'Artist', 2000, ?, ?)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(Uuid::from(&stored.id))
+            .bind(title)
+            .bind(format!("https://open.spotify.com/track/{title}"))
+            .bind(position)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        sqlx::raw_sql(include_str!(
+            "../../../migrations/20250926090000_unique_track_positions.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let renumbered = repository
+            .get(&session_id, &stored.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let titles: Vec<_> = renumbered.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["a", "b", "c", "d"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_by_tag_only_returns_tagged_playlists() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        let session = SessionId::new();
+        let tagged = repository
+            .create(&session, &playlist("Tagged", vec![]))
+            .await
+            .unwrap();
+        let untagged = repository
+            .create(&session, &playlist("Untagged", vec![]))
+            .await
+            .unwrap();
+
+        let tag = Tag::parse(" Game-Night").unwrap();
+        repository.add_tag(&tagged.id, &tag).await.unwrap();
+        // Adding the same tag twice is a no-op
+        repository.add_tag(&tagged.id, &tag).await.unwrap();
+
+        let page = repository.list_by_tag(&session, &tag, ALL).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.playlists[0].id, tagged.id);
+        assert!(
+            repository
+                .get_tags(&session, &untagged.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        repository.remove_tag(&tagged.id, &tag).await.unwrap();
+        assert!(
+            repository
+                .list_by_tag(&session, &tag, ALL)
+                .await
+                .unwrap()
+                .playlists
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_by_tag_paginates_tagged_playlists() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        let session = SessionId::new();
+        let tag = Tag::parse("party").unwrap();
+        for i in 0..5 {
+            let created = repository
+                .create(&session, &playlist(&format!("Playlist {i}"), vec![]))
+                .await
+                .unwrap();
+            if i % 2 == 1 {
+                repository.add_tag(&created.id, &tag).await.unwrap();
+            }
+        }
+
+        let page = repository.list_by_tag(&session, &tag, ALL).await.unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.playlists.len(), 2);
+
+        let first_page = Pagination {
+            page: 1,
+            per_page: 1,
+        };
+        let page = repository
+            .list_by_tag(&session, &tag, first_page)
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.playlists.len(), 1);
+
+        assert_eq!(
+            repository
+                .list(&session, ALL, PlaylistOrder::Created)
+                .await
+                .unwrap()
+                .total,
+            5
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_orders_by_created_or_updated_time() {
+        let pool = test_pool().await;
+        let repository = PlaylistRepository::new(pool.clone()).await.unwrap();
+        let session = SessionId::new();
+        let mut created = Vec::new();
+        for i in 0..7 {
+            let playlist = playlist(&format!("Playlist {i}"), vec![]);
+            // Inserted directly, updating created_at would also stamp updated_at
+            sqlx::query(
+                "INSERT INTO playlists (id, name, created_at, session_id) VALUES (?, ?, ?, ?)",
+            )
+            .bind(Uuid::from(&playlist.id))
+            .bind(&playlist.name)
+            .bind(format!("2025-01-0{} 12:00:00", i + 1))
+            .bind(session.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+            created.push(playlist);
+        }
+        // The trigger stamps the oldest playlist as updated just now
+        let oldest = created[0].clone();
+        repository.update(&oldest).await.unwrap();
+        let five = Pagination {
+            page: 1,
+            per_page: 5,
+        };
+
+        let newest = repository
+            .list(&session, five, PlaylistOrder::Created)
+            .await
+            .unwrap();
+        let names: Vec<_> = newest.playlists.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "Playlist 6",
+                "Playlist 5",
+                "Playlist 4",
+                "Playlist 3",
+                "Playlist 2"
+            ]
+        );
+        assert_eq!(newest.total, 7);
+
+        let updated = repository
+            .list(&session, five, PlaylistOrder::Updated)
+            .await
+            .unwrap();
+        assert_eq!(updated.playlists[0].id, oldest.id);
+        assert_eq!(updated.playlists[1].name, "Playlist 6");
+    }
+
+    #[tokio::test]
+    async fn test_list_after_continues_from_cursor() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        let session = SessionId::new();
+        // Created within the same second, so only the ID tells them apart
+        for i in 0..5 {
+            repository
+                .create(&session, &playlist(&format!("Playlist {i}"), vec![]))
+                .await
+                .unwrap();
+        }
+        repository
+            .create(&SessionId::new(), &playlist("Someone else's", vec![]))
+            .await
+            .unwrap();
+
+        let first = repository.list_after(&session, None, 2).await.unwrap();
+        let second = repository
+            .list_after(&session, Some(&first[1].id), 2)
+            .await
+            .unwrap();
+        let last = repository
+            .list_after(&session, Some(&second[1].id), 2)
+            .await
+            .unwrap();
+
+        assert_eq!((first.len(), second.len(), last.len()), (2, 2, 1));
+        let mut seen: Vec<_> = [first, second, last]
+            .concat()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 5);
+        assert!(
+            repository
+                .list_after(&SessionId::new(), Some(&PlaylistId::new()), 2)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_by_name_matches_substrings_ignoring_case() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        let session = SessionId::new();
+        for name in ["Summer Hits", "Hits of 100% Rock", "Winter Chill"] {
+            repository
+                .create(&session, &playlist(name, vec![]))
+                .await
+                .unwrap();
+        }
+        repository
+            .create(&SessionId::new(), &playlist("More hits", vec![]))
+            .await
+            .unwrap();
+
+        let names = |playlists: Vec<Playlist>| -> Vec<String> {
+            let mut names: Vec<_> = playlists.into_iter().map(|p| p.name).collect();
+            names.sort();
+            names
+        };
+        let hits = repository.search_by_name(&session, "HITS").await.unwrap();
+        assert_eq!(names(hits), ["Hits of 100% Rock", "Summer Hits"]);
+        let percent = repository.search_by_name(&session, "0%").await.unwrap();
+        assert_eq!(names(percent), ["Hits of 100% Rock"]);
+        assert!(
+            repository
+                .search_by_name(&session, "_")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sessions_only_see_their_own_playlists() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        let alice = SessionId::new();
+        let bob = SessionId::new();
+        let alices = repository
+            .create(&alice, &playlist("Alice's", vec![]))
+            .await
+            .unwrap();
+        let bobs = repository
+            .create(&bob, &playlist("Bob's", vec![]))
+            .await
+            .unwrap();
+
+        let listed = repository
+            .list(&alice, ALL, PlaylistOrder::Created)
+            .await
+            .unwrap();
+        assert_eq!(listed.total, 1);
+        assert_eq!(listed.playlists[0].id, alices.id);
+
+        let listed = repository
+            .list(&bob, ALL, PlaylistOrder::Created)
+            .await
+            .unwrap();
+        assert_eq!(listed.total, 1);
+        assert_eq!(listed.playlists[0].id, bobs.id);
+
+        assert!(repository.get(&alice, &bobs.id).await.unwrap().is_none());
+        assert!(repository.get(&bob, &bobs.id).await.unwrap().is_some());
+
+        let tag = Tag::parse("party").unwrap();
+        repository.add_tag(&bobs.id, &tag).await.unwrap();
+        assert!(
+            repository
+                .list_by_tag(&alice, &tag, ALL)
+                .await
+                .unwrap()
+                .playlists
+                .is_empty()
+        );
+        assert!(
+            repository
+                .get_tags(&alice, &bobs.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_database_errors_include_context() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        let session = SessionId::new();
+        let playlist = playlist("Duplicate", vec![]);
+        repository.create(&session, &playlist).await.unwrap();
+
+        let err = repository.create(&session, &playlist).await.unwrap_err();
+
+        let expected = format!("while creating playlist {}", playlist.id);
+        assert!(err.chain().any(|cause| cause.to_string() == expected));
+        assert!(
+            err.chain()
+                .any(|cause| cause.to_string().contains("UNIQUE constraint failed"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pdfs_round_trip() {
+        use crate::application::IJobsRepository;
+        use crate::infrastructure::JobsRepository;
+
+        let pool = test_pool().await;
+        let repository = PlaylistRepository::new(pool.clone()).await.unwrap();
+        let job = JobsRepository::new(pool)
+            .create(Job::new(serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(repository.get_pdfs(&job.id).await.unwrap(), None);
+
+        let pdfs = [Pdf::new(b"front".to_vec()), Pdf::new(b"back".to_vec())];
+        repository.save_pdfs(&job.id, &pdfs).await.unwrap();
+
+        assert_eq!(repository.get_pdfs(&job.id).await.unwrap(), Some(pdfs));
+    }
+
+    #[tokio::test]
+    async fn test_get_playlists_with_stale_jobs() {
+        use crate::application::IJobsRepository;
+        use crate::domain::JobStatus;
+        use crate::infrastructure::JobsRepository;
+
+        let pool = test_pool().await;
+        let repository = PlaylistRepository::new(pool.clone()).await.unwrap();
+        let jobs = JobsRepository::new(pool);
+        let job_for = |playlist_id: &PlaylistId, status, age: chrono::Duration| {
+            let mut job = Job::new(serde_json::json!({"playlist_id": playlist_id}));
+            job.status = status;
+            job.created_at = Utc::now() - age;
+            job
+        };
+        let stuck = PlaylistId::new();
+        let recent = PlaylistId::new();
+        let done = PlaylistId::new();
+        for job in [
+            job_for(&stuck, JobStatus::Pending, chrono::Duration::hours(2)),
+            job_for(&stuck, JobStatus::Processing, chrono::Duration::hours(1)),
+            job_for(&recent, JobStatus::Pending, chrono::Duration::minutes(1)),
+            job_for(&done, JobStatus::Completed, chrono::Duration::hours(2)),
+            Job::new(serde_json::json!({})),
+        ] {
+            jobs.create(job).await.unwrap();
+        }
+
+        let stale = repository
+            .get_playlists_with_stale_jobs(chrono::Duration::minutes(30))
+            .await
+            .unwrap();
+
+        assert_eq!(stale, vec![stuck]);
+    }
+
+    #[tokio::test]
+    async fn test_spotify_track_count_is_stored() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        let session = SessionId::new();
+        let mut stored = Playlist {
+            spotify_track_count: Some(100),
+            ..playlist("Partial", vec![])
+        };
+        repository.create(&session, &stored).await.unwrap();
+
+        let loaded = repository.get(&session, &stored.id).await.unwrap().unwrap();
+        assert_eq!(loaded.spotify_track_count, Some(100));
+
+        stored.spotify_track_count = Some(120);
+        repository.update(&stored).await.unwrap();
+        let loaded = repository.get(&session, &stored.id).await.unwrap().unwrap();
+        assert_eq!(loaded.spotify_track_count, Some(120));
+    }
+
+    #[tokio::test]
+    async fn test_counts_playlists_and_tracks_of_all_sessions() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        assert_eq!(repository.count_playlists().await.unwrap(), 0);
+        assert_eq!(repository.count_tracks().await.unwrap(), 0);
+
+        let track = |n: u32| Track {
+            title: format!("Song {n}"),
+            artist: "Artist".to_string(),
+            year: 1984,
+            spotify_url: format!("https://open.spotify.com/track/{n}"),
+            album_cover_url: None,
+            popularity: None,
+            duration_ms: None,
+            notes: None,
+        };
+        let first = Playlist {
+            tracks: vec![track(1), track(2)],
+            ..playlist("First", vec![])
+        };
+        let second = Playlist {
+            tracks: vec![track(3)],
+            ..playlist("Second", vec![])
+        };
+        repository.create(&SessionId::new(), &first).await.unwrap();
+        repository.create(&SessionId::new(), &second).await.unwrap();
+
+        assert_eq!(repository.count_playlists().await.unwrap(), 2);
+        assert_eq!(repository.count_tracks().await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_returns_existing_copy_of_spotify_playlist() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        let session = SessionId::new();
+        let spotify_id: SpotifyId = "37i9dQZF1DXcBWIGoYBM5M".parse().unwrap();
+        let first = Playlist {
+            spotify_id: Some(spotify_id.clone()),
+            ..playlist("First", vec![])
+        };
+        let second = Playlist {
+            spotify_id: Some(spotify_id),
+            ..playlist("Second", vec![])
+        };
+
+        let (stored, was_created) = repository.upsert(&session, &first).await.unwrap();
+        assert!(was_created);
+        assert_eq!(stored.id, first.id);
+
+        let (stored, was_created) = repository.upsert(&session, &second).await.unwrap();
+        assert!(!was_created);
+        assert_eq!(stored.id, first.id);
+
+        let (_, was_created) = repository.upsert(&SessionId::new(), &second).await.unwrap();
+        assert!(was_created);
+    }
+
+    #[tokio::test]
+    async fn test_spotify_ids_keep_their_resource_type() {
+        let pool = test_pool().await;
+        let repository = PlaylistRepository::new(pool.clone()).await.unwrap();
+        let session = SessionId::new();
+        let album_id = SpotifyId::parse_album("0ETFjACtuP2ADo6LFhL6HN").unwrap();
+        let album = Playlist {
+            spotify_id: Some(album_id.clone()),
+            ..playlist("Album", vec![])
+        };
+        repository.create(&session, &album).await.unwrap();
+
+        let stored = repository.get(&session, &album.id).await.unwrap().unwrap();
+        assert_eq!(stored.spotify_id, Some(album_id.clone()));
+        assert!(
+            repository
+                .get_by_spotify_id(&session, &album_id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        let column: String = sqlx::query_scalar("SELECT spotify_id FROM playlists WHERE id = ?")
+            .bind(Uuid::from(&album.id))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(column, "spotify:album:0ETFjACtuP2ADo6LFhL6HN");
+    }
+
+    #[tokio::test]
+    async fn test_update_stamps_updated_at() {
+        let repository = PlaylistRepository::new(test_pool().await).await.unwrap();
+        let session = SessionId::new();
+        let stored = playlist("Touched", vec![]);
+        repository.create(&session, &stored).await.unwrap();
+        assert_eq!(
+            repository
+                .get(&session, &stored.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .updated_at,
+            None
+        );
+
+        let before = Utc::now() - chrono::Duration::seconds(1);
+        let updated = repository.update(&stored).await.unwrap();
+        assert!(updated.updated_at.is_some_and(|at| at >= before));
+
+        let loaded = repository.get(&session, &stored.id).await.unwrap().unwrap();
+        assert!(loaded.updated_at.is_some_and(|at| at >= before));
     }
 }
@@ -0,0 +1,90 @@
+//! Periodic logging of SQLite pool utilisation, to notice exhaustion before acquires time out
+
+use sqlx::SqlitePool;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// How often the pool utilisation is logged
+const POOL_METRICS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Utilisation of the pool at one point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Open connections, idle or in use
+    pub size: u32,
+    pub idle: usize,
+    pub max_connections: u32,
+}
+
+impl PoolStats {
+    pub fn of(pool: &SqlitePool) -> Self {
+        Self {
+            size: pool.size(),
+            idle: pool.num_idle(),
+            max_connections: pool.options().get_max_connections(),
+        }
+    }
+
+    pub fn in_use(&self) -> usize {
+        (self.size as usize).saturating_sub(self.idle)
+    }
+}
+
+/// Time it takes to get a connection from the pool, or the error once the acquire timeout passed
+async fn measure_acquire(pool: &SqlitePool) -> Result<Duration, sqlx::Error> {
+    let started = Instant::now();
+    let connection = pool.acquire().await?;
+    let waited = started.elapsed();
+    drop(connection);
+    Ok(waited)
+}
+
+/// Logs the pool utilisation and acquire wait every minute, starting right away.
+///
+/// The wait is measured by acquiring a connection like any query would, so it grows
+/// when all connections are busy. Raise `database.max_connections` when it does.
+pub fn spawn_pool_metrics(pool: SqlitePool) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POOL_METRICS_INTERVAL);
+        loop {
+            interval.tick().await;
+            let stats = PoolStats::of(&pool);
+            match measure_acquire(&pool).await {
+                Ok(waited) => info!(
+                    "Database pool: {} of {} connections open, {} in use, {} idle, acquire waited {:?}",
+                    stats.size,
+                    stats.max_connections,
+                    stats.in_use(),
+                    stats.idle,
+                    waited
+                ),
+                Err(e) => warn!(
+                    "Database pool exhausted: {} of {} connections in use, acquire failed: {}",
+                    stats.in_use(),
+                    stats.max_connections,
+                    e
+                ),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_pool;
+
+    #[tokio::test]
+    async fn test_pool_stats_count_connections_in_use() {
+        let pool = test_pool().await;
+        let connection = pool.acquire().await.unwrap();
+
+        let stats = PoolStats::of(&pool);
+        assert_eq!(stats.max_connections, 1);
+        assert_eq!(stats.in_use(), 1);
+
+        drop(connection);
+        assert!(measure_acquire(&pool).await.is_ok());
+    }
+}
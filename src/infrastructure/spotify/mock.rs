@@ -0,0 +1,259 @@
+//! In-memory [`ISpotifyClient`] for tests that should not reach Spotify
+
+use crate::application::{
+    FetchProgress, ISpotifyClient, PartialFetchError, PlaylistFetchResult, PlaylistMetadata,
+    SpotifyPlaylistSummary,
+};
+use crate::domain::{Playlist, PlaylistId, SpotifyId, Track};
+use anyhow::anyhow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Answers with canned playlists, albums and tracks and counts the calls of each method.
+///
+/// Fetching an ID without a canned answer fails, so tests notice requests they did not expect.
+#[derive(Clone, Default)]
+pub(crate) struct MockSpotifyClient {
+    playlists: HashMap<SpotifyId, Option<Playlist>>,
+    albums: HashMap<SpotifyId, Option<Playlist>>,
+    /// Returned by `refresh_track_metadata`, keyed by Spotify track URL
+    tracks: HashMap<String, Track>,
+    /// Shared between clones, so the client handed to the code under test still counts
+    calls: Arc<Mutex<HashMap<&'static str, usize>>>,
+    /// Number of tracks of every `refresh_track_metadata` call, shared between clones
+    refresh_batches: Arc<Mutex<Vec<usize>>>,
+    /// Reported as the track count of every playlist instead of its canned tracks
+    track_count: Option<u32>,
+    /// How long a playlist fetch takes after counting its tracks as fetched
+    fetch_delay: Duration,
+    /// Playlist fetches time out after fetching this many tracks
+    fetch_timeout_after: Option<usize>,
+}
+
+impl MockSpotifyClient {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers fetches of the playlist `id`, `None` as if Spotify did not know it
+    pub(crate) fn with_playlist(mut self, id: SpotifyId, playlist: Option<Playlist>) -> Self {
+        self.playlists.insert(id, playlist);
+        self
+    }
+
+    /// Answers fetches of the album `id`, `None` as if Spotify did not know it
+    pub(crate) fn with_album(mut self, id: SpotifyId, album: Option<Playlist>) -> Self {
+        self.albums.insert(id, album);
+        self
+    }
+
+    /// Lets playlist fetches take `delay` after reporting their progress
+    pub(crate) fn with_fetch_delay(mut self, delay: Duration) -> Self {
+        self.fetch_delay = delay;
+        self
+    }
+
+    /// Reports `count` as the track count of every playlist, without fetching it
+    pub(crate) fn with_track_count(mut self, count: u32) -> Self {
+        self.track_count = Some(count);
+        self
+    }
+
+    /// Lets playlist fetches time out with a [`PartialFetchError`] after `fetched_tracks`
+    pub(crate) fn with_fetch_timeout_after(mut self, fetched_tracks: usize) -> Self {
+        self.fetch_timeout_after = Some(fetched_tracks);
+        self
+    }
+
+    /// Answers metadata refreshes of the track's Spotify URL with `track`
+    pub(crate) fn with_track(mut self, track: Track) -> Self {
+        self.tracks.insert(track.spotify_url.clone(), track);
+        self
+    }
+
+    /// Number of calls of the [`ISpotifyClient`] method named `method`
+    pub(crate) fn calls(&self, method: &str) -> usize {
+        self.calls.lock().unwrap().get(method).copied().unwrap_or(0)
+    }
+
+    /// Number of tracks asked for by each `refresh_track_metadata` call, in order
+    pub(crate) fn refresh_batches(&self) -> Vec<usize> {
+        self.refresh_batches.lock().unwrap().clone()
+    }
+
+    fn record(&self, method: &'static str) {
+        *self.calls.lock().unwrap().entry(method).or_default() += 1;
+    }
+
+    /// The canned playlist `id` as Spotify returns it, with a new ID on every fetch
+    fn playlist(&self, id: &SpotifyId) -> anyhow::Result<Option<Playlist>> {
+        let playlist = self
+            .playlists
+            .get(id)
+            .ok_or_else(|| anyhow!("no canned Spotify playlist {id}"))?;
+        Ok(playlist.clone().map(|playlist| Playlist {
+            id: PlaylistId::new(),
+            spotify_id: Some(id.clone()),
+            ..playlist
+        }))
+    }
+}
+
+impl ISpotifyClient for MockSpotifyClient {
+    async fn get_playlist_metadata(
+        &self,
+        id: &SpotifyId,
+    ) -> anyhow::Result<Option<PlaylistMetadata>> {
+        self.record("get_playlist_metadata");
+        Ok(self.playlist(id)?.map(|playlist| PlaylistMetadata {
+            track_count: self.track_count.unwrap_or(playlist.track_count() as u32),
+            name: playlist.name,
+        }))
+    }
+
+    async fn get_playlist_track_count(&self, id: &SpotifyId) -> anyhow::Result<u32> {
+        self.record("get_playlist_track_count");
+        if let Some(track_count) = self.track_count {
+            return Ok(track_count);
+        }
+        let playlist = self
+            .playlist(id)?
+            .ok_or_else(|| anyhow!("Spotify playlist {id} not found"))?;
+        Ok(playlist.track_count() as u32)
+    }
+
+    async fn get_playlist_with_tracks(
+        &self,
+        id: &SpotifyId,
+        progress: &FetchProgress,
+    ) -> anyhow::Result<Option<PlaylistFetchResult>> {
+        self.record("get_playlist_with_tracks");
+        if let Some(fetched_tracks) = self.fetch_timeout_after {
+            return Err(
+                anyhow!("operation timed out").context(PartialFetchError { fetched_tracks })
+            );
+        }
+        let Some(playlist) = self.playlist(id)? else {
+            return Ok(None);
+        };
+        progress.start(playlist.track_count() as u32);
+        progress.add(playlist.track_count() as u32);
+        tokio::time::sleep(self.fetch_delay).await;
+
+        Ok(Some(PlaylistFetchResult {
+            playlist,
+            skipped_episodes: 0,
+        }))
+    }
+
+    async fn get_album_tracks(&self, album_id: &SpotifyId) -> anyhow::Result<Option<Playlist>> {
+        self.record("get_album_tracks");
+        self.albums
+            .get(album_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("no canned Spotify album {album_id}"))
+    }
+
+    async fn refresh_track_metadata(
+        &self,
+        spotify_urls: Vec<String>,
+    ) -> anyhow::Result<HashMap<String, Track>> {
+        self.record("refresh_track_metadata");
+        self.refresh_batches
+            .lock()
+            .unwrap()
+            .push(spotify_urls.len());
+        Ok(spotify_urls
+            .into_iter()
+            .filter_map(|url| Some((url.clone(), self.tracks.get(&url)?.clone())))
+            .collect())
+    }
+
+    async fn refresh_token(&self) -> anyhow::Result<()> {
+        self.record("refresh_token");
+        Ok(())
+    }
+
+    async fn has_token(&self) -> bool {
+        true
+    }
+
+    async fn search_playlists(
+        &self,
+        _query: &str,
+        _limit: u32,
+    ) -> anyhow::Result<Vec<SpotifyPlaylistSummary>> {
+        self.record("search_playlists");
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(spotify_url: &str) -> Track {
+        Track {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            year: 1984,
+            spotify_url: spotify_url.to_string(),
+            album_cover_url: None,
+            popularity: None,
+            duration_ms: None,
+            notes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_answers_canned_albums_and_tracks() {
+        let album_id: SpotifyId = "4aawyAB9vmqN3uQ7FjRGTy".parse().unwrap();
+        let album = Playlist {
+            id: PlaylistId::new(),
+            spotify_id: Some(album_id.clone()),
+            name: "Album".to_string(),
+            created_at: None,
+            updated_at: None,
+            back_text: None,
+            spotify_track_count: None,
+            custom_name: None,
+            tracks: vec![track("https://open.spotify.com/track/1")],
+        };
+        let client = MockSpotifyClient::new()
+            .with_album(album_id.clone(), Some(album))
+            .with_track(track("https://open.spotify.com/track/1"));
+
+        let fetched = client.get_album_tracks(&album_id).await.unwrap().unwrap();
+        assert_eq!(fetched.name, "Album");
+
+        let refreshed = client
+            .refresh_track_metadata(vec![
+                "https://open.spotify.com/track/1".to_string(),
+                "https://open.spotify.com/track/2".to_string(),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(
+            refreshed.keys().collect::<Vec<_>>(),
+            vec!["https://open.spotify.com/track/1"]
+        );
+        assert_eq!(client.calls("get_album_tracks"), 1);
+        assert_eq!(client.calls("refresh_track_metadata"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_playlist_is_an_error() {
+        let client = MockSpotifyClient::new();
+        let id: SpotifyId = "37i9dQZF1DXcBWIGoYBM5M".parse().unwrap();
+
+        assert!(
+            client
+                .get_playlist_with_tracks(&id, &FetchProgress::default())
+                .await
+                .is_err()
+        );
+        assert_eq!(client.calls("get_playlist_with_tracks"), 1);
+        assert_eq!(client.calls("get_playlist_metadata"), 0);
+    }
+}
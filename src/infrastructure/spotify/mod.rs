@@ -1,3 +1,7 @@
+#[cfg(test)]
+pub(crate) mod mock;
 pub mod service;
 
+#[cfg(test)]
+pub(crate) use mock::MockSpotifyClient;
 pub use service::SpotifyClient;
@@ -1,65 +1,262 @@
 use crate::Settings;
-use crate::application::ISpotifyClient;
+use crate::application::{
+    FetchProgress, ISpotifyClient, MAX_TRACKS_PER_REQUEST, PartialFetchError, PlaylistFetchResult,
+    PlaylistMetadata, SpotifyError, SpotifyPlaylistSummary,
+};
 use crate::domain;
-use anyhow::Result;
-use futures_util::StreamExt;
-use rspotify::model::PlayableItem;
-use rspotify::{ClientCredsSpotify, Credentials, prelude::BaseClient};
-use tracing::{error, info, instrument};
+use anyhow::{Context, Result, anyhow};
+use futures_util::{Stream, StreamExt};
+use rspotify::http::{HttpError, Query};
+use rspotify::model::{AlbumId, PlayableItem, SearchResult, SearchType, TrackId};
+use rspotify::{
+    ClientCredsSpotify, ClientError, ClientResult, Config, Credentials,
+    prelude::{BaseClient, Id},
+};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{error, info, instrument, warn};
+
+/// Retries of a single request Spotify answered with 429 before giving up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
 
 #[derive(Clone)]
 pub struct SpotifyClient {
     client: ClientCredsSpotify,
+    skip_episodes: bool,
+    /// Caps the `Retry-After` wait so a single response cannot stall a worker for hours
+    max_retry_after_seconds: u64,
 }
 
 impl SpotifyClient {
     #[instrument(skip(settings))]
     pub async fn new(settings: &Settings) -> Result<Self> {
         let creds = Credentials::new(&settings.spotify.client_id, &settings.spotify.client_secret);
-        let client = ClientCredsSpotify::new(creds);
-        client.request_token().await?;
+        let client = match &settings.spotify.api_base_url {
+            Some(base_url) => {
+                let config = Config {
+                    api_base_url: base_url.clone(),
+                    auth_base_url: base_url.clone(),
+                    ..Default::default()
+                };
+                ClientCredsSpotify::with_config(creds, config)
+            }
+            None => ClientCredsSpotify::new(creds),
+        };
+        client
+            .request_token()
+            .await
+            .context("while authenticating with Spotify")?;
         info!("Spotify authentication successful");
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            skip_episodes: settings.spotify.skip_episodes,
+            max_retry_after_seconds: settings.spotify.max_retry_after_seconds,
+        })
+    }
+
+    /// Fetches only `fields` of the playlist, so Spotify does not send its first page of tracks.
+    ///
+    /// rspotify's `playlist()` needs the full object, so the filtered response is parsed here.
+    async fn get_playlist_fields<T: DeserializeOwned>(
+        &self,
+        id: &domain::SpotifyId,
+        fields: &str,
+    ) -> Result<T> {
+        let spotify_id = id.to_string();
+        let rspotify_playlist_id = rspotify::model::PlaylistId::from_id_or_uri(&spotify_id)
+            .with_context(|| format!("while parsing Spotify playlist ID {spotify_id}"))?;
+        let url = format!("playlists/{}", rspotify_playlist_id.id());
+        let params = Query::from([("fields", fields)]);
+        let body = self
+            .with_rate_limit_retries(|| self.client.api_get(&url, &params))
+            .await
+            .with_context(|| format!("while fetching {fields} of Spotify playlist {spotify_id}"))?;
+
+        serde_json::from_str(&body)
+            .with_context(|| format!("while parsing {fields} of Spotify playlist {spotify_id}"))
+    }
+
+    /// Runs `request`, retrying when Spotify rate limits it.
+    ///
+    /// Waits as long as the `Retry-After` header asks, capped at `max_retry_after_seconds`,
+    /// and backs off exponentially when the header is missing. Failures are returned
+    /// with a [`SpotifyError`] context when they are rate limits or rejected credentials.
+    async fn with_rate_limit_retries<T, Fut>(&self, mut request: impl FnMut() -> Fut) -> Result<T>
+    where
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let err = match request().await {
+                Ok(value) => return Ok(value),
+                Err(err) if attempt < MAX_RATE_LIMIT_RETRIES => err,
+                Err(err) => return Err(classify_error(err)),
+            };
+            let Some(retry_after) = rate_limit_retry_after(&err) else {
+                return Err(classify_error(err));
+            };
+            let seconds = retry_after
+                .unwrap_or(1 << attempt)
+                .min(self.max_retry_after_seconds);
+            info!("Spotify rate limited, waiting {} seconds", seconds);
+            tokio::time::sleep(Duration::from_secs(seconds)).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Playlist object as returned with `fields=name,tracks.total`
+#[derive(Deserialize)]
+struct PlaylistMetadataFields {
+    name: String,
+    tracks: TrackTotal,
+}
+
+/// Playlist object as returned with `fields=tracks.total`
+#[derive(Deserialize)]
+struct PlaylistTrackCountFields {
+    tracks: TrackTotal,
+}
+
+#[derive(Deserialize)]
+struct TrackTotal {
+    total: u32,
+}
+
+/// Attaches the [`SpotifyError`] that `err` stands for, if any
+fn classify_error(err: ClientError) -> anyhow::Error {
+    let kind = match &err {
+        ClientError::InvalidToken => Some(SpotifyError::Unauthorized),
+        ClientError::Http(http) => match http.as_ref() {
+            HttpError::StatusCode(response) if response.status().as_u16() == 401 => {
+                Some(SpotifyError::Unauthorized)
+            }
+            _ => {
+                rate_limit_retry_after(&err).map(|retry_after_seconds| SpotifyError::RateLimited {
+                    retry_after_seconds,
+                })
+            }
+        },
+        _ => None,
+    };
+
+    match kind {
+        Some(kind) => anyhow::Error::new(err).context(kind),
+        None => err.into(),
+    }
+}
+
+/// `Some` with the `Retry-After` seconds, if sent, when Spotify answered 429
+fn rate_limit_retry_after(err: &ClientError) -> Option<Option<u64>> {
+    let ClientError::Http(http) = err else {
+        return None;
+    };
+    let HttpError::StatusCode(response) = http.as_ref() else {
+        return None;
+    };
+    if response.status().as_u16() != 429 {
+        return None;
+    }
+
+    Some(
+        response
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok()),
+    )
+}
+
+/// Whether Spotify answered 404, as it does for IDs that belong to nothing
+fn is_not_found(err: &anyhow::Error) -> bool {
+    let Some(ClientError::Http(http)) = err.downcast_ref::<ClientError>() else {
+        return false;
+    };
+    matches!(http.as_ref(), HttpError::StatusCode(response) if response.status().as_u16() == 404)
+}
+
+/// Collects every page of a paginated stream, failing with a [`PartialFetchError`] if any page fails
+async fn collect_pages<T, E>(
+    pages: impl Stream<Item = std::result::Result<Vec<T>, E>>,
+) -> Result<Vec<T>>
+where
+    E: Into<anyhow::Error>,
+{
+    let mut pages = std::pin::pin!(pages);
+    let mut items = Vec::new();
+    while let Some(page) = pages.next().await {
+        match page {
+            Ok(page) => items.extend(page),
+            Err(e) => {
+                return Err(e.into().context(PartialFetchError {
+                    fetched_tracks: items.len(),
+                }));
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// Extracts the track ID from a Spotify track URL like `https://open.spotify.com/track/{id}?si=...`
+pub(crate) fn extract_track_id_from_url(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("/track/")?;
+    let id = rest.split(['?', '/', '#']).next()?;
+
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
     }
+
+    Some(id.to_string())
 }
 
 impl ISpotifyClient for SpotifyClient {
     #[instrument(skip(self), fields(id = %id))]
-    async fn get_playlist(&self, id: &domain::SpotifyId) -> Result<Option<domain::Playlist>> {
-        let spotify_id = id.to_string();
-        let rspotify_playlist_id = rspotify::model::PlaylistId::from_id_or_uri(&spotify_id)?;
-        let full_playlist = self
-            .client
-            .playlist(rspotify_playlist_id, None, None)
-            .await?;
+    async fn get_playlist_metadata(
+        &self,
+        id: &domain::SpotifyId,
+    ) -> Result<Option<PlaylistMetadata>> {
+        let fields: PlaylistMetadataFields =
+            self.get_playlist_fields(id, "name,tracks.total").await?;
 
-        Ok(Some(domain::Playlist {
-            id: domain::PlaylistId::new()?,
-            name: full_playlist.name,
-            tracks: Vec::new(),
-            spotify_id: Some(id.clone()),
-            created_at: None,
-            updated_at: None,
+        Ok(Some(PlaylistMetadata {
+            name: fields.name,
+            track_count: fields.tracks.total,
         }))
     }
 
+    #[instrument(skip(self), fields(id = %id))]
+    async fn get_playlist_track_count(&self, id: &domain::SpotifyId) -> Result<u32> {
+        let fields: PlaylistTrackCountFields = self.get_playlist_fields(id, "tracks.total").await?;
+
+        Ok(fields.tracks.total)
+    }
+
     #[instrument(skip(self), fields(id = %id))]
     async fn get_playlist_with_tracks(
         &self,
         id: &domain::SpotifyId,
-    ) -> Result<Option<domain::Playlist>> {
+        progress: &FetchProgress,
+    ) -> Result<Option<PlaylistFetchResult>> {
         let spotify_id = id.to_string();
-        let rspotify_playlist_id = rspotify::model::PlaylistId::from_id_or_uri(&spotify_id)?;
+        let rspotify_playlist_id = rspotify::model::PlaylistId::from_id_or_uri(&spotify_id)
+            .with_context(|| format!("while parsing Spotify playlist ID {spotify_id}"))?;
 
         let before_full = std::time::Instant::now();
         let full_playlist = self
-            .client
-            .playlist(rspotify_playlist_id, None, None)
-            .await?;
+            .with_rate_limit_retries(|| {
+                self.client
+                    .playlist(rspotify_playlist_id.clone(), None, None)
+            })
+            .await
+            .with_context(|| format!("while fetching Spotify playlist {spotify_id}"))?;
         let after_full = std::time::Instant::now();
         let diff_full = after_full.duration_since(before_full);
         info!("Fetched full playlist metadata in {:?}", diff_full);
+        progress.start(full_playlist.tracks.total);
 
         let limit = full_playlist.tracks.limit;
 
@@ -71,97 +268,297 @@ impl ISpotifyClient for SpotifyClient {
         let pages_to_fetch = full_playlist.tracks.total / limit;
         let futures = (0..pages_to_fetch).map(|page| {
             let offset = 100 + page * limit;
-            let client = &self.client;
             let playlist_id = full_playlist.id.clone();
             async move {
-                client
-                    .playlist_items_manual(playlist_id, None, None, Some(limit), Some(offset))
-                    .await
+                self.with_rate_limit_retries(|| {
+                    self.client.playlist_items_manual(
+                        playlist_id.clone(),
+                        None,
+                        None,
+                        Some(limit),
+                        Some(offset),
+                    )
+                })
+                .await
             }
         });
 
-        let first_page_stream = futures_util::stream::iter(first_100_tracks);
-        let tracks_stream = futures_util::stream::iter(futures)
-            .buffer_unordered(5)
-            .map(|res| match res {
-                Ok(page) => page.items,
-                Err(e) => {
-                    // Log the error and return an empty vector for this page
-                    // In a real application, you might want to handle this differently
-                    error!("Error fetching playlist page: {}", e);
-                    Vec::new()
-                }
-            })
-            .flat_map(futures_util::stream::iter);
-        // Create a stream of all tracks by combining the first 100 tracks with the rest
-        let full_stream = first_page_stream.chain(tracks_stream);
+        let first_page_stream = futures_util::stream::once(async { Ok(first_100_tracks) });
+        // `buffered` keeps the pages in playlist order
+        let pages_stream = futures_util::stream::iter(futures)
+            .buffered(5)
+            .map(|res| res.map(|page| page.items));
 
         let before = std::time::Instant::now();
-        let tracks = full_stream
-            .filter_map(|item| async move {
-                if let Some(PlayableItem::Track(track)) = item.track {
-                    track.try_into().ok()
-                } else {
-                    None
+        // A missing page would silently drop tracks, so any failed page fails the whole fetch
+        let pages = first_page_stream.chain(pages_stream).inspect(|page| {
+            if let Ok(items) = page {
+                progress.add(items.len() as u32);
+            }
+        });
+        let items = collect_pages(pages)
+            .await
+            .with_context(|| format!("while fetching tracks of Spotify playlist {spotify_id}"))?;
+
+        let mut tracks = Vec::with_capacity(items.len());
+        let mut skipped_episodes = 0;
+        for item in items {
+            match item.track {
+                Some(PlayableItem::Track(track)) => {
+                    if let Ok(track) = track.try_into() {
+                        tracks.push(track);
+                    }
                 }
-            })
-            .collect::<Vec<_>>()
-            .await;
+                Some(PlayableItem::Episode(episode)) => {
+                    if !self.skip_episodes {
+                        return Err(anyhow!("Playlist contains podcast episodes"));
+                    }
+                    warn!("Skipping podcast episode: {}", episode.name);
+                    skipped_episodes += 1;
+                }
+                _ => {}
+            }
+        }
         let after = std::time::Instant::now();
         let diff = after.duration_since(before);
         info!("Fetched {} tracks in {:?}", tracks.len(), diff);
 
+        Ok(Some(PlaylistFetchResult {
+            playlist: domain::Playlist {
+                id: domain::PlaylistId::new(),
+                name: full_playlist.name,
+                tracks,
+                spotify_id: Some(id.clone()),
+                created_at: None,
+                updated_at: None,
+                back_text: None,
+                spotify_track_count: Some(full_playlist.tracks.total),
+                custom_name: None,
+            },
+            skipped_episodes,
+        }))
+    }
+
+    #[instrument(skip(self), fields(id = %album_id))]
+    async fn get_album_tracks(
+        &self,
+        album_id: &domain::SpotifyId,
+    ) -> Result<Option<domain::Playlist>> {
+        let spotify_id = album_id.to_string();
+        let rspotify_album_id = AlbumId::from_id(spotify_id.as_str())
+            .with_context(|| format!("while parsing Spotify album ID {spotify_id}"))?;
+
+        // Album tracks are `SimplifiedTrack`s without album details, so the cover and
+        // release date come from the album itself
+        let album = match self
+            .with_rate_limit_retries(|| self.client.album(rspotify_album_id.clone(), None))
+            .await
+        {
+            Ok(album) => album,
+            Err(err) if is_not_found(&err) => return Ok(None),
+            Err(err) => {
+                return Err(err.context(format!("while fetching Spotify album {spotify_id}")));
+            }
+        };
+
+        let pages = self
+            .client
+            .album_track(rspotify_album_id, None)
+            .map(|res| res.map(|track| vec![track]));
+        let items = collect_pages(pages)
+            .await
+            .with_context(|| format!("while fetching tracks of Spotify album {spotify_id}"))?;
+
+        let mut tracks = Vec::with_capacity(items.len());
+        for item in items {
+            match conversions::track_from_album(item, &album) {
+                Ok(track) => tracks.push(track),
+                Err(e) => error!("Skipping album track: {}", e),
+            }
+        }
+        info!("Fetched {} tracks from album {}", tracks.len(), album.name);
+
         Ok(Some(domain::Playlist {
-            id: domain::PlaylistId::new()?,
-            name: full_playlist.name,
+            id: domain::PlaylistId::new(),
+            name: album.name,
             tracks,
-            spotify_id: Some(id.clone()),
+            // Playlists only keep playlist IDs, which refetching relies on
+            spotify_id: None,
             created_at: None,
             updated_at: None,
+            back_text: None,
+            spotify_track_count: Some(album.tracks.total),
+            custom_name: None,
         }))
     }
+
+    #[instrument(skip(self, spotify_urls), fields(count = spotify_urls.len()))]
+    async fn refresh_track_metadata(
+        &self,
+        spotify_urls: Vec<String>,
+    ) -> Result<HashMap<String, domain::Track>> {
+        let ids: Vec<(String, String)> = spotify_urls
+            .into_iter()
+            .filter_map(|url| extract_track_id_from_url(&url).map(|id| (id, url)))
+            .collect();
+
+        let mut refreshed = HashMap::with_capacity(ids.len());
+        for chunk in ids.chunks(MAX_TRACKS_PER_REQUEST) {
+            let urls_by_id: HashMap<&str, &str> = chunk
+                .iter()
+                .map(|(id, url)| (id.as_str(), url.as_str()))
+                .collect();
+            let track_ids = chunk
+                .iter()
+                .map(|(id, _)| TrackId::from_id(id.as_str()))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .context("while parsing Spotify track IDs")?;
+
+            let tracks = self
+                .with_rate_limit_retries(|| self.client.tracks(track_ids.clone(), None))
+                .await
+                .with_context(|| format!("while fetching {} Spotify tracks", chunk.len()))?;
+
+            for full_track in tracks {
+                let Some(url) = full_track
+                    .id
+                    .as_ref()
+                    .and_then(|id| urls_by_id.get(id.id()))
+                else {
+                    continue;
+                };
+                let url = url.to_string();
+                match domain::Track::try_from(full_track) {
+                    Ok(track) => {
+                        refreshed.insert(url, track);
+                    }
+                    Err(e) => error!("Skipping refreshed track {}: {}", url, e),
+                }
+            }
+        }
+
+        Ok(refreshed)
+    }
+
+    async fn refresh_token(&self) -> Result<()> {
+        self.client
+            .request_token()
+            .await
+            .context("while refreshing the Spotify token")
+    }
+
+    #[instrument(skip(self))]
+    async fn search_playlists(
+        &self,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<SpotifyPlaylistSummary>> {
+        let result = self
+            .with_rate_limit_retries(|| {
+                self.client
+                    .search(query, SearchType::Playlist, None, None, Some(limit), None)
+            })
+            .await
+            .with_context(|| format!("while searching Spotify playlists for '{query}'"))?;
+        let SearchResult::Playlists(page) = result else {
+            return Err(anyhow!(
+                "Spotify answered a playlist search with other results"
+            ));
+        };
+
+        page.items
+            .into_iter()
+            .map(|playlist| {
+                Ok(SpotifyPlaylistSummary {
+                    id: domain::SpotifyId::parse(playlist.id.id())?,
+                    name: playlist.name,
+                    owner: playlist
+                        .owner
+                        .display_name
+                        .unwrap_or_else(|| playlist.owner.id.id().to_string()),
+                    track_count: playlist.tracks.total,
+                    image_url: playlist.images.into_iter().next().map(|image| image.url),
+                })
+            })
+            .collect()
+    }
+
+    async fn has_token(&self) -> bool {
+        self.client.get_token().lock().await.is_ok_and(|token| {
+            token
+                .as_ref()
+                .is_some_and(|token| !token.access_token.is_empty())
+        })
+    }
 }
 
 mod conversions {
     use crate::domain::Track;
     use anyhow::{Context, Result, bail};
     use chrono::{Datelike, NaiveDate};
-    use rspotify::model::FullTrack;
+    use rspotify::model::{FullAlbum, FullTrack, SimplifiedTrack};
+
+    /// Parses the year out of a Spotify release date in "YYYY-MM-DD" or "YYYY" format
+    fn release_year(release_date: Option<&str>, track_name: &str) -> Result<i32> {
+        match release_date {
+            None => bail!("Missing release date for track: {}", track_name),
+            Some("") => bail!("Empty release date for track: {}", track_name),
+            Some(date_string) => {
+                // Spotify can return dates in "YYYY-MM-DD" or "YYYY" format
+                // Sometimes the year can be "0000" which is invalid
+                if date_string.contains('-') {
+                    let date = date_string
+                        .parse::<NaiveDate>()
+                        .context(format!("Invalid date format {date_string}"))?;
+                    Ok(date.year())
+                } else {
+                    let year = date_string
+                        .parse::<i32>()
+                        .context(format!("Invalid year format {date_string}"))?;
+                    if year == 0 {
+                        bail!("Year cannot be zero for track: {}", track_name);
+                    }
+                    Ok(year)
+                }
+            }
+        }
+    }
+
+    fn artist_names(artists: &[rspotify::model::SimplifiedArtist]) -> String {
+        artists
+            .iter()
+            .map(|artist| artist.name.clone())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Maps a track of `album`, taking the release year and cover from the album
+    pub(super) fn track_from_album(value: SimplifiedTrack, album: &FullAlbum) -> Result<Track> {
+        let year = release_year(Some(&album.release_date), &value.name)?;
+        let spotify_url = match value.external_urls.get("spotify") {
+            None => bail!("Missing Spotify URL for track: {}", value.name),
+            Some(url) => url.clone(),
+        };
+
+        Ok(Track {
+            artist: artist_names(&value.artists),
+            title: value.name,
+            year,
+            spotify_url,
+            album_cover_url: album.images.first().map(|img| img.url.clone()),
+            // Simplified tracks carry no popularity
+            popularity: None,
+            duration_ms: u32::try_from(value.duration.num_milliseconds()).ok(),
+            notes: None,
+        })
+    }
 
     impl TryFrom<FullTrack> for Track {
         type Error = anyhow::Error;
 
         fn try_from(value: FullTrack) -> Result<Self> {
-            let artist_names = value
-                .artists
-                .iter()
-                .map(|artist| artist.name.clone())
-                .collect::<Vec<String>>()
-                .join(", ");
-            let year = match value.album.release_date {
-                None => bail!("Missing release date for track: {}", value.name),
-                Some(ref date_string) if date_string.is_empty() => {
-                    bail!("Empty release date for track: {}", value.name)
-                }
-                Some(ref date_string) => {
-                    // Spotify can return dates in "YYYY-MM-DD" or "YYYY" format
-                    // Sometimes the year can be "0000" which is invalid
-                    if date_string.contains('-') {
-                        let date = date_string
-                            .parse::<NaiveDate>()
-                            .context(format!("Invalid date format {date_string}"))?;
-                        date.year()
-                    } else {
-                        let year = date_string
-                            .parse::<i32>()
-                            .context(format!("Invalid year format {date_string}"))?;
-                        if year == 0 {
-                            bail!("Year cannot be zero for track: {}", value.name);
-                        }
-                        year
-                    }
-                }
-            };
+            let artist_names = artist_names(&value.artists);
+            let year = release_year(value.album.release_date.as_deref(), &value.name)?;
             let spotify_url = match value.external_urls.get("spotify") {
                 None => bail!("Missing Spotify URL for track: {}", value.name),
                 Some(url) => url.clone(),
@@ -173,7 +570,50 @@ mod conversions {
                 year,
                 spotify_url,
                 album_cover_url: value.album.images.first().map(|img| img.url.clone()),
+                popularity: Some(value.popularity.min(u8::MAX as u32) as u8),
+                duration_ms: u32::try_from(value.duration.num_milliseconds()).ok(),
+                notes: None,
             })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_collect_pages_fails_on_page_error() {
+        let pages = futures_util::stream::iter(vec![
+            Ok(vec![1, 2, 3]),
+            Err(std::io::Error::from(std::io::ErrorKind::TimedOut)),
+            Ok(vec![4]),
+        ]);
+
+        let err = collect_pages(pages).await.unwrap_err();
+        let partial = err.downcast_ref::<PartialFetchError>().unwrap();
+        assert_eq!(partial.fetched_tracks, 3);
+    }
+
+    #[test]
+    fn test_extract_track_id_from_url() {
+        assert_eq!(
+            extract_track_id_from_url("https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC"),
+            Some("4uLU6hMCjMI75M1A2tKUQC".to_string())
+        );
+        assert_eq!(
+            extract_track_id_from_url(
+                "https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC?si=abc123"
+            ),
+            Some("4uLU6hMCjMI75M1A2tKUQC".to_string())
+        );
+        assert_eq!(
+            extract_track_id_from_url("https://open.spotify.com/album/4uLU6hMCjMI75M1A2tKUQC"),
+            None
+        );
+        assert_eq!(
+            extract_track_id_from_url("https://open.spotify.com/track/"),
+            None
+        );
+    }
+}
@@ -1,7 +1,12 @@
 pub mod application;
+pub mod cli;
 pub mod config;
 pub mod domain;
+#[cfg(test)]
+mod fixtures;
 pub mod infrastructure;
+pub mod metrics;
+pub mod telemetry;
 pub mod web;
 
 pub use application::PdfGenerator;
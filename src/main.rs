@@ -1,23 +1,68 @@
-use anyhow::Result;
-use hitster::application::worker::{GeneratePlaylistPdfsTask, RefetchPlaylistTask, Worker};
-use hitster::application::{PlaylistService, worker};
-use hitster::infrastructure::JobsRepository;
+use anyhow::{Context, Result};
+use clap::Parser;
+use hitster::application::worker::{
+    GeneratePlaylistPdfsTask, RefetchPlaylistTask, Worker, WorkerOptions,
+};
+use hitster::application::{
+    CardBorderStyle, CardFonts, IJobsRepository, PdfGeneratorOptions, PlaylistService,
+    QrCodeFormat, worker,
+};
+use hitster::cli::{Cli, Command};
+use hitster::config::{CardBorder, ConfigError, QrCodeOutput};
 use hitster::infrastructure::playlist::PlaylistRepository;
+use hitster::infrastructure::{
+    EventStore, JobsRepository, check_database_integrity, spawn_pool_metrics,
+};
+use hitster::web::admin_auth::AdminToken;
+use hitster::web::controllers::jobs::SseLimits;
+use hitster::web::rate_limit::RateLimiter;
 use hitster::web::server::run;
 use hitster::{PdfGenerator, SpotifyClient};
 use sqlx::sqlite::SqliteConnectOptions;
+use std::num::NonZeroU32;
 use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter("hitster=debug")
-        .init();
+    let tracer_provider = hitster::telemetry::init_tracing()?;
 
+    let cli = Cli::parse();
     let settings = hitster::Settings::new()?;
+    settings.validate().map_err(invalid_configuration)?;
 
     // infrastructure
     let spotify_client = Arc::new(SpotifyClient::new(&settings).await?);
+    worker::spawn_spotify_token_refresh(spotify_client.clone());
+    let fonts = match &settings.pdf.fallback_font_path {
+        Some(path) => CardFonts::load_fallback(path)?,
+        None => CardFonts::default(),
+    };
+    let pdf_generator = Arc::new(PdfGenerator::with_options(PdfGeneratorOptions {
+        card_border: card_border_style(settings.pdf.card_border),
+        show_notes: settings.pdf.show_notes,
+        qr_code_format: qr_code_format(settings.pdf.qr_code_format),
+        fonts,
+    }));
+
+    if let Some(Command::Generate(command)) = cli.command {
+        let generated = hitster::cli::generate(
+            &command,
+            spotify_client.as_ref(),
+            pdf_generator.as_ref(),
+            &settings.pdf.generator_config(),
+        )
+        .await;
+        // The spans of a failed run are the interesting ones, so flush them either way
+        hitster::telemetry::shutdown_tracing(tracer_provider)?;
+        return generated.map(|_| ());
+    }
+
+    // Only the server opens the database, so the CLI runs without one
+    settings
+        .validate_database()
+        .map_err(invalid_configuration)?;
 
     // Database setup with connection pooling
     let sqlite_pool = sqlx::sqlite::SqlitePoolOptions::new()
@@ -32,44 +77,133 @@ async fn main() -> Result<()> {
         )
         .await?;
     sqlx::migrate!("./migrations").run(&sqlite_pool).await?;
+    if settings.db_integrity_check_on_startup {
+        check_database_integrity(&sqlite_pool).await?;
+    }
+    spawn_pool_metrics(sqlite_pool.clone());
 
     let jobs_repository = Arc::new(JobsRepository::new(sqlite_pool.clone()));
+    worker::spawn_job_cleanup(
+        jobs_repository.clone(),
+        chrono::Duration::days(settings.job_retention_days.into()),
+    );
     let playlist_repository = Arc::new(PlaylistRepository::new(sqlite_pool.clone()).await?);
-    let pdf_generator = Arc::new(PdfGenerator::new());
-
+    let event_store = Arc::new(EventStore::new(sqlite_pool.clone()));
+    // Jobs interrupted by the previous shutdown are run again from the start
+    let interrupted = jobs_repository.reset_processing().await?;
+    if interrupted > 0 {
+        info!(
+            "Retrying {} jobs interrupted by the last shutdown",
+            interrupted
+        );
+    }
     let pdf_worker_state = Arc::new(worker::GeneratePlaylistPdfsState {
         playlist_repository: playlist_repository.clone(),
         pdf_generator: pdf_generator.clone(),
+        event_store: event_store.clone(),
+        pdf_config: settings.pdf.generator_config(),
     });
     let pdf_worker: Worker<
         JobsRepository,
-        GeneratePlaylistPdfsTask<PlaylistRepository, PdfGenerator>,
-    > = Worker::new(jobs_repository.clone(), pdf_worker_state);
+        GeneratePlaylistPdfsTask<PlaylistRepository, PdfGenerator, EventStore>,
+    > = Worker::with_options(
+        jobs_repository.clone(),
+        pdf_worker_state,
+        WorkerOptions {
+            concurrency: settings.pdf_worker_count.into(),
+            capacity: settings.job_queue_capacity,
+            ..Default::default()
+        },
+    )
+    .with_pending(worker::load_pending_tasks(jobs_repository.as_ref()).await?);
     let refetch_worker_state = Arc::new(worker::RefetchPlaylistState {
         playlist_repository: playlist_repository.clone(),
         spotify_client: spotify_client.clone(),
+        event_store: event_store.clone(),
+        jobs_repository: jobs_repository.clone(),
+        dedup_on_import: settings.dedup_on_import,
     });
     let refetch_worker: Worker<
         JobsRepository,
-        RefetchPlaylistTask<PlaylistRepository, SpotifyClient>,
-    > = Worker::new(jobs_repository.clone(), refetch_worker_state);
+        RefetchPlaylistTask<PlaylistRepository, SpotifyClient, EventStore, JobsRepository>,
+    > = Worker::with_options(
+        jobs_repository.clone(),
+        refetch_worker_state,
+        WorkerOptions {
+            capacity: settings.job_queue_capacity,
+            ..Default::default()
+        },
+    )
+    .with_pending(worker::load_pending_tasks(jobs_repository.as_ref()).await?);
+
+    let pdf_worker = Arc::new(pdf_worker);
+    let refetch_worker = Arc::new(refetch_worker);
+    let stale_after = chrono::Duration::minutes(settings.stale_job_minutes.into());
+    worker::spawn_stale_job_requeue(playlist_repository.clone(), pdf_worker.clone(), stale_after);
+    worker::spawn_stale_job_requeue(
+        playlist_repository.clone(),
+        refetch_worker.clone(),
+        stale_after,
+    );
 
     // application
     let playlist_service = PlaylistService::new(
         playlist_repository,
         spotify_client,
         jobs_repository,
-        Arc::new(pdf_worker),
-        Arc::new(refetch_worker),
+        event_store,
+        pdf_worker.clone(),
+        refetch_worker.clone(),
+        settings.dedup_on_import,
+        settings.max_playlist_tracks,
     )
     .into();
 
-    run(
+    let server = run(
         &settings.server.host,
         settings.server.port,
         playlist_service,
-    )
-    .await?;
+        settings.prefer_websocket,
+        SseLimits::new(
+            settings.max_sse_connections,
+            Duration::from_secs(settings.sse_idle_timeout_seconds),
+        ),
+        RateLimiter::per_minute(
+            NonZeroU32::new(settings.pdf_rate_limit_per_minute)
+                .context("pdf_rate_limit_per_minute must be at least 1")?,
+        ),
+        AdminToken::new(settings.admin_token.clone()),
+    );
+    tokio::select! {
+        result = server => result?,
+        result = tokio::signal::ctrl_c() => {
+            result?;
+            info!("Shutting down, waiting for running jobs to finish");
+        }
+    }
+
+    // Jobs still queued stay pending and are resumed on the next start
+    tokio::join!(pdf_worker.shutdown(), refetch_worker.shutdown());
+    hitster::telemetry::shutdown_tracing(tracer_provider)
+}
+
+fn invalid_configuration(errors: Vec<ConfigError>) -> anyhow::Error {
+    let errors: Vec<String> = errors.iter().map(|error| format!("  - {error}")).collect();
+    anyhow::anyhow!("Invalid configuration:\n{}", errors.join("\n"))
+}
+
+fn card_border_style(border: CardBorder) -> CardBorderStyle {
+    match border {
+        CardBorder::Solid => CardBorderStyle::Solid,
+        CardBorder::Dashed => CardBorderStyle::DASHED,
+        CardBorder::Dotted => CardBorderStyle::Dotted,
+        CardBorder::None => CardBorderStyle::None,
+    }
+}
 
-    Ok(())
+fn qr_code_format(output: QrCodeOutput) -> QrCodeFormat {
+    match output {
+        QrCodeOutput::Png => QrCodeFormat::default(),
+        QrCodeOutput::Svg => QrCodeFormat::Svg,
+    }
 }
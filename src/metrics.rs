@@ -0,0 +1,94 @@
+//! Process wide metrics exported in the Prometheus text format
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Worker tasks that panicked instead of returning a result
+pub static WORKER_PANICS_TOTAL: Counter =
+    Counter::new("hitster_worker_panics_total", "Worker tasks that panicked");
+
+/// Tracks received from Spotify before a playlist fetch failed and was discarded
+pub static PARTIALLY_FETCHED_TRACKS_TOTAL: Counter = Counter::new(
+    "hitster_partially_fetched_tracks_total",
+    "Tracks fetched from Spotify before a playlist fetch failed",
+);
+
+/// Jobs waiting for a worker, sampled from the database on every scrape
+pub const JOBS_PENDING_TOTAL: Gauge = Gauge {
+    name: "hitster_jobs_pending_total",
+    help: "Jobs waiting for a worker",
+};
+
+static COUNTERS: &[&Counter] = &[&WORKER_PANICS_TOTAL, &PARTIALLY_FETCHED_TRACKS_TOTAL];
+
+/// Monotonically increasing counter
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    value: AtomicU64,
+}
+
+impl Counter {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, amount: u64) {
+        self.value.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+/// Value read at scrape time instead of being tracked by the process
+pub struct Gauge {
+    name: &'static str,
+    help: &'static str,
+}
+
+impl Gauge {
+    /// Renders the gauge with `value` in the Prometheus text exposition format
+    pub fn render(&self, value: u64) -> String {
+        format!(
+            "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n",
+            name = self.name,
+            help = self.help,
+        )
+    }
+}
+
+/// Renders all counters in the Prometheus text exposition format
+pub fn render() -> String {
+    let mut output = String::new();
+    for counter in COUNTERS {
+        let _ = writeln!(output, "# HELP {} {}", counter.name, counter.help);
+        let _ = writeln!(output, "# TYPE {} counter", counter.name);
+        let _ = writeln!(output, "{} {}", counter.name, counter.get());
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gauge_render() {
+        assert_eq!(
+            JOBS_PENDING_TOTAL.render(3),
+            "# HELP hitster_jobs_pending_total Jobs waiting for a worker\n\
+             # TYPE hitster_jobs_pending_total gauge\n\
+             hitster_jobs_pending_total 3\n"
+        );
+    }
+}
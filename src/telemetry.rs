@@ -0,0 +1,52 @@
+//! Log output and, when an OTLP endpoint is configured, trace export
+
+use anyhow::Context;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Standard OpenTelemetry variable naming the collector, e.g. `http://localhost:4318`
+const OTLP_ENDPOINT_VAR: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Installs the global subscriber logging to stdout.
+///
+/// When `OTEL_EXPORTER_OTLP_ENDPOINT` is set, spans are also exported there over OTLP/HTTP.
+/// The returned provider must be shut down before exiting, so the last spans are flushed.
+pub fn init_tracing() -> anyhow::Result<Option<SdkTracerProvider>> {
+    let registry = tracing_subscriber::registry()
+        .with(EnvFilter::new("hitster=debug"))
+        .with(tracing_subscriber::fmt::layer());
+
+    if std::env::var_os(OTLP_ENDPOINT_VAR).is_none() {
+        registry.init();
+        return Ok(None);
+    }
+
+    // The exporter reads the endpoint and the other OTEL_EXPORTER_OTLP_* variables itself
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()
+        .context("while creating the OTLP span exporter")?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name("hitster").build())
+        .build();
+
+    registry
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("hitster")))
+        .init();
+    Ok(Some(provider))
+}
+
+/// Flushes the spans not exported yet, if [`init_tracing`] set up an exporter
+pub fn shutdown_tracing(provider: Option<SdkTracerProvider>) -> anyhow::Result<()> {
+    if let Some(provider) = provider {
+        provider
+            .shutdown()
+            .context("while flushing the remaining traces")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! Access to the admin pages, which show the data of every session
+
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+
+/// Token admin requests have to send as `Authorization: Bearer <token>`.
+///
+/// Without a token the admin pages are disabled.
+#[derive(Clone, Default)]
+pub struct AdminToken(Option<Arc<str>>);
+
+impl AdminToken {
+    pub fn new(token: Option<String>) -> Self {
+        Self(token.map(Into::into))
+    }
+
+    fn accepts(&self, authorization: Option<&str>) -> bool {
+        let (Some(expected), Some(provided)) = (
+            &self.0,
+            authorization.and_then(|value| value.strip_prefix("Bearer ")),
+        ) else {
+            return false;
+        };
+        // Compared in constant time, so response times do not reveal how much of a guess matched
+        expected.len() == provided.len()
+            && expected
+                .bytes()
+                .zip(provided.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+/// Answers 404 while no admin token is configured and 401 to requests without it
+pub async fn require_admin_token(
+    State(token): State<AdminToken>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if token.0.is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let authorization = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !token.accepts(authorization) {
+        tracing::info!("Rejected admin request without a valid token");
+        return (StatusCode::UNAUTHORIZED, [(WWW_AUTHENTICATE, "Bearer")]).into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    async fn status_of(token: AdminToken, authorization: Option<&str>) -> StatusCode {
+        let app = Router::new().route(
+            "/admin",
+            get(|| async { "ok" }).layer(axum::middleware::from_fn_with_state(
+                token,
+                require_admin_token,
+            )),
+        );
+        let mut request = Request::get("/admin");
+        if let Some(authorization) = authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_admin_pages_need_the_token() {
+        let token = AdminToken::new(Some("secret".to_string()));
+
+        assert_eq!(
+            status_of(token.clone(), Some("Bearer secret")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_of(token.clone(), Some("Bearer secreT")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status_of(token, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status_of(AdminToken::default(), Some("Bearer secret")).await,
+            StatusCode::NOT_FOUND
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use crate::application::playlist_service::IPlaylistService;
+use crate::web::controllers::jobs::ListJobsQuery;
+use crate::web::error::TemplateError;
+use crate::web::server::Services;
+use crate::web::templates::AdminJobsTemplate;
+use crate::web::templates::admin::AdminJobVM;
+use askama::Template;
+use axum::extract::{Query, State};
+use axum::response::Html;
+
+/// Dashboard of the jobs of every session, newest first, behind [`require_admin_token`]
+///
+/// [`require_admin_token`]: crate::web::admin_auth::require_admin_token
+pub async fn list_jobs<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    Query(query): Query<ListJobsQuery>,
+) -> Result<Html<String>, TemplateError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let pagination = query.pagination()?;
+    let filter = query.filter();
+    let page = services
+        .playlist_service
+        .list_jobs_with_playlist_names(&filter, pagination)
+        .await?;
+
+    let jobs = page
+        .jobs
+        .iter()
+        .map(|(job, playlist_name)| AdminJobVM::new(job, playlist_name.clone()))
+        .collect();
+
+    let shown = u64::from(pagination.offset()) + page.jobs.len() as u64;
+    let template = AdminJobsTemplate {
+        title: "Jobs".to_string(),
+        jobs,
+        status_filter: filter
+            .status
+            .map(|status| status.to_string())
+            .unwrap_or_default(),
+        page: pagination.page,
+        previous_page: (pagination.page > 1).then(|| pagination.page - 1),
+        next_page: pagination.next().filter(|_| shown < page.total),
+        total: page.total,
+    };
+
+    Ok(Html(template.render()?))
+}
@@ -0,0 +1,311 @@
+use crate::application::playlist_service::IPlaylistService;
+use crate::domain;
+use crate::web::error::ApiError;
+use crate::web::server::Services;
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, State};
+use axum::http::HeaderValue;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use std::io::Write;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+/// Number of chunks buffered between the ZIP writer and the response body
+const ZIP_STREAM_BUFFER: usize = 16;
+
+pub async fn export_pdf_bundle<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    let playlist = services
+        .playlist_service
+        .get_playlist(&user, &playlist_id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    let [front, back] = services
+        .playlist_service
+        .get_playlist_pdfs(&user, &playlist_id)
+        .await?;
+
+    let name = playlist.file_stem();
+    let entries = vec![
+        (format!("{name}_front.pdf"), Vec::<u8>::from(front)),
+        (format!("{name}_back.pdf"), Vec::<u8>::from(back)),
+    ];
+
+    Ok(zip_response(&format!("{name}_cards.zip"), entries))
+}
+
+/// Front and back PDFs of the playlist as `hitster_<id>.zip`, holding `front.pdf` and
+/// `back.pdf`
+pub async fn download_pdfs<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    services
+        .playlist_service
+        .get_playlist(&user, &playlist_id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    let [front, back] = services
+        .playlist_service
+        .get_playlist_pdfs(&user, &playlist_id)
+        .await?;
+
+    let entries = vec![
+        ("front.pdf".to_string(), Vec::<u8>::from(front)),
+        ("back.pdf".to_string(), Vec::<u8>::from(back)),
+    ];
+
+    Ok(zip_response(&format!("hitster_{playlist_id}.zip"), entries))
+}
+
+/// Tracks of the playlist as a CSV attachment, positions count from 0 like the track routes
+pub async fn export_csv<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    let playlist = services
+        .playlist_service
+        .get_playlist(&user, &playlist_id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(csv_response(
+        &format!("{}.csv", playlist.file_stem()),
+        playlist.tracks,
+    ))
+}
+
+/// Streams the tracks as CSV rows one at a time instead of writing the whole file first
+fn csv_response(filename: &str, tracks: Vec<domain::Track>) -> Response {
+    let header = csv_row([
+        "position",
+        "title",
+        "artist",
+        "year",
+        "spotify_url",
+        "album_cover_url",
+    ]);
+    let rows = tracks.into_iter().enumerate().map(|(position, track)| {
+        csv_row([
+            position.to_string(),
+            track.title,
+            track.artist,
+            track.year.to_string(),
+            track.spotify_url,
+            track.album_cover_url.unwrap_or_default(),
+        ])
+    });
+
+    (
+        [
+            (
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/csv; charset=utf-8"),
+            ),
+            (
+                CONTENT_DISPOSITION,
+                HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)).unwrap(),
+            ),
+        ],
+        Body::from_stream(futures_util::stream::iter(
+            std::iter::once(header).chain(rows),
+        )),
+    )
+        .into_response()
+}
+
+/// A single CSV line, quoting fields as needed
+fn csv_row<I, T>(fields: I) -> Result<Bytes, std::io::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(fields)?;
+    let row = writer
+        .into_inner()
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    Ok(Bytes::from(row))
+}
+
+/// Streams a ZIP archive of `entries` as an attachment while it is being written
+fn zip_response(filename: &str, entries: Vec<(String, Vec<u8>)>) -> Response {
+    let (sender, receiver) = mpsc::channel(ZIP_STREAM_BUFFER);
+
+    tokio::task::spawn_blocking(move || {
+        let error_sender = sender.clone();
+        if let Err(e) = write_zip(ChannelWriter(sender), entries) {
+            tracing::error!("Failed to write ZIP archive: {}", e);
+            let _ = error_sender.blocking_send(Err(std::io::Error::other(e)));
+        }
+    });
+
+    (
+        [
+            (CONTENT_TYPE, HeaderValue::from_static("application/zip")),
+            (
+                CONTENT_DISPOSITION,
+                HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)).unwrap(),
+            ),
+        ],
+        Body::from_stream(ReceiverStream::new(receiver)),
+    )
+        .into_response()
+}
+
+fn write_zip(writer: ChannelWriter, entries: Vec<(String, Vec<u8>)>) -> zip::result::ZipResult<()> {
+    let mut zip = ZipWriter::new_stream(writer);
+    for (name, data) in entries {
+        zip.start_file(name, SimpleFileOptions::default())?;
+        zip.write_all(&data)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Forwards everything written to it as chunks of the response body
+struct ChannelWriter(mpsc::Sender<Result<Bytes, std::io::Error>>);
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "client went away"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{IJobsRepository, IPlaylistRepository};
+    use crate::fixtures;
+    use crate::infrastructure::spotify::MockSpotifyClient;
+
+    #[tokio::test]
+    async fn test_csv_response_quotes_fields() {
+        let tracks = vec![domain::Track {
+            title: "Hello, \"World\"".to_string(),
+            artist: "Artist".to_string(),
+            year: 1999,
+            spotify_url: "https://open.spotify.com/track/1".to_string(),
+            album_cover_url: None,
+            popularity: None,
+            duration_ms: None,
+            notes: None,
+        }];
+
+        let response = csv_response("Party.csv", tracks);
+        assert_eq!(
+            response.headers()[CONTENT_DISPOSITION],
+            "attachment; filename=\"Party.csv\""
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::str::from_utf8(&body).unwrap(),
+            "position,title,artist,year,spotify_url,album_cover_url\n\
+             0,\"Hello, \"\"World\"\"\",Artist,1999,https://open.spotify.com/track/1,\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_zip_response_contains_both_pdfs() {
+        let entries = vec![
+            ("Party_front.pdf".to_string(), b"%PDF-front".to_vec()),
+            ("Party_back.pdf".to_string(), b"%PDF-back".to_vec()),
+        ];
+
+        let response = zip_response("Party_cards.zip", entries);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/zip");
+        assert_eq!(
+            response.headers()[CONTENT_DISPOSITION],
+            "attachment; filename=\"Party_cards.zip\""
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(body)).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert_eq!(archive.by_index(0).unwrap().name(), "Party_front.pdf");
+        assert_eq!(archive.by_index(1).unwrap().name(), "Party_back.pdf");
+    }
+
+    #[tokio::test]
+    async fn test_download_pdfs_names_the_archive_after_the_playlist_id() {
+        let (service, playlist_repository, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let user = domain::UserContext::new(domain::SessionId::new());
+        let playlist = playlist_repository
+            .create(&user.session_id, &fixtures::playlist("Party", vec![]))
+            .await
+            .unwrap();
+        let mut job = domain::Job::new(serde_json::json!({
+            "type": domain::JobType::GeneratePlaylistPdfs,
+            "playlist_id": playlist.id,
+            "session_id": user.session_id,
+        }));
+        job.status = domain::JobStatus::Completed;
+        job.completed_at = Some(chrono::Utc::now());
+        job.result = Some(serde_json::json!({ "front_size": 10, "back_size": 9 }));
+        let job = jobs_repository.create(job).await.unwrap();
+        // Creating a job does not store when it completed
+        jobs_repository.update(job.clone()).await.unwrap();
+        playlist_repository
+            .save_pdfs(
+                &job.id,
+                &[b"%PDF-front".to_vec().into(), b"%PDF-back".to_vec().into()],
+            )
+            .await
+            .unwrap();
+
+        let response = download_pdfs(
+            State(fixtures::services(service)),
+            user,
+            Path(playlist.id.to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            response.headers()[CONTENT_DISPOSITION],
+            format!("attachment; filename=\"hitster_{}.zip\"", playlist.id).as_str()
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(body)).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert_eq!(archive.by_index(0).unwrap().name(), "front.pdf");
+        assert_eq!(archive.by_index(1).unwrap().name(), "back.pdf");
+    }
+}
@@ -0,0 +1,55 @@
+use crate::application::playlist_service::IPlaylistService;
+use crate::web::error::ApiError;
+use crate::web::server::Services;
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    status: &'static str,
+    pending_jobs: u64,
+}
+
+/// Ready once the database answers, reporting the job queue depth
+pub async fn ready<PlaylistService: IPlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+) -> Result<Json<ReadinessResponse>, ApiError> {
+    let pending_jobs = services.playlist_service.pending_job_count().await?;
+    Ok(Json(ReadinessResponse {
+        status: "ok",
+        pending_jobs,
+    }))
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct HealthResponse {
+    /// `ok` or why the database check failed
+    db: String,
+    /// `ok` or why the Spotify check failed
+    spotify: String,
+}
+
+/// Liveness check of the database and the Spotify token, 503 when either fails.
+///
+/// Load balancers call this without credentials, keep it outside of any authentication.
+pub async fn health<PlaylistService: IPlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+) -> (StatusCode, Json<HealthResponse>) {
+    let report = services.playlist_service.check_health().await;
+    let status = if report.is_healthy() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let describe = |check: Result<(), String>| check.err().unwrap_or_else(|| "ok".to_string());
+
+    (
+        status,
+        Json(HealthResponse {
+            db: describe(report.database),
+            spotify: describe(report.spotify),
+        }),
+    )
+}
@@ -0,0 +1,625 @@
+use crate::application::playlist_service::{IPlaylistService, JobStats, LibraryStats};
+use crate::application::worker::RefetchPlaylistResult;
+use crate::application::{JobFilter, PageOutOfRangeError, Pagination};
+use crate::domain;
+use crate::web::error::ApiError;
+use crate::web::server::Services;
+use axum::Json;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Response;
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::ReceiverStream;
+use uuid::Uuid;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const DEFAULT_JOBS_PER_PAGE: u32 = 20;
+const MAX_JOBS_PER_PAGE: u32 = 100;
+
+/// Caps the number of open SSE and WebSocket job status streams and how long they wait on
+/// idle clients
+#[derive(Debug, Clone)]
+pub struct SseLimits {
+    active: Arc<AtomicUsize>,
+    max_connections: usize,
+    idle_timeout: Duration,
+}
+
+impl SseLimits {
+    pub fn new(max_connections: usize, idle_timeout: Duration) -> Self {
+        Self {
+            active: Arc::new(AtomicUsize::new(0)),
+            max_connections,
+            idle_timeout,
+        }
+    }
+
+    pub fn active_connections(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Reserves a connection slot, failing with 503 when all slots are taken
+    fn acquire(&self) -> Result<SseConnection, ApiError> {
+        let previous = self.active.fetch_add(1, Ordering::AcqRel);
+        if previous >= self.max_connections {
+            self.active.fetch_sub(1, Ordering::AcqRel);
+            return Err(ApiError::ServiceUnavailable(
+                "Too many open job status streams".to_string(),
+            ));
+        }
+        Ok(SseConnection(self.active.clone()))
+    }
+}
+
+/// Frees its connection slot when dropped
+struct SseConnection(Arc<AtomicUsize>);
+
+impl Drop for SseConnection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Forwards `events` to the returned stream from a background task.
+///
+/// The task stops, freeing the connection slot, once the client disconnects
+/// or has not taken an event for the idle timeout.
+pub(crate) fn forward_events<T>(
+    limits: &SseLimits,
+    events: impl Stream<Item = T> + Send + 'static,
+) -> Result<ReceiverStream<T>, ApiError>
+where
+    T: Send + 'static,
+{
+    let connection = limits.acquire()?;
+    let idle_timeout = limits.idle_timeout;
+    let (sender, receiver) = mpsc::channel(1);
+
+    tokio::spawn(async move {
+        let _connection = connection;
+        let mut events = std::pin::pin!(events);
+        while let Some(event) = events.next().await {
+            match tokio::time::timeout(idle_timeout, sender.send(event)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) => {
+                    tracing::debug!("Job status client disconnected, closing stream");
+                    break;
+                }
+                Err(_) => {
+                    tracing::info!(
+                        "Job status client idle for {:?}, closing stream",
+                        idle_timeout
+                    );
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(ReceiverStream::new(receiver))
+}
+
+#[derive(Deserialize)]
+pub struct ListJobsQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    #[serde(rename = "type")]
+    job_type: Option<domain::JobType>,
+    status: Option<domain::JobStatus>,
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+impl ListJobsQuery {
+    pub(crate) fn pagination(&self) -> Result<Pagination, PageOutOfRangeError> {
+        Pagination::new(
+            self.page.unwrap_or(1),
+            self.per_page
+                .unwrap_or(DEFAULT_JOBS_PER_PAGE)
+                .clamp(1, MAX_JOBS_PER_PAGE),
+        )
+    }
+
+    pub(crate) fn filter(&self) -> JobFilter {
+        JobFilter {
+            from: self.from,
+            to: self.to,
+            status: self.status.clone(),
+            filter_by_type: self.job_type.map(|job_type| job_type.as_str().to_string()),
+            session_id: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct JobSummaryResponse {
+    id: Uuid,
+    #[serde(rename = "type")]
+    job_type: Option<domain::JobType>,
+    status: String,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    error_message: Option<String>,
+}
+
+impl From<domain::Job> for JobSummaryResponse {
+    fn from(job: domain::Job) -> Self {
+        Self {
+            job_type: job.job_type(),
+            id: job.id.into(),
+            status: job.status.to_string(),
+            created_at: job.created_at,
+            completed_at: job.completed_at,
+            error_message: job.error_message,
+        }
+    }
+}
+
+/// Paginated envelope for job listings
+#[derive(Serialize)]
+pub struct JobsPageResponse {
+    jobs: Vec<JobSummaryResponse>,
+    page: u32,
+    per_page: u32,
+    total: u64,
+}
+
+/// Number of jobs in each status
+#[derive(Debug, Serialize, PartialEq)]
+pub struct JobStatsResponse {
+    pending: u64,
+    processing: u64,
+    retrying: u64,
+    completed: u64,
+    failed: u64,
+    cancelled: u64,
+}
+
+impl From<JobStats> for JobStatsResponse {
+    fn from(stats: JobStats) -> Self {
+        Self {
+            pending: stats.pending,
+            processing: stats.processing,
+            retrying: stats.retrying,
+            completed: stats.completed,
+            failed: stats.failed,
+            cancelled: stats.cancelled,
+        }
+    }
+}
+
+/// Job counts by status next to the size of the playlist library
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StatsResponse {
+    #[serde(flatten)]
+    jobs: JobStatsResponse,
+    playlists: u64,
+    tracks: u64,
+}
+
+impl StatsResponse {
+    fn new(jobs: JobStats, library: LibraryStats) -> Self {
+        Self {
+            jobs: jobs.into(),
+            playlists: library.playlists,
+            tracks: library.tracks,
+        }
+    }
+}
+
+/// Job status sent by the SSE and WebSocket status endpoints, as JSON over WebSockets
+#[derive(Debug, Serialize, PartialEq)]
+pub struct JobStatusMessage {
+    pub(crate) status: String,
+    /// Units of work done so far, for running jobs that report it
+    pub(crate) progress: Option<domain::JobProgress>,
+    /// Set once the job finished: the status, followed by why it failed or, for
+    /// refetches, what changed, such as "completed: 12 tracks added, 3 removed"
+    pub(crate) done: Option<String>,
+}
+
+impl From<&domain::Job> for JobStatusMessage {
+    fn from(job: &domain::Job) -> Self {
+        let done = job
+            .status
+            .is_done()
+            .then(|| match (&job.status, &job.error_message) {
+                (domain::JobStatus::Failed, Some(error)) => format!("{}: {}", job.status, error),
+                (domain::JobStatus::Completed, _) if let Some(result) = refetch_result(job) => {
+                    format!("{}: {}", job.status, result.summary())
+                }
+                _ => job.status.to_string(),
+            });
+        Self {
+            status: job.status.to_string(),
+            progress: job.progress.filter(|_| done.is_none()),
+            done,
+        }
+    }
+}
+
+/// Changes made by a completed refetch job, `None` for other jobs
+fn refetch_result(job: &domain::Job) -> Option<RefetchPlaylistResult> {
+    if job.job_type() != Some(domain::JobType::RefetchPlaylist) {
+        return None;
+    }
+    serde_json::from_value(job.result.clone()?).ok()
+}
+
+/// Polls the user's job every 200 ms, shared by the SSE and WebSocket status endpoints
+pub(crate) fn poll_job<PlaylistService>(
+    playlist_service: Arc<PlaylistService>,
+    user: domain::UserContext,
+    job_id: domain::JobId,
+) -> impl Stream<Item = anyhow::Result<Option<domain::Job>>>
+where
+    PlaylistService: IPlaylistService,
+{
+    tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(POLL_INTERVAL)).then(
+        move |_| {
+            let job_id = job_id.clone();
+            let user = user.clone();
+            let playlist_service = playlist_service.clone();
+            async move { playlist_service.get_job_by_id(&user, &job_id).await }
+        },
+    )
+}
+
+/// Like [`poll_job`] for a job of the playlist, loading only the playlist's jobs created
+/// since `job` rather than looking it up by ID
+pub(crate) fn poll_playlist_job<PlaylistService>(
+    playlist_service: Arc<PlaylistService>,
+    user: domain::UserContext,
+    playlist_id: domain::PlaylistId,
+    job: domain::Job,
+) -> impl Stream<Item = anyhow::Result<Option<domain::Job>>>
+where
+    PlaylistService: IPlaylistService,
+{
+    tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(POLL_INTERVAL)).then(
+        move |_| {
+            let job = job.clone();
+            let user = user.clone();
+            let playlist_id = playlist_id.clone();
+            let playlist_service = playlist_service.clone();
+            async move {
+                let jobs = playlist_service
+                    .get_jobs_for_playlist_since(&user, &playlist_id, job.created_at)
+                    .await?;
+                Ok(jobs.into_iter().find(|polled| polled.id == job.id))
+            }
+        },
+    )
+}
+
+/// Jobs enqueued by the caller's session, newest first
+pub async fn list_jobs<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Query(query): Query<ListJobsQuery>,
+) -> Result<Json<JobsPageResponse>, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let pagination = query.pagination()?;
+    let filter = JobFilter {
+        session_id: Some(user.session_id),
+        ..query.filter()
+    };
+
+    let page = services
+        .playlist_service
+        .list_jobs(&filter, pagination)
+        .await?;
+
+    Ok(Json(JobsPageResponse {
+        jobs: page
+            .jobs
+            .into_iter()
+            .map(JobSummaryResponse::from)
+            .collect(),
+        page: pagination.page,
+        per_page: pagination.per_page,
+        total: page.total,
+    }))
+}
+
+/// Job counts by status for monitoring the queue depth, with the number of stored playlists and tracks
+pub async fn job_stats<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+) -> Result<Json<StatsResponse>, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let (jobs, library) = tokio::try_join!(
+        services.playlist_service.job_stats(),
+        services.playlist_service.library_stats()
+    )?;
+    Ok(Json(StatsResponse::new(jobs, library)))
+}
+
+/// Job history of a playlist, newest first
+pub async fn list_playlist_jobs<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+) -> Result<Json<Vec<JobSummaryResponse>>, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+
+    let jobs = services
+        .playlist_service
+        .get_jobs_for_playlist(&user, &playlist_id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(
+        jobs.into_iter().map(JobSummaryResponse::from).collect(),
+    ))
+}
+
+/// Cancels a job of the playlist that has not finished yet
+pub async fn cancel_playlist_job<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path((playlist_id, job_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    let job_id: domain::JobId = job_id
+        .parse()
+        .map_err(|_| ApiError::ValidationError("Invalid job ID".to_string()))?;
+
+    // Only jobs of the user's own playlists can be cancelled
+    let jobs = services
+        .playlist_service
+        .get_jobs_for_playlist(&user, &playlist_id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    if !jobs.iter().any(|job| job.id == job_id) {
+        return Err(ApiError::NotFound);
+    }
+
+    if !services.playlist_service.cancel_job(&user, &job_id).await? {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Sends [`JobStatusMessage`]s of the user's job over a WebSocket, counted toward the
+/// same [`SseLimits`] as the SSE streams
+pub async fn job_status_ws<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(job_id): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let job_id: domain::JobId = job_id
+        .parse()
+        .map_err(|_| ApiError::ValidationError("Invalid job ID".to_string()))?;
+
+    let connection = services.sse_limits.acquire()?;
+    let idle_timeout = services.sse_limits.idle_timeout;
+    let updates = poll_job(services.playlist_service.clone(), user, job_id);
+    Ok(ws.on_upgrade(move |socket| async move {
+        let _connection = connection;
+        send_job_updates(socket, updates, idle_timeout).await;
+    }))
+}
+
+/// Pushes the job status to the socket until the job reaches a terminal state, or the
+/// client has not taken a message for `idle_timeout`
+async fn send_job_updates(
+    mut socket: WebSocket,
+    updates: impl Stream<Item = anyhow::Result<Option<domain::Job>>>,
+    idle_timeout: Duration,
+) {
+    let mut updates = std::pin::pin!(updates);
+
+    while let Some(update) = updates.next().await {
+        let job = match update {
+            Ok(Some(job)) => job,
+            Ok(None) => {
+                tracing::info!("Job not found, closing status socket");
+                break;
+            }
+            Err(e) => {
+                tracing::error!("Failed to poll job status: {}", e);
+                break;
+            }
+        };
+
+        let message = JobStatusMessage::from(&job);
+        let Ok(json) = serde_json::to_string(&message) else {
+            break;
+        };
+        match tokio::time::timeout(idle_timeout, socket.send(Message::text(json))).await {
+            Ok(Ok(())) => {}
+            // The client went away
+            Ok(Err(_)) => return,
+            Err(_) => {
+                tracing::info!(
+                    "Job status client idle for {:?}, closing socket",
+                    idle_timeout
+                );
+                return;
+            }
+        }
+
+        if message.done.is_some() {
+            break;
+        }
+    }
+
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::infrastructure::spotify::MockSpotifyClient;
+    use axum::Router;
+    use axum::routing::get;
+
+    async fn wait_for_no_connections(limits: &SseLimits, within: Duration) {
+        let deadline = tokio::time::Instant::now() + within;
+        while limits.active_connections() > 0 {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "stream still running after {within:?}"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forward_events_stops_when_client_drops() {
+        let limits = SseLimits::new(10, Duration::from_secs(60));
+        let receiver =
+            forward_events(&limits, tokio_stream::iter(0..).throttle(POLL_INTERVAL)).unwrap();
+        assert_eq!(limits.active_connections(), 1);
+
+        drop(receiver);
+
+        wait_for_no_connections(&limits, Duration::from_secs(1)).await;
+    }
+
+    #[tokio::test]
+    async fn test_forward_events_stops_when_client_is_idle() {
+        let limits = SseLimits::new(10, Duration::from_millis(50));
+        let _receiver = forward_events(&limits, tokio_stream::iter(0..)).unwrap();
+
+        wait_for_no_connections(&limits, Duration::from_secs(1)).await;
+    }
+
+    #[tokio::test]
+    async fn test_forward_events_rejects_connections_over_limit() {
+        let limits = SseLimits::new(1, Duration::from_secs(60));
+        let _first = forward_events(&limits, tokio_stream::pending::<()>()).unwrap();
+
+        let second = forward_events(&limits, tokio_stream::pending::<()>());
+        assert!(matches!(second, Err(ApiError::ServiceUnavailable(_))));
+        assert_eq!(limits.active_connections(), 1);
+    }
+
+    fn job(status: domain::JobStatus) -> domain::Job {
+        let mut job = domain::Job::new(serde_json::json!({}));
+        job.status = status;
+        job
+    }
+
+    #[test]
+    fn test_done_message_of_a_failed_job_says_why() {
+        let failed = domain::Job {
+            error_message: Some("while fetching the playlist: timed out".to_string()),
+            ..job(domain::JobStatus::Failed)
+        };
+
+        let message = JobStatusMessage::from(&failed);
+        assert_eq!(message.status, "failed");
+        assert_eq!(
+            message.done.as_deref(),
+            Some("failed: while fetching the playlist: timed out")
+        );
+
+        let message = JobStatusMessage::from(&job(domain::JobStatus::Failed));
+        assert_eq!(message.done.as_deref(), Some("failed"));
+    }
+
+    #[tokio::test]
+    async fn test_ws_sends_updates_until_completed() {
+        let app = Router::new().route(
+            "/ws",
+            get(|ws: WebSocketUpgrade| async move {
+                let processing = domain::Job {
+                    progress: Some(domain::JobProgress { done: 1, total: 2 }),
+                    ..job(domain::JobStatus::Processing)
+                };
+                let updates = tokio_stream::iter(vec![
+                    Ok(Some(job(domain::JobStatus::Pending))),
+                    Ok(Some(processing)),
+                    Ok(Some(job(domain::JobStatus::Completed))),
+                    // Never sent, the socket closes once the job is completed
+                    Ok(Some(job(domain::JobStatus::Processing))),
+                ]);
+                ws.on_upgrade(move |socket| {
+                    send_job_updates(socket, updates, Duration::from_secs(60))
+                })
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+            .await
+            .unwrap();
+
+        let mut messages = vec![];
+        while let Some(message) = client.next().await {
+            match message.unwrap() {
+                tokio_tungstenite::tungstenite::Message::Text(text) => {
+                    messages.push(serde_json::from_str::<serde_json::Value>(&text).unwrap());
+                }
+                tokio_tungstenite::tungstenite::Message::Close(_) => break,
+                _ => {}
+            }
+        }
+        let _ = client.close(None).await;
+
+        let statuses: Vec<_> = messages.iter().map(|m| m["status"].clone()).collect();
+        assert_eq!(statuses, ["pending", "processing", "completed"]);
+        assert_eq!(
+            messages[1]["progress"],
+            serde_json::json!({"done": 1, "total": 2})
+        );
+        assert_eq!(messages[1]["done"], serde_json::Value::Null);
+        assert_eq!(messages[2]["done"], "completed");
+    }
+
+    #[tokio::test]
+    async fn test_ws_counts_toward_the_stream_limit() {
+        let (service, _) = fixtures::playlist_service(MockSpotifyClient::new()).await;
+        let mut services = fixtures::services(service);
+        services.sse_limits = SseLimits::new(0, Duration::from_secs(60));
+        let app = Router::new()
+            .route("/ws/jobs/{job_id}/status", get(job_status_ws))
+            .with_state(services)
+            .layer(axum::Extension(domain::UserContext::new(
+                domain::SessionId::new(),
+            )));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let err = tokio_tungstenite::connect_async(format!(
+            "ws://{addr}/ws/jobs/{}/status",
+            domain::JobId::new()
+        ))
+        .await
+        .unwrap_err();
+        let tokio_tungstenite::tungstenite::Error::Http(response) = err else {
+            panic!("expected an HTTP error, got {err}");
+        };
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
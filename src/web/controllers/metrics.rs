@@ -0,0 +1,20 @@
+use crate::application::playlist_service::IPlaylistService;
+use crate::web::error::ApiError;
+use crate::web::server::Services;
+use axum::extract::State;
+use axum::http::HeaderValue;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+
+pub async fn metrics<PlaylistService: IPlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let pending_jobs = services.playlist_service.pending_job_count().await?;
+    Ok((
+        [(
+            CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; version=0.0.4"),
+        )],
+        crate::metrics::render() + &crate::metrics::JOBS_PENDING_TOTAL.render(pending_jobs),
+    ))
+}
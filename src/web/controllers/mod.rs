@@ -1,3 +1,8 @@
 //! Web controllers for handling HTTP requests
+pub mod admin;
+pub mod export;
+pub mod health;
+pub mod jobs;
+pub mod metrics;
 pub mod playlist;
 pub mod view;
@@ -1,4 +1,6 @@
 use crate::application::playlist_service::IPlaylistService;
+use crate::application::{Pagination, PlaylistOrder, SpotifyPlaylistSummary};
+use crate::web::controllers::{jobs, view};
 use crate::web::error::ApiError;
 use crate::web::extensions::HtmxExtension;
 use crate::web::server::Services;
@@ -6,22 +8,26 @@ use crate::{PlaylistTemplate, domain};
 use anyhow::anyhow;
 use askama::Template;
 use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
-use axum::http::{HeaderMap, HeaderValue};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
 use axum::response::sse::{Event, KeepAlive};
 use axum::response::{IntoResponse, Response, Sse};
 use axum::{
     Form,
-    extract::{Path, State},
+    extract::{FromRequest, Path, Query, Request, State},
     response::{Html, Json, Redirect},
 };
 use futures_util::{self, Stream};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
 use tokio_stream::StreamExt;
 use uuid::Uuid;
 
 const MAX_PLAYLIST_ID_LENGTH: usize = 200;
 const MIN_PLAYLIST_ID_LENGTH: usize = 16; // Spotify IDs are typically 22 characters
+const DEFAULT_PLAYLISTS_PER_PAGE: u32 = 20;
+const MAX_PLAYLISTS_PER_PAGE: u32 = 100;
+const DEFAULT_SEARCH_RESULTS: u32 = 10;
+/// Spotify returns at most 50 results per search
+const MAX_SEARCH_RESULTS: u32 = 50;
 
 pub struct PlaylistController {}
 
@@ -31,20 +37,20 @@ pub struct CreatePlaylistForm {
     playlist_id: String,
 }
 
+#[derive(Deserialize)]
+pub struct CreateAlbumPlaylistForm {
+    #[serde(rename = "id")]
+    album_id: String,
+}
+
 #[derive(Serialize)]
 pub struct JobResponse {
     job_id: Uuid,
 }
 
-pub async fn create_playlist<PlaylistService>(
-    headers: HeaderMap,
-    State(services): State<Services<PlaylistService>>,
-    Form(form): Form<CreatePlaylistForm>,
-) -> Result<impl IntoResponse, ApiError>
-where
-    PlaylistService: IPlaylistService,
-{
-    let input = form.playlist_id.trim();
+/// Trims user supplied Spotify URL/ID input and rejects implausible lengths
+fn check_spotify_input_length(input: &str) -> Result<&str, ApiError> {
+    let input = input.trim();
     if input.len() > MAX_PLAYLIST_ID_LENGTH {
         return Err(ApiError::ValidationError(
             "Spotify URL/ID is too long".to_string(),
@@ -57,15 +63,232 @@ where
         ));
     }
 
+    Ok(input)
+}
+
+/// Validates user supplied Spotify URL/ID input and parses it into a `SpotifyId`
+fn parse_spotify_id_input(input: &str) -> Result<domain::SpotifyId, ApiError> {
+    let input = check_spotify_input_length(input)?;
+
     // Parse the Spotify ID (this will do additional format validation)
-    let spotify_id = domain::SpotifyId::parse(input).map_err(|e| {
-        ApiError::ValidationError(format!("Invalid Spotify playlist format: {}", e))
-    })?;
+    domain::SpotifyId::parse(input)
+        .map_err(|e| ApiError::ValidationError(format!("Invalid Spotify playlist format: {}", e)))
+}
+
+/// Validates user supplied Spotify album URL/ID input and parses it into a `SpotifyId`
+fn parse_album_id_input(input: &str) -> Result<domain::SpotifyId, ApiError> {
+    let input = check_spotify_input_length(input)?;
+
+    domain::SpotifyId::parse_album(input)
+        .map_err(|e| ApiError::ValidationError(format!("Invalid Spotify album format: {}", e)))
+}
+
+#[derive(Deserialize)]
+pub struct UpdatePlaylistRequest {
+    /// New local display name, `None` keeps the current name
+    #[serde(default)]
+    name: Option<String>,
+    /// `None` when omitted, `Some(None)` when explicitly cleared with `null`
+    #[serde(default, deserialize_with = "deserialize_present")]
+    back_text: Option<Option<String>>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTrackRequest {
+    /// `None` when omitted, `Some(None)` when explicitly cleared with `null`
+    #[serde(default, deserialize_with = "deserialize_present")]
+    notes: Option<Option<String>>,
+}
+
+fn deserialize_present<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+pub struct SortRequest {
+    sort_order: domain::SortOrder,
+}
+
+#[derive(Deserialize)]
+pub struct TagRequest {
+    tag: String,
+}
+
+#[derive(Serialize)]
+pub struct TagsResponse {
+    tags: Vec<domain::Tag>,
+}
+
+#[derive(Deserialize)]
+pub struct ListPlaylistsQuery {
+    tag: Option<String>,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    /// Cursor, the ID of the last playlist of the previous request
+    after: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct SearchPlaylistsQuery {
+    q: String,
+    limit: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct FindPlaylistsQuery {
+    name: String,
+}
+
+#[derive(Serialize)]
+pub struct SpotifyPlaylistSummaryResponse {
+    id: domain::SpotifyId,
+    name: String,
+    owner: String,
+    track_count: u32,
+    image_url: Option<String>,
+}
+
+impl From<SpotifyPlaylistSummary> for SpotifyPlaylistSummaryResponse {
+    fn from(playlist: SpotifyPlaylistSummary) -> Self {
+        Self {
+            id: playlist.id,
+            name: playlist.name,
+            owner: playlist.owner,
+            track_count: playlist.track_count,
+            image_url: playlist.image_url,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PlaylistSummaryResponse {
+    id: Uuid,
+    name: String,
+    track_count: usize,
+    spotify_id: Option<domain::SpotifyId>,
+    spotify_url: Option<domain::SpotifyIdAsUrl>,
+}
+
+impl From<domain::Playlist> for PlaylistSummaryResponse {
+    fn from(playlist: domain::Playlist) -> Self {
+        Self {
+            track_count: playlist.track_count(),
+            name: playlist.display_name().to_string(),
+            id: playlist.id.into(),
+            spotify_url: playlist
+                .spotify_id
+                .clone()
+                .map(domain::SpotifyIdAsUrl::from),
+            spotify_id: playlist.spotify_id,
+        }
+    }
+}
+
+/// Full playlist with its tracks, for JSON clients of `GET /playlist/{id}`
+#[derive(Serialize)]
+pub struct PlaylistResponse {
+    id: Uuid,
+    name: String,
+    track_count: usize,
+    spotify_id: Option<domain::SpotifyId>,
+    spotify_url: Option<domain::SpotifyIdAsUrl>,
+    /// When the playlist was stored, always set for playlists loaded from the repository
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Last change, including refetches from Spotify, `None` if never changed since it was created
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    tracks: Vec<TrackResponse>,
+}
+
+impl From<domain::Playlist> for PlaylistResponse {
+    fn from(playlist: domain::Playlist) -> Self {
+        Self {
+            track_count: playlist.track_count(),
+            name: playlist.display_name().to_string(),
+            id: playlist.id.into(),
+            spotify_url: playlist
+                .spotify_id
+                .clone()
+                .map(domain::SpotifyIdAsUrl::from),
+            spotify_id: playlist.spotify_id,
+            created_at: playlist.created_at,
+            updated_at: playlist.updated_at,
+            tracks: playlist
+                .tracks
+                .into_iter()
+                .map(TrackResponse::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct TrackResponse {
+    title: String,
+    artist: String,
+    year: i32,
+    spotify_url: String,
+    album_cover_url: Option<String>,
+    duration_ms: Option<u32>,
+    notes: Option<String>,
+}
+
+impl From<domain::Track> for TrackResponse {
+    fn from(track: domain::Track) -> Self {
+        Self {
+            title: track.title,
+            artist: track.artist,
+            year: track.year,
+            spotify_url: track.spotify_url,
+            album_cover_url: track.album_cover_url,
+            duration_ms: track.duration_ms,
+            notes: track.notes,
+        }
+    }
+}
+
+/// Cursor paginated envelope for playlist listings
+#[derive(Serialize)]
+pub struct PlaylistsCursorResponse {
+    playlists: Vec<PlaylistSummaryResponse>,
+    /// Pass as `after` to get the next playlists, `None` on the last page
+    next_after: Option<Uuid>,
+}
+
+/// Paginated envelope for playlist listings
+#[derive(Serialize)]
+pub struct PlaylistsPageResponse {
+    playlists: Vec<PlaylistSummaryResponse>,
+    page: u32,
+    per_page: u32,
+    total: u64,
+}
+
+#[derive(Serialize)]
+pub struct MissingYearTrackResponse {
+    position: usize,
+    title: String,
+    artist: String,
+}
+
+pub async fn create_playlist<PlaylistService>(
+    headers: HeaderMap,
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Form(form): Form<CreatePlaylistForm>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let spotify_id = parse_spotify_id_input(&form.playlist_id)?;
 
     if headers.is_htmx_request() {
         let (playlist, job) = services
             .playlist_service
-            .create_partial_playlist_from_spotify(&spotify_id)
+            .create_partial_playlist_from_spotify(&user, &spotify_id)
             .await?;
         return match (playlist, job) {
             (Some(playlist), None) => {
@@ -76,20 +299,31 @@ where
                 let location = format!("/playlist/{}", &playlist.id);
 
                 let template = PlaylistTemplate {
-                    title: playlist.name.clone(),
+                    title: playlist.display_name().to_string(),
                     total_tracks: playlist.tracks.len(),
                     tracks: vec![],
+                    next_page: None,
                     playlist_id: playlist.id.to_string(),
-                    latest_job: Some(job.into()),
+                    latest_pdf_job: None,
+                    latest_refetch_job: Some(job.into()),
                     has_generated_pdfs: false,
+                    prefer_websocket: services.prefer_websocket,
+                    missing_year_count: 0,
+                    completeness_score: playlist.completeness_score(),
+                    tags: vec![],
                 };
-                let mut headers = HeaderMap::new();
-                headers.insert("HX-Replace-Url", HeaderValue::from_str(&location).unwrap());
+                let mut response_headers = HeaderMap::new();
+                let url_header = if is_form_page_or(&headers, &location) {
+                    "HX-Replace-Url"
+                } else {
+                    "HX-Redirect"
+                };
+                response_headers.insert(url_header, HeaderValue::from_str(&location).unwrap());
 
                 let html = template
                     .render()
                     .map_err(|_| anyhow!("Failed to render playlist template"))?;
-                Ok((headers, Html(html)).into_response())
+                Ok((response_headers, Html(html)).into_response())
             }
             (None, _) => Err(ApiError::NotFound),
         };
@@ -97,7 +331,7 @@ where
 
     let playlist = services
         .playlist_service
-        .create_from_spotify(&spotify_id)
+        .create_from_spotify(&user, &spotify_id)
         .await?;
 
     if let Some(playlist) = playlist {
@@ -108,18 +342,87 @@ where
     }
 }
 
+/// Whether the HTMX request came from the import form or from `location` itself.
+///
+/// Replacing the URL of any other page, e.g. when the user navigated away before the
+/// response arrived, would leave it showing content that does not match its URL.
+fn is_form_page_or(headers: &HeaderMap, location: &str) -> bool {
+    let Some(current_url) = headers.current_url() else {
+        return true;
+    };
+    axum::http::Uri::try_from(current_url)
+        .map(|uri| uri.path() == "/" || uri.path() == location)
+        .unwrap_or(false)
+}
+
+pub async fn create_playlist_from_album<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Form(form): Form<CreateAlbumPlaylistForm>,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let album_id = parse_album_id_input(&form.album_id)?;
+
+    let playlist = services
+        .playlist_service
+        .create_from_spotify_album(&user, &album_id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    let location = format!("/playlist/{}", playlist.id);
+    Ok(Redirect::to(&location).into_response())
+}
+
+pub async fn get_playlist_by_spotify_id<PlaylistService>(
+    headers: HeaderMap,
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(spotify_id): Path<String>,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let spotify_id = parse_spotify_id_input(&spotify_id)?;
+
+    let playlist = services
+        .playlist_service
+        .get_by_spotify_id(&user, &spotify_id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    if headers.is_htmx_request() {
+        let location = format!("/playlist/{}", &playlist.id);
+        let template = view::playlist_template(&services, &user, playlist)
+            .await
+            .map_err(|e| anyhow!("Failed to build playlist template: {e}"))?;
+        let html = template
+            .render()
+            .map_err(|_| anyhow!("Failed to render playlist template"))?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("HX-Replace-Url", HeaderValue::from_str(&location).unwrap());
+        return Ok((headers, Html(html)).into_response());
+    }
+
+    let location = format!("/playlist/{}", playlist.id);
+    Ok(Redirect::to(&location).into_response())
+}
+
 pub async fn refetch_playlist<PlaylistService>(
     State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
     Path(playlist_id): Path<String>,
     headers: HeaderMap,
 ) -> Result<Response, ApiError>
 where
     PlaylistService: IPlaylistService,
 {
-    let playlist_id: domain::PlaylistId = playlist_id.parse()?;
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
     let job = services
         .playlist_service
-        .refetch_playlist(&playlist_id)
+        .refetch_playlist(&user, &playlist_id)
         .await?;
 
     // If the request is from HTMX reload the current page
@@ -138,16 +441,17 @@ where
 
 pub async fn generate_pdfs<PlaylistService>(
     State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
     Path(playlist_id): Path<String>,
     headers: HeaderMap,
 ) -> Result<Response, ApiError>
 where
     PlaylistService: IPlaylistService,
 {
-    let playlist_id: domain::PlaylistId = playlist_id.parse()?;
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
     let job = services
         .playlist_service
-        .generate_playlist_pdfs(&playlist_id)
+        .generate_playlist_pdfs(&user, &playlist_id)
         .await?;
 
     // If the request is from HTMX reload the current page
@@ -166,12 +470,13 @@ where
 
 pub async fn download_pdf<PlaylistService>(
     State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
     Path((playlist_id, pdf_side)): Path<(String, String)>,
 ) -> Result<Response, ApiError>
 where
     PlaylistService: IPlaylistService,
 {
-    let playlist_id: domain::PlaylistId = playlist_id.parse()?;
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
 
     // Validate PDF type
     if pdf_side != "front" && pdf_side != "back" {
@@ -183,7 +488,7 @@ where
     // Get the PDFs from the service
     let pdfs = services
         .playlist_service
-        .get_playlist_pdfs(&playlist_id)
+        .get_playlist_pdfs(&user, &playlist_id)
         .await?;
 
     let pdf_data = match pdf_side.as_str() {
@@ -207,34 +512,638 @@ where
         .into_response())
 }
 
+pub async fn get_missing_years<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+) -> Result<Json<Vec<MissingYearTrackResponse>>, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    let tracks = services
+        .playlist_service
+        .get_missing_year_tracks(&user, &playlist_id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(
+        tracks
+            .into_iter()
+            .map(|(position, track)| MissingYearTrackResponse {
+                position,
+                title: track.title,
+                artist: track.artist,
+            })
+            .collect(),
+    ))
+}
+
+/// Renames the playlist or changes its back text, answering with the updated playlist
+pub async fn update_playlist<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+    headers: HeaderMap,
+    request: Request,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+
+    // The inline rename form on the playlist page sends form data, API clients send JSON
+    let body = if headers.is_htmx_request() {
+        Form::<UpdatePlaylistRequest>::from_request(request, &())
+            .await
+            .map_err(|e| ApiError::ValidationError(e.body_text()))?
+            .0
+    } else {
+        Json::<UpdatePlaylistRequest>::from_request(request, &())
+            .await
+            .map_err(|e| ApiError::ValidationError(e.body_text()))?
+            .0
+    };
+
+    if let Some(name) = body.name {
+        services
+            .playlist_service
+            .rename_playlist(&user, &playlist_id, &name)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+    }
+    if let Some(back_text) = body.back_text {
+        services
+            .playlist_service
+            .update_back_text(&user, &playlist_id, back_text)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+    }
+
+    // If the request is from HTMX reload the current page
+    if headers.is_htmx_request() {
+        let redirect_to = format!("/playlist/{}", playlist_id);
+        let mut headers = HeaderMap::new();
+        headers.insert("HX-Redirect", HeaderValue::from_str(&redirect_to).unwrap());
+        return Ok((headers, axum::body::Body::empty()).into_response());
+    }
+
+    let playlist = services
+        .playlist_service
+        .get_playlist(&user, &playlist_id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(PlaylistResponse::from(playlist)).into_response())
+}
+
+pub async fn delete_playlist<PlaylistService>(
+    headers: HeaderMap,
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+
+    if !services
+        .playlist_service
+        .delete_playlist(&user, &playlist_id)
+        .await?
+    {
+        return Err(ApiError::NotFound);
+    }
+
+    // The playlist page is gone, send HTMX back to the start page
+    if headers.is_htmx_request() {
+        let mut headers = HeaderMap::new();
+        headers.insert("HX-Redirect", HeaderValue::from_static("/"));
+        return Ok((headers, axum::body::Body::empty()).into_response());
+    }
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+pub async fn update_track<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path((playlist_id, position)): Path<(String, usize)>,
+    Json(body): Json<UpdateTrackRequest>,
+) -> Result<StatusCode, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+
+    if let Some(notes) = body.notes {
+        services
+            .playlist_service
+            .update_track_notes(&user, &playlist_id, position, notes)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn sort_tracks<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+    headers: HeaderMap,
+    request: Request,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+
+    // The sort button on the playlist page posts form data, API clients send JSON
+    let body = if headers.is_htmx_request() {
+        Form::<SortRequest>::from_request(request, &())
+            .await
+            .map_err(|e| ApiError::ValidationError(e.body_text()))?
+            .0
+    } else {
+        Json::<SortRequest>::from_request(request, &())
+            .await
+            .map_err(|e| ApiError::ValidationError(e.body_text()))?
+            .0
+    };
+
+    services
+        .playlist_service
+        .sort_tracks(&user, &playlist_id, body.sort_order)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    // If the request is from HTMX reload the current page
+    if headers.is_htmx_request() {
+        let redirect_to = format!("/playlist/{}", playlist_id);
+        let mut headers = HeaderMap::new();
+        headers.insert("HX-Redirect", HeaderValue::from_str(&redirect_to).unwrap());
+        return Ok((headers, axum::body::Body::empty()).into_response());
+    }
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+pub async fn add_tag<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+    headers: HeaderMap,
+    request: Request,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+
+    // The inline form on the playlist page posts form data, API clients send JSON
+    let body = if headers.is_htmx_request() {
+        Form::<TagRequest>::from_request(request, &())
+            .await
+            .map_err(|e| ApiError::ValidationError(e.body_text()))?
+            .0
+    } else {
+        Json::<TagRequest>::from_request(request, &())
+            .await
+            .map_err(|e| ApiError::ValidationError(e.body_text()))?
+            .0
+    };
+    let tag = domain::Tag::parse(&body.tag)?;
+
+    if services
+        .playlist_service
+        .get_playlist(&user, &playlist_id)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::NotFound);
+    }
+
+    let tags = services
+        .playlist_service
+        .add_tag(&user, &playlist_id, &tag)
+        .await?;
+
+    // If the request is from HTMX reload the current page
+    if headers.is_htmx_request() {
+        let redirect_to = format!("/playlist/{}", playlist_id);
+        let mut headers = HeaderMap::new();
+        headers.insert("HX-Redirect", HeaderValue::from_str(&redirect_to).unwrap());
+        return Ok((headers, axum::body::Body::empty()).into_response());
+    }
+
+    Ok(Json(TagsResponse { tags }).into_response())
+}
+
+pub async fn remove_tag<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path((playlist_id, tag)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    let tag = domain::Tag::parse(&tag)?;
+
+    if services
+        .playlist_service
+        .get_playlist(&user, &playlist_id)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::NotFound);
+    }
+
+    let tags = services
+        .playlist_service
+        .remove_tag(&user, &playlist_id, &tag)
+        .await?;
+
+    // If the request is from HTMX reload the current page
+    if headers.is_htmx_request() {
+        let redirect_to = format!("/playlist/{}", playlist_id);
+        let mut headers = HeaderMap::new();
+        headers.insert("HX-Redirect", HeaderValue::from_str(&redirect_to).unwrap());
+        return Ok((headers, axum::body::Body::empty()).into_response());
+    }
+
+    Ok(Json(TagsResponse { tags }).into_response())
+}
+
+pub async fn list_playlists<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Query(query): Query<ListPlaylistsQuery>,
+) -> Result<Response, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    if query.after.is_some() || query.limit.is_some() {
+        if query.tag.is_some() || query.page.is_some() || query.per_page.is_some() {
+            return Err(ApiError::ValidationError(
+                "after and limit cannot be combined with tag, page or per_page".to_string(),
+            ));
+        }
+        let after = query
+            .after
+            .as_deref()
+            .map(domain::PlaylistId::try_from)
+            .transpose()?;
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PLAYLISTS_PER_PAGE)
+            .clamp(1, MAX_PLAYLISTS_PER_PAGE);
+
+        let playlists = services
+            .playlist_service
+            .list_playlists_after(&user, after.as_ref(), limit)
+            .await?;
+        let next_after = match playlists.last() {
+            Some(last) if playlists.len() == limit as usize => Some(Uuid::from(&last.id)),
+            _ => None,
+        };
+        return Ok(Json(PlaylistsCursorResponse {
+            playlists: playlists.into_iter().map(Into::into).collect(),
+            next_after,
+        })
+        .into_response());
+    }
+
+    let pagination = Pagination::new(
+        query.page.unwrap_or(1),
+        query
+            .per_page
+            .unwrap_or(DEFAULT_PLAYLISTS_PER_PAGE)
+            .clamp(1, MAX_PLAYLISTS_PER_PAGE),
+    )?;
+
+    // Without a tag every playlist of the session is listed
+    let page = match query.tag {
+        Some(tag) => {
+            let tag = domain::Tag::parse(&tag)?;
+            services
+                .playlist_service
+                .get_playlists_by_tag(&user, &tag, pagination)
+                .await?
+        }
+        None => {
+            services
+                .playlist_service
+                .list_playlists(&user, pagination, PlaylistOrder::Created)
+                .await?
+        }
+    };
+
+    Ok(Json(PlaylistsPageResponse {
+        playlists: page.playlists.into_iter().map(Into::into).collect(),
+        page: pagination.page,
+        per_page: pagination.per_page,
+        total: page.total,
+    })
+    .into_response())
+}
+
+/// Spotify playlists matching `q`, for picking a playlist to import without its URL
+pub async fn search_playlists<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    Query(query): Query<SearchPlaylistsQuery>,
+) -> Result<Json<Vec<SpotifyPlaylistSummaryResponse>>, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Err(ApiError::ValidationError(
+            "Search query cannot be empty".to_string(),
+        ));
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_RESULTS)
+        .clamp(1, MAX_SEARCH_RESULTS);
+
+    let playlists = services
+        .playlist_service
+        .search_spotify_playlists(q, limit)
+        .await?;
+    Ok(Json(playlists.into_iter().map(Into::into).collect()))
+}
+
+/// Playlists of the session whose name contains `name`
+pub async fn find_playlists_by_name<PlaylistService>(
+    State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Query(query): Query<FindPlaylistsQuery>,
+) -> Result<Json<Vec<PlaylistSummaryResponse>>, ApiError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let name = query.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::ValidationError(
+            "Playlist name cannot be empty".to_string(),
+        ));
+    }
+
+    let playlists = services
+        .playlist_service
+        .search_playlists_by_name(&user, name)
+        .await?;
+    Ok(Json(playlists.into_iter().map(Into::into).collect()))
+}
+
+/// Data of `status` events, swapped into the page as is.
+///
+/// `data-elapsed-ms` holds the milliseconds since the job was queued, for scripts
+/// that show a finer timer than the seconds in the text.
+fn job_status_html(job: &domain::Job, now: chrono::DateTime<chrono::Utc>) -> String {
+    let elapsed = now - job.created_at;
+    format!(
+        r#"<span data-elapsed-ms="{}">{} ({}s)</span>"#,
+        elapsed.num_milliseconds(),
+        job.status,
+        elapsed.num_seconds()
+    )
+}
+
+/// Sends [`jobs::JobStatusMessage`]s of the user's job as `status` events with HTML to swap
+/// in, `progress` events with JSON and a final `done` event
 pub async fn get_job_status<PlaylistService>(
     State(services): State<Services<PlaylistService>>,
+    user: domain::UserContext,
     Path((playlist_id, job_id)): Path<(String, String)>,
-) -> Sse<impl Stream<Item = Result<Event, ApiError>>>
+) -> Result<Sse<impl Stream<Item = Result<Event, ApiError>>>, ApiError>
 where
     PlaylistService: IPlaylistService + Send + Sync + 'static,
 {
-    let _playlist_id: domain::PlaylistId = playlist_id.parse().unwrap();
-    let job_id: domain::JobId = job_id.parse().unwrap();
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    let job_id: domain::JobId = job_id
+        .parse()
+        .map_err(|_| ApiError::ValidationError("Invalid job ID".to_string()))?;
+    let job = services
+        .playlist_service
+        .get_job_by_id(&user, &job_id)
+        .await?
+        .filter(|job| job.playlist_id().as_ref() == Some(&playlist_id))
+        .ok_or(ApiError::NotFound)?;
 
-    let stream = tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(
-        Duration::from_millis(200),
-    ))
-    .then(move |_| {
-        let job_id = job_id.clone();
-        let playlist_service = services.playlist_service.clone();
-        async move {
-            let job = playlist_service.get_job_by_id(&job_id).await?;
-
-            match job {
-                Some(ref j) if j.status == domain::JobStatus::Completed => {
-                    Ok(Event::default().event("done").data(j.status.to_string()))
+    let events = jobs::poll_playlist_job(services.playlist_service.clone(), user, playlist_id, job)
+        .then(|job| {
+            async move {
+                let job = job?.ok_or(ApiError::NotFound)?;
+                let message = jobs::JobStatusMessage::from(&job);
+                if let Some(done) = message.done {
+                    return Ok(vec![Event::default().event("done").data(done)]);
+                }
+
+                let mut events = vec![
+                    Event::default()
+                        .event("status")
+                        .data(job_status_html(&job, chrono::Utc::now())),
+                ];
+                // Refetches store how many tracks they fetched while they run
+                if let Some(progress) = message.progress {
+                    let event = Event::default()
+                        .event("progress")
+                        .json_data(progress)
+                        .map_err(|e| ApiError::Internal(anyhow!(e)))?;
+                    events.push(event);
                 }
-                Some(ref j) => Ok(Event::default().event("status").data(j.status.to_string())),
-                None => Err(ApiError::NotFound),
+                Ok::<_, ApiError>(events)
             }
-        }
+        });
+    let events = futures_util::StreamExt::flat_map(events, |events| {
+        futures_util::stream::iter(match events {
+            Ok(events) => events.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        })
     });
+    let stream = jobs::forward_events(&services.sse_limits, events)?;
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
 
-    Sse::new(stream).keep_alive(KeepAlive::default())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{IJobsRepository, IPlaylistRepository};
+    use crate::fixtures;
+    use crate::infrastructure::spotify::MockSpotifyClient;
+    use axum::body::Body;
+
+    async fn stored_playlist() -> (
+        Services<fixtures::TestPlaylistService>,
+        domain::UserContext,
+        domain::PlaylistId,
+    ) {
+        let (service, playlist_repository) =
+            fixtures::playlist_service(MockSpotifyClient::new()).await;
+        let user = domain::UserContext::new(domain::SessionId::new());
+        let playlist = fixtures::playlist("Liked Songs", vec![]);
+        playlist_repository
+            .create(&user.session_id, &playlist)
+            .await
+            .unwrap();
+        (fixtures::services(service), user, playlist.id)
+    }
+
+    #[tokio::test]
+    async fn test_get_playlist_by_spotify_id_redirects_to_the_playlist() {
+        let (service, playlist_repository) =
+            fixtures::playlist_service(MockSpotifyClient::new()).await;
+        let services = fixtures::services(service);
+        let user = domain::UserContext::new(domain::SessionId::new());
+        let playlist = domain::Playlist {
+            spotify_id: Some(domain::SpotifyId::parse("37i9dQZF1DXcBWIGoYBM5M").unwrap()),
+            ..fixtures::playlist("Liked Songs", vec![])
+        };
+        playlist_repository
+            .create(&user.session_id, &playlist)
+            .await
+            .unwrap();
+        let location = format!("/playlist/{}", playlist.id);
+
+        let response = get_playlist_by_spotify_id(
+            HeaderMap::new(),
+            State(services.clone()),
+            user.clone(),
+            Path("37i9dQZF1DXcBWIGoYBM5M".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers()[axum::http::header::LOCATION],
+            location.as_str()
+        );
+
+        let mut htmx = HeaderMap::new();
+        htmx.insert("HX-Request", HeaderValue::from_static("true"));
+        let response = get_playlist_by_spotify_id(
+            htmx,
+            State(services.clone()),
+            user.clone(),
+            Path("37i9dQZF1DXcBWIGoYBM5M".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["HX-Replace-Url"], location.as_str());
+
+        let unknown = get_playlist_by_spotify_id(
+            HeaderMap::new(),
+            State(services),
+            user,
+            Path("5ABHKGoOzxkaa28ttQV9sE".to_string()),
+        )
+        .await;
+        assert!(matches!(unknown, Err(ApiError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_job_status_of_a_job_of_another_playlist_is_not_found() {
+        let (service, _, jobs_repository) =
+            fixtures::sqlite_playlist_service(MockSpotifyClient::new()).await;
+        let services = fixtures::services(service);
+        let user = domain::UserContext::new(domain::SessionId::new());
+        let job = jobs_repository
+            .create(domain::Job::new(serde_json::json!({
+                "type": domain::JobType::RefetchPlaylist,
+                "playlist_id": domain::PlaylistId::new(),
+                "session_id": user.session_id,
+            })))
+            .await
+            .unwrap();
+
+        let result = get_job_status(
+            State(services),
+            user,
+            Path((domain::PlaylistId::new().to_string(), job.id.to_string())),
+        )
+        .await;
+
+        assert!(matches!(result, Err(ApiError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_missing_years_of_an_unknown_playlist_is_not_found() {
+        let (services, user, _) = stored_playlist().await;
+
+        let result = get_missing_years(
+            State(services),
+            user,
+            Path(domain::PlaylistId::new().to_string()),
+        )
+        .await;
+
+        assert!(matches!(result, Err(ApiError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_update_playlist_answers_json_clients_with_the_playlist() {
+        let (services, user, playlist_id) = stored_playlist().await;
+        let request = Request::builder()
+            .method("PATCH")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"name":"Party Mix"}"#))
+            .unwrap();
+
+        let response = update_playlist(
+            State(services.clone()),
+            user.clone(),
+            Path(playlist_id.to_string()),
+            request.headers().clone(),
+            request,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["name"], "Party Mix");
+    }
+
+    #[tokio::test]
+    async fn test_update_playlist_redirects_htmx_forms_to_the_playlist() {
+        let (services, user, playlist_id) = stored_playlist().await;
+        let request = Request::builder()
+            .method("PATCH")
+            .header("HX-Request", "true")
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from("name=Party+Mix"))
+            .unwrap();
+
+        let response = update_playlist(
+            State(services.clone()),
+            user.clone(),
+            Path(playlist_id.to_string()),
+            request.headers().clone(),
+            request,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["HX-Redirect"],
+            format!("/playlist/{playlist_id}").as_str()
+        );
+        let playlist = services
+            .playlist_service
+            .get_playlist(&user, &playlist_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(playlist.display_name(), "Party Mix");
+    }
 }
@@ -1,31 +1,252 @@
 use crate::application::playlist_service::IPlaylistService;
+use crate::application::{Pagination, PlaylistOrder};
 use crate::domain;
+use crate::web::controllers::playlist::PlaylistResponse;
 use crate::web::error::TemplateError;
+use crate::web::extensions::{AcceptExtension, ConditionalExtension, HtmxExtension};
 use crate::web::server::Services;
-use crate::web::templates::playlist::{JobVM, TrackVM};
-use crate::web::templates::{IndexTemplate, PlaylistTemplate};
+use crate::web::templates::index::{PlaylistSummaryVM, RECENT_PLAYLISTS_COUNT};
+use crate::web::templates::playlist::JobVM;
+use crate::web::templates::{
+    IndexTemplate, PlaylistCardsTemplate, PlaylistSearchResultsTemplate, PlaylistTemplate,
+    PrintPreviewTemplate, RecentPlaylistsTemplate,
+};
 use askama::Template;
+use axum::http::header::{ACCEPT, CACHE_CONTROL, CONTENT_TYPE, ETAG, VARY};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::Html,
 };
+use serde::Deserialize;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::num::NonZeroUsize;
+
+pub async fn index<PlaylistService>(
+    State(server): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+) -> Result<Html<String>, TemplateError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let RecentPlaylistsTemplate {
+        recently_created,
+        recently_updated,
+    } = recent_playlists_template(&server, &user).await?;
+    let library_stats = server.playlist_service.library_stats().await?;
 
-pub async fn index() -> Result<Html<String>, TemplateError> {
     let template = IndexTemplate {
         title: "Welcome to Playlist Card Generator".to_string(),
+        recently_created,
+        recently_updated,
+        library_stats: (library_stats.playlists > 0).then(|| library_stats.into()),
     };
     Ok(Html(template.render()?))
 }
 
+/// HTMX partial refreshing the recent playlists of the index page
+pub async fn recent_playlists<PlaylistService>(
+    headers: HeaderMap,
+    State(server): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+) -> Result<Response, TemplateError>
+where
+    PlaylistService: IPlaylistService,
+{
+    if !headers.is_htmx_request() {
+        return Ok(Redirect::to("/").into_response());
+    }
+
+    let template = recent_playlists_template(&server, &user).await?;
+    Ok(Html(template.render()?).into_response())
+}
+
+#[derive(Deserialize)]
+pub struct PlaylistSearchQuery {
+    #[serde(default)]
+    name: String,
+}
+
+/// HTMX partial with the playlists matching the search input of the index page
+pub async fn search_playlists<PlaylistService>(
+    headers: HeaderMap,
+    State(server): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Query(query): Query<PlaylistSearchQuery>,
+) -> Result<Response, TemplateError>
+where
+    PlaylistService: IPlaylistService,
+{
+    if !headers.is_htmx_request() {
+        return Ok(Redirect::to("/").into_response());
+    }
+
+    // Clearing the input clears the results
+    let query = query.name.trim();
+    let playlists = if query.is_empty() {
+        Vec::new()
+    } else {
+        server
+            .playlist_service
+            .search_playlists_by_name(&user, query)
+            .await?
+    };
+
+    let template = PlaylistSearchResultsTemplate {
+        query: query.to_string(),
+        playlists: playlists.iter().map(PlaylistSummaryVM::from).collect(),
+    };
+    Ok(Html(template.render()?).into_response())
+}
+
+async fn recent_playlists_template<PlaylistService>(
+    server: &Services<PlaylistService>,
+    user: &domain::UserContext,
+) -> anyhow::Result<RecentPlaylistsTemplate>
+where
+    PlaylistService: IPlaylistService,
+{
+    let first_page = Pagination {
+        page: 1,
+        per_page: RECENT_PLAYLISTS_COUNT,
+    };
+    let recently_created = server
+        .playlist_service
+        .list_playlists(user, first_page, PlaylistOrder::Created)
+        .await?;
+    let recently_updated = server
+        .playlist_service
+        .list_playlists(user, first_page, PlaylistOrder::Updated)
+        .await?;
+
+    Ok(RecentPlaylistsTemplate {
+        recently_created: recently_created
+            .playlists
+            .iter()
+            .map(PlaylistSummaryVM::from)
+            .collect(),
+        recently_updated: recently_updated
+            .playlists
+            .iter()
+            .map(PlaylistSummaryVM::from)
+            .collect(),
+    })
+}
+
+/// Renders the playlist page, or the playlist as JSON when the `Accept` header asks for it
 pub async fn view_playlist<PlaylistService>(
+    headers: HeaderMap,
     State(server): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+) -> Result<Response, TemplateError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    let playlist = match server
+        .playlist_service
+        .get_playlist(&user, &playlist_id)
+        .await?
+    {
+        None => Err(TemplateError::NotFound(format!(
+            "Playlist with id {} not found",
+            playlist_id
+        )))?,
+        Some(p) => p,
+    };
+
+    if headers.accepts_json() {
+        let body = serde_json::to_vec(&PlaylistResponse::from(playlist))
+            .map_err(|e| TemplateError::ApplicationError(e.into()))?;
+        return Ok(tagged_response(&headers, "application/json", body));
+    }
+
+    let template = playlist_template(&server, &user, playlist).await?;
+
+    Ok(tagged_response(
+        &headers,
+        "text/html; charset=utf-8",
+        template.render()?.into_bytes(),
+    ))
+}
+
+/// Weak ETag of a response body, stable between requests while the body stays the same
+fn weak_etag(body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("W/\"{:016x}\"", hasher.finish())
+}
+
+/// Sends `body` with its ETag, or 304 without it when the client already has that version.
+///
+/// The ETag hashes the rendered body rather than `updated_at`, since the page also shows
+/// tags and jobs that change without touching the playlist. Browsers revalidate every
+/// time, so the page is never stale, but unchanged pages are not downloaded again.
+fn tagged_response(headers: &HeaderMap, content_type: &'static str, body: Vec<u8>) -> Response {
+    let etag = weak_etag(&body);
+    let cache_headers = [
+        (ETAG, etag.clone()),
+        (CACHE_CONTROL, "private, no-cache".to_string()),
+        // The same URL answers with HTML or JSON
+        (VARY, ACCEPT.to_string()),
+    ];
+
+    if headers.if_none_match(&etag) {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+    (cache_headers, [(CONTENT_TYPE, content_type)], body).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct CardsQuery {
+    page: Option<NonZeroUsize>,
+}
+
+/// HTMX partial rendering a single page of track cards for infinite scrolling
+pub async fn playlist_cards<PlaylistService>(
+    State(server): State<Services<PlaylistService>>,
+    user: domain::UserContext,
+    Path(playlist_id): Path<String>,
+    Query(query): Query<CardsQuery>,
+) -> Result<Html<String>, TemplateError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    let playlist = match server
+        .playlist_service
+        .get_playlist(&user, &playlist_id)
+        .await?
+    {
+        None => Err(TemplateError::NotFound(format!(
+            "Playlist with id {} not found",
+            playlist_id
+        )))?,
+        Some(p) => p,
+    };
+
+    let template =
+        PlaylistCardsTemplate::for_page(&playlist, query.page.unwrap_or(NonZeroUsize::MIN))?;
+
+    Ok(Html(template.render()?))
+}
+
+pub async fn print_preview<PlaylistService>(
+    State(server): State<Services<PlaylistService>>,
+    user: domain::UserContext,
     Path(playlist_id): Path<String>,
 ) -> Result<Html<String>, TemplateError>
 where
     PlaylistService: IPlaylistService,
 {
-    let playlist_id: domain::PlaylistId = playlist_id.parse()?;
-    let playlist = match server.playlist_service.get_playlist(&playlist_id).await? {
+    let playlist_id = domain::PlaylistId::try_from(playlist_id.as_str())?;
+    let playlist = match server
+        .playlist_service
+        .get_playlist(&user, &playlist_id)
+        .await?
+    {
         None => Err(TemplateError::NotFound(format!(
             "Playlist with id {} not found",
             playlist_id
@@ -33,56 +254,89 @@ where
         Some(p) => p,
     };
 
+    let template = PrintPreviewTemplate::new(&playlist)?;
+
+    Ok(Html(template.render()?))
+}
+
+/// Builds the playlist page template, including the latest job and PDF availability
+pub(crate) async fn playlist_template<PlaylistService>(
+    server: &Services<PlaylistService>,
+    user: &domain::UserContext,
+    playlist: domain::Playlist,
+) -> Result<PlaylistTemplate, TemplateError>
+where
+    PlaylistService: IPlaylistService,
+{
+    let playlist_id = playlist.id.clone();
     let total_tracks = playlist.tracks.len();
-    let tracks: Vec<TrackVM> = playlist
-        .tracks
-        .iter()
-        .take(20)
-        .map(|track| -> Result<TrackVM, TemplateError> {
-            let code = qrcode::QrCode::new(&track.spotify_url)?;
-            let svg = code
-                .render::<qrcode::render::svg::Color>()
-                .min_dimensions(0, 200)
-                .max_dimensions(200, 200)
-                .build();
-            let svg = svg.replace(
-                r#"crispEdges""#,
-                r#"crispEdges" style="height: 100%; width: 100%""#,
-            );
-
-            Ok(TrackVM {
-                title: track.title.clone(),
-                artist: track.artist.clone(),
-                year: track.year,
-                qr_code: svg,
-                album_cover_url: track.album_cover_url.clone(),
-            })
-        })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let latest_job = server.playlist_service.get_latest_job(&playlist_id).await?;
-    let latest_job = latest_job.map(|job| JobVM {
-        id: job.id.to_string(),
-        is_in_progress: match job.status {
-            domain::JobStatus::Pending | domain::JobStatus::Processing => true,
-            domain::JobStatus::Completed | domain::JobStatus::Failed => false,
-        },
-    });
+    let missing_year_count = playlist.missing_year_count();
+    let completeness_score = playlist.completeness_score();
+    let first_page = PlaylistCardsTemplate::for_page(&playlist, NonZeroUsize::MIN)?;
+
+    let latest_pdf_job = server
+        .playlist_service
+        .get_latest_job_by_type(&playlist_id, domain::JobType::GeneratePlaylistPdfs)
+        .await?
+        .map(JobVM::from);
+    let latest_refetch_job = server
+        .playlist_service
+        .get_latest_job_by_type(&playlist_id, domain::JobType::RefetchPlaylist)
+        .await?
+        .map(JobVM::from);
+
+    let tags = server
+        .playlist_service
+        .get_tags(user, &playlist_id)
+        .await?
+        .into_iter()
+        .map(String::from)
+        .collect();
 
     let has_pdfs = server
         .playlist_service
-        .get_playlist_pdfs(&playlist_id)
+        .get_playlist_pdfs(user, &playlist_id)
         .await
         .ok()
         .is_some();
-    let template = PlaylistTemplate {
-        title: playlist.name.clone(),
+
+    Ok(PlaylistTemplate {
+        title: playlist.display_name().to_string(),
         total_tracks,
-        tracks,
+        tracks: first_page.tracks,
+        next_page: first_page.next_page,
         playlist_id: playlist_id.to_string(),
-        latest_job,
+        latest_pdf_job,
+        latest_refetch_job,
         has_generated_pdfs: has_pdfs,
-    };
+        prefer_websocket: server.prefer_websocket,
+        missing_year_count,
+        completeness_score,
+        tags,
+    })
+}
 
-    Ok(Html(template.render()?))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header::IF_NONE_MATCH;
+
+    #[test]
+    fn test_tagged_response_is_not_modified_for_the_same_body() {
+        let body = b"<html>playlist</html>".to_vec();
+        let response = tagged_response(&HeaderMap::new(), "text/html", body.clone());
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[ETAG].clone();
+        assert!(etag.to_str().unwrap().starts_with("W/\""));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, etag.clone());
+        let response = tagged_response(&headers, "text/html", body);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[ETAG], etag);
+
+        let response = tagged_response(&headers, "text/html", b"<html>changed</html>".to_vec());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers()[ETAG], etag);
+    }
 }
@@ -1,6 +1,13 @@
-use crate::domain::SpotifyIdParserError;
+use crate::application::playlist_service::{JobAlreadyDoneError, PlaylistTooLargeError};
+use crate::application::worker::WorkerFullError;
+use crate::application::{PageOutOfRangeError, SpotifyError};
+use crate::domain::{
+    BackTextError, PlaylistIdError, PlaylistNameError, SpotifyIdParserError, TagError,
+    TrackNotesError,
+};
 use crate::web::templates::ErrorTemplate;
 use askama::Template;
+use axum::http::header::RETRY_AFTER;
 use axum::http::{HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 
@@ -9,18 +16,44 @@ use axum::response::{IntoResponse, Response};
 pub enum TemplateError {
     /// Not found
     NotFound(String),
+    /// Bad request: {0}
+    BadRequest(String),
 
     /// Template rendering error: {0}
     RenderError(#[from] askama::Error),
     ///  error: {0}
-    ApplicationError(#[from] anyhow::Error),
+    ApplicationError(anyhow::Error),
     /// Custom error: {0}
     GenerateQrCode(#[from] qrcode::types::QrError),
 }
 
+impl From<PlaylistIdError> for TemplateError {
+    fn from(err: PlaylistIdError) -> Self {
+        // A malformed ID in a page URL can never match a playlist
+        TemplateError::NotFound(err.to_string())
+    }
+}
+
+impl From<PageOutOfRangeError> for TemplateError {
+    fn from(err: PageOutOfRangeError) -> Self {
+        TemplateError::BadRequest(err.to_string())
+    }
+}
+
+impl From<anyhow::Error> for TemplateError {
+    fn from(err: anyhow::Error) -> Self {
+        if is_validation_error(&err) {
+            return TemplateError::BadRequest(err.to_string());
+        }
+
+        TemplateError::ApplicationError(err)
+    }
+}
+
 impl IntoResponse for TemplateError {
     fn into_response(self) -> Response {
-        let mut details = "Something went wrong. Please try again later.".to_string();
+        // Internal error messages are only logged, users get the request ID to report instead
+        let mut details = String::new();
 
         let status = match self {
             TemplateError::NotFound(message) => {
@@ -28,12 +61,17 @@ impl IntoResponse for TemplateError {
                 details = message;
                 StatusCode::NOT_FOUND
             }
+            TemplateError::BadRequest(message) => {
+                tracing::info!("Bad Request: {}", message);
+                details = message;
+                StatusCode::BAD_REQUEST
+            }
             TemplateError::RenderError(err) => {
                 tracing::error!("Error: {}", err);
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             TemplateError::ApplicationError(err) => {
-                tracing::error!("Error: {}", err);
+                tracing::error!("Error: {:#}", err);
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             TemplateError::GenerateQrCode(err) => {
@@ -42,10 +80,7 @@ impl IntoResponse for TemplateError {
             }
         };
         // Try to render the error template
-        let template = ErrorTemplate {
-            details,
-            status_code: status,
-        };
+        let template = ErrorTemplate::new(status, details);
 
         match template.render() {
             Ok(body) => {
@@ -70,11 +105,47 @@ impl IntoResponse for TemplateError {
 #[derive(Debug, displaydoc::Display, thiserror::Error)]
 pub enum ApiError {
     /// Unmapped error: {0}
-    Internal(#[from] anyhow::Error),
+    Internal(anyhow::Error),
     /// ValidationError: {0}
     ValidationError(String),
     ///  Resource not found
     NotFound,
+    /// Service unavailable: {0}
+    ServiceUnavailable(String),
+    /// Too many jobs are waiting, try again later
+    WorkerFull,
+}
+
+/// Seconds clients are asked to wait before retrying when a worker queue is full
+const WORKER_FULL_RETRY_AFTER_SECONDS: u64 = 10;
+
+/// Domain validation errors raised inside services are client errors
+fn is_validation_error(err: &anyhow::Error) -> bool {
+    err.is::<BackTextError>()
+        || err.is::<TagError>()
+        || err.is::<TrackNotesError>()
+        || err.is::<SpotifyIdParserError>()
+        || err.is::<PlaylistIdError>()
+        || err.is::<PlaylistNameError>()
+        || err.is::<PlaylistTooLargeError>()
+        || err.is::<JobAlreadyDoneError>()
+        || err.is::<PageOutOfRangeError>()
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        if is_validation_error(&err) {
+            return ApiError::ValidationError(err.to_string());
+        }
+        if err.is::<WorkerFullError>() {
+            return ApiError::WorkerFull;
+        }
+        if let Some(rate_limited @ SpotifyError::RateLimited { .. }) = err.downcast_ref() {
+            return ApiError::ServiceUnavailable(rate_limited.to_string());
+        }
+
+        ApiError::Internal(err)
+    }
 }
 
 impl From<SpotifyIdParserError> for ApiError {
@@ -83,11 +154,30 @@ impl From<SpotifyIdParserError> for ApiError {
     }
 }
 
+impl From<PlaylistIdError> for ApiError {
+    fn from(err: PlaylistIdError) -> Self {
+        ApiError::ValidationError(err.to_string())
+    }
+}
+
+impl From<PageOutOfRangeError> for ApiError {
+    fn from(err: PageOutOfRangeError) -> Self {
+        ApiError::ValidationError(err.to_string())
+    }
+}
+
+impl From<TagError> for ApiError {
+    fn from(err: TagError) -> Self {
+        ApiError::ValidationError(err.to_string())
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let status = match &self {
-            ApiError::Internal(_) => {
-                tracing::error!("{}", self);
+            ApiError::Internal(err) => {
+                // Alternate formatting logs the full context chain
+                tracing::error!("Unmapped error: {:#}", err);
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             ApiError::ValidationError(_) => {
@@ -98,6 +188,19 @@ impl IntoResponse for ApiError {
                 tracing::info!("{}", self);
                 StatusCode::NOT_FOUND
             }
+            ApiError::ServiceUnavailable(_) => {
+                tracing::warn!("{}", self);
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            ApiError::WorkerFull => {
+                tracing::warn!("{}", self);
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(RETRY_AFTER, WORKER_FULL_RETRY_AFTER_SECONDS.to_string())],
+                    self.to_string(),
+                )
+                    .into_response();
+            }
         };
 
         (status, self.to_string()).into_response()
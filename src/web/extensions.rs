@@ -1,9 +1,105 @@
 pub trait HtmxExtension {
     fn is_htmx_request(&self) -> bool;
+    /// URL of the page the HTMX request was sent from
+    fn current_url(&self) -> Option<&str>;
 }
 
 impl HtmxExtension for axum::http::HeaderMap {
     fn is_htmx_request(&self) -> bool {
         self.get("hx-request").map(|v| v == "true").unwrap_or(false)
     }
+
+    fn current_url(&self) -> Option<&str> {
+        self.get("hx-current-url").and_then(|v| v.to_str().ok())
+    }
+}
+
+pub trait AcceptExtension {
+    /// Whether the `Accept` header asks for JSON, an absent header means HTML
+    fn accepts_json(&self) -> bool;
+}
+
+impl AcceptExtension for axum::http::HeaderMap {
+    fn accepts_json(&self) -> bool {
+        self.get_all(axum::http::header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|media_type| {
+                media_type
+                    .split(';')
+                    .next()
+                    .is_some_and(|media_type| media_type.trim() == "application/json")
+            })
+    }
+}
+
+pub trait ConditionalExtension {
+    /// Whether `If-None-Match` lists `etag` or `*`, comparing weakly as GET requests do
+    fn if_none_match(&self, etag: &str) -> bool;
+}
+
+impl ConditionalExtension for axum::http::HeaderMap {
+    fn if_none_match(&self, etag: &str) -> bool {
+        let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+        let etag = opaque(etag);
+        self.get_all(axum::http::header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn test_current_url() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(headers.current_url(), None);
+
+        headers.insert(
+            "hx-current-url",
+            HeaderValue::from_static("http://localhost:3000/"),
+        );
+        assert_eq!(headers.current_url(), Some("http://localhost:3000/"));
+    }
+
+    #[test]
+    fn test_accepts_json() {
+        let mut headers = HeaderMap::new();
+        assert!(!headers.accepts_json());
+
+        headers.insert(
+            "accept",
+            HeaderValue::from_static("text/html,application/xhtml+xml,*/*;q=0.8"),
+        );
+        assert!(!headers.accepts_json());
+
+        headers.insert(
+            "accept",
+            HeaderValue::from_static("text/plain, application/json; charset=utf-8"),
+        );
+        assert!(headers.accepts_json());
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let mut headers = HeaderMap::new();
+        assert!(!headers.if_none_match("W/\"abc\""));
+
+        headers.insert(
+            "if-none-match",
+            HeaderValue::from_static("\"xyz\", W/\"abc\""),
+        );
+        assert!(headers.if_none_match("W/\"abc\""));
+        assert!(headers.if_none_match("\"abc\""));
+        assert!(!headers.if_none_match("W/\"ab\""));
+
+        headers.insert("if-none-match", HeaderValue::from_static("*"));
+        assert!(headers.if_none_match("W/\"anything\""));
+    }
 }
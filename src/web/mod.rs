@@ -1,7 +1,11 @@
+pub mod admin_auth;
 pub mod controllers;
 pub mod error;
 pub mod extensions;
+pub mod rate_limit;
+pub mod request_id;
 pub mod server;
+pub mod session;
 pub mod templates;
 
 pub use controllers::*;
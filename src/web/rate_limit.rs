@@ -0,0 +1,128 @@
+//! Per client rate limiting of endpoints that start expensive work
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::StatusCode;
+use axum::http::header::RETRY_AFTER;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultKeyedRateLimiter, Quota};
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Token bucket per client IP, refilled evenly over the minute.
+///
+/// Clones share their buckets, so every worker thread draws from the same ones.
+#[derive(Clone)]
+pub struct RateLimiter {
+    limiter: Arc<DefaultKeyedRateLimiter<IpAddr>>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(requests: NonZeroU32) -> Self {
+        Self {
+            limiter: Arc::new(governor::RateLimiter::keyed(Quota::per_minute(requests))),
+        }
+    }
+
+    /// Takes a token from the bucket of `ip`, or returns how long until the next one is available
+    fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        self.limiter
+            .check_key(&ip)
+            .map_err(|not_until| not_until.wait_time_from(DefaultClock::default().now()))
+    }
+
+    /// Forgets clients whose buckets are full again, so the buckets do not grow without bound
+    pub fn retain_recent(&self) {
+        self.limiter.retain_recent();
+        self.limiter.shrink_to_fit();
+    }
+}
+
+/// Rejects requests with 429 and `Retry-After` while the client's bucket is empty.
+///
+/// Requests without a known client address, such as in tests, are not limited.
+pub async fn limit_by_client_ip(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client_ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    if let Some(ip) = client_ip
+        && let Err(wait) = limiter.check(ip)
+    {
+        tracing::info!("Rate limited {} for {:?}", ip, wait);
+        // Round up, a client retrying early would only be rejected again
+        let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(RETRY_AFTER, retry_after.max(1).to_string())],
+            "Too many requests, try again later",
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::routing::post;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_rate_limiter_keeps_a_bucket_per_client() {
+        let limiter = RateLimiter::per_minute(NonZeroU32::new(2).unwrap());
+        let client: IpAddr = "192.0.2.1".parse().unwrap();
+        let other: IpAddr = "192.0.2.2".parse().unwrap();
+
+        assert!(limiter.check(client).is_ok());
+        assert!(limiter.check(client).is_ok());
+        let wait = limiter.check(client).unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_secs(30));
+
+        assert!(limiter.check(other).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_router_rejects_clients_over_the_limit() {
+        let app = Router::new().route(
+            "/generate",
+            post(|| async { "ok" }).layer(axum::middleware::from_fn_with_state(
+                RateLimiter::per_minute(NonZeroU32::MIN),
+                limit_by_client_ip,
+            )),
+        );
+        let request = |ip: [u8; 4]| {
+            let mut request = Request::post("/generate").body(Body::empty()).unwrap();
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from((ip, 40000))));
+            request
+        };
+
+        let response = app.clone().oneshot(request([192, 0, 2, 1])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.clone().oneshot(request([192, 0, 2, 1])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=60).contains(&retry_after));
+
+        let response = app.oneshot(request([192, 0, 2, 2])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
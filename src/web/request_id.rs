@@ -0,0 +1,73 @@
+//! Per-request IDs that users can quote in bug reports
+
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+use std::fmt::Formatter;
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Identifier of a single HTTP request, attached to its log lines and error pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestId(Uuid);
+
+impl RequestId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+/// ID of the request being handled, `None` outside of [`assign_request_id`]
+pub fn current_request_id() -> Option<RequestId> {
+    CURRENT_REQUEST_ID.try_with(|id| *id).ok()
+}
+
+/// Gives every request a new [`RequestId`], returned in the `X-Request-Id` header
+pub async fn assign_request_id(request: Request, next: Next) -> Response {
+    let request_id = RequestId::new();
+    let span = tracing::info_span!("request", request_id = %request_id);
+
+    let mut response = CURRENT_REQUEST_ID
+        .scope(request_id, next.run(request).instrument(span))
+        .await;
+    response.headers_mut().insert(
+        REQUEST_ID_HEADER,
+        HeaderValue::from_str(&request_id.to_string()).unwrap(),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_current_request_id_is_scoped() {
+        assert_eq!(current_request_id(), None);
+
+        let request_id = RequestId::new();
+        let seen = CURRENT_REQUEST_ID
+            .scope(request_id, async { current_request_id() })
+            .await;
+        assert_eq!(seen, Some(request_id));
+    }
+}
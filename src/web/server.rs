@@ -1,18 +1,26 @@
 use crate::application::playlist_service::IPlaylistService;
-use crate::web::controllers;
+use crate::web::admin_auth::{self, AdminToken};
+use crate::web::controllers::jobs::SseLimits;
+use crate::web::rate_limit::{self, RateLimiter};
+use crate::web::{controllers, request_id, session};
 use axum::{
     Router,
-    routing::{get, post},
+    routing::{delete, get, patch, post},
 };
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::info;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Services<PlaylistService>
 where
     PlaylistService: IPlaylistService,
 {
     pub playlist_service: Arc<PlaylistService>,
+    /// Use WebSockets instead of SSE for job status updates
+    pub prefer_websocket: bool,
+    pub sse_limits: SseLimits,
 }
 
 impl<PlaylistService> Clone for Services<PlaylistService>
@@ -22,6 +30,8 @@ where
     fn clone(&self) -> Self {
         Self {
             playlist_service: self.playlist_service.clone(),
+            prefer_websocket: self.prefer_websocket,
+            sse_limits: self.sse_limits.clone(),
         }
     }
 }
@@ -30,11 +40,19 @@ pub async fn run<PlaylistService>(
     host: &str,
     port: u16,
     playlist_service: Arc<PlaylistService>,
+    prefer_websocket: bool,
+    sse_limits: SseLimits,
+    pdf_rate_limiter: RateLimiter,
+    admin_token: AdminToken,
 ) -> anyhow::Result<()>
 where
     PlaylistService: IPlaylistService + 'static,
 {
-    let services = Services { playlist_service };
+    let services = Services {
+        playlist_service,
+        prefer_websocket,
+        sse_limits,
+    };
 
     let app = Router::new()
         // Playlist API endpoints
@@ -48,7 +66,10 @@ where
         )
         .route(
             "/api/playlist/{playlist_id}/generate-pdfs",
-            post(controllers::playlist::generate_pdfs),
+            post(controllers::playlist::generate_pdfs).layer(axum::middleware::from_fn_with_state(
+                pdf_rate_limiter.clone(),
+                rate_limit::limit_by_client_ip,
+            )),
         )
         .route(
             "/api/playlist/{playlist_id}/download-pdf/{side}",
@@ -58,19 +79,125 @@ where
             "/api/playlist/{playlist_id}/jobs/{job_id}/status",
             get(controllers::playlist::get_job_status),
         )
+        .route(
+            "/ws/jobs/{job_id}/status",
+            get(controllers::jobs::job_status_ws),
+        )
+        .route("/jobs", get(controllers::jobs::list_jobs))
+        .route("/api/stats", get(controllers::jobs::job_stats))
+        .route(
+            "/admin/jobs",
+            get(controllers::admin::list_jobs).layer(axum::middleware::from_fn_with_state(
+                admin_token,
+                admin_auth::require_admin_token,
+            )),
+        )
+        .route("/metrics", get(controllers::metrics::metrics))
+        .route("/health", get(controllers::health::health))
+        .route("/health/ready", get(controllers::health::ready))
         // View endpoints
         .route("/", get(controllers::view::index))
         .route(
             "/playlist/{playlist_id}",
-            get(controllers::view::view_playlist),
+            get(controllers::view::view_playlist)
+                .patch(controllers::playlist::update_playlist)
+                .delete(controllers::playlist::delete_playlist),
+        )
+        .route(
+            "/playlist/{playlist_id}/jobs",
+            get(controllers::jobs::list_playlist_jobs),
+        )
+        .route(
+            "/playlist/{playlist_id}/jobs/{job_id}",
+            delete(controllers::jobs::cancel_playlist_job),
+        )
+        .route(
+            "/playlist/{playlist_id}/cards",
+            get(controllers::view::playlist_cards),
+        )
+        .route(
+            "/playlist/{playlist_id}/preview",
+            get(controllers::view::print_preview),
+        )
+        .route(
+            "/playlist/{playlist_id}/export/pdf-bundle",
+            get(controllers::export::export_pdf_bundle),
+        )
+        .route(
+            "/playlist/{playlist_id}/export.csv",
+            get(controllers::export::export_csv),
+        )
+        .route(
+            "/playlist/{playlist_id}/download",
+            get(controllers::export::download_pdfs),
+        )
+        .route(
+            "/playlist/{playlist_id}/missing-years",
+            get(controllers::playlist::get_missing_years),
+        )
+        .route(
+            "/playlist/by-spotify/{spotify_id}",
+            get(controllers::playlist::get_playlist_by_spotify_id),
+        )
+        .route(
+            "/playlist/{playlist_id}/tracks/{position}",
+            patch(controllers::playlist::update_track),
+        )
+        .route(
+            "/playlist/{playlist_id}/sort",
+            post(controllers::playlist::sort_tracks),
+        )
+        .route(
+            "/playlist/{playlist_id}/tags",
+            post(controllers::playlist::add_tag),
+        )
+        .route(
+            "/playlist/{playlist_id}/tags/{tag}",
+            delete(controllers::playlist::remove_tag),
+        )
+        .route("/playlists", get(controllers::playlist::list_playlists))
+        .route(
+            "/api/playlists",
+            get(controllers::playlist::find_playlists_by_name),
         )
+        .route(
+            "/api/playlists/search",
+            get(controllers::playlist::search_playlists),
+        )
+        .route(
+            "/playlists/recent",
+            get(controllers::view::recent_playlists),
+        )
+        .route(
+            "/playlists/search",
+            get(controllers::view::search_playlists),
+        )
+        .route(
+            "/playlists/from-album",
+            post(controllers::playlist::create_playlist_from_album),
+        )
+        .layer(axum::middleware::from_fn(session::ensure_session))
+        .layer(axum::middleware::from_fn(request_id::assign_request_id))
         .with_state(services);
 
     let addr = format!("{}:{}", host, port);
     info!("Listening on {}", addr);
 
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            pdf_rate_limiter.retain_recent();
+        }
+    });
+
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app.into_make_service()).await?;
+    // The client address is what rate limits are keyed on
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
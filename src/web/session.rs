@@ -0,0 +1,94 @@
+//! Anonymous sessions identified by a `session_id` cookie
+
+use crate::domain::{SessionId, UserContext};
+use crate::web::error::ApiError;
+use anyhow::anyhow;
+use axum::extract::{FromRequestParts, Request};
+use axum::http::header::{COOKIE, SET_COOKIE};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use uuid::Uuid;
+
+pub const SESSION_COOKIE: &str = "session_id";
+
+/// Attaches the caller's [`UserContext`] to every request, starting a new session
+/// and setting its cookie when the request has none
+pub async fn ensure_session(mut request: Request, next: Next) -> Response {
+    let existing = session_from_cookies(request.headers());
+    let session_id = existing.clone().unwrap_or_default();
+    request
+        .extensions_mut()
+        .insert(UserContext::new(session_id.clone()));
+
+    let mut response = next.run(request).await;
+    if existing.is_none() {
+        response.headers_mut().append(
+            SET_COOKIE,
+            HeaderValue::from_str(&session_cookie(&session_id)).unwrap(),
+        );
+    }
+    response
+}
+
+/// `SameSite=Lax` so following a link from another site still sends the cookie;
+/// with `Strict` such a navigation would start, and overwrite, a new session
+fn session_cookie(session_id: &SessionId) -> String {
+    format!("{SESSION_COOKIE}={session_id}; Path=/; HttpOnly; SameSite=Lax")
+}
+
+/// Reads the session ID from the `Cookie` headers, ignoring malformed values
+fn session_from_cookies(headers: &HeaderMap) -> Option<SessionId> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .and_then(|(_, value)| Uuid::parse_str(value).ok())
+        .map(SessionId::from)
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for UserContext {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<UserContext>()
+            .cloned()
+            .ok_or_else(|| ApiError::Internal(anyhow!("Request has no session")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_from_cookies() {
+        let session_id = SessionId::new();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            COOKIE,
+            HeaderValue::from_str(&format!("theme=dark; session_id={session_id}")).unwrap(),
+        );
+        assert_eq!(session_from_cookies(&headers), Some(session_id));
+
+        headers.insert(COOKIE, HeaderValue::from_static("session_id=not-a-uuid"));
+        assert_eq!(session_from_cookies(&headers), None);
+
+        assert_eq!(session_from_cookies(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_session_cookie_is_http_only_and_lax() {
+        let session_id = SessionId::new();
+        assert_eq!(
+            session_cookie(&session_id),
+            format!("session_id={session_id}; Path=/; HttpOnly; SameSite=Lax")
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::domain;
+
+/// A row of the admin jobs table
+#[derive(Debug)]
+pub struct AdminJobVM {
+    pub id: String,
+    pub job_type: String,
+    pub status: domain::JobStatus,
+    /// `None` when the playlist was deleted or the job predates sessions
+    pub playlist_name: Option<String>,
+    pub playlist_id: Option<String>,
+    pub created_at: String,
+    /// Only known once the job is done
+    pub duration: Option<String>,
+}
+
+impl AdminJobVM {
+    pub fn new(job: &domain::Job, playlist_name: Option<String>) -> Self {
+        Self {
+            id: job.id.to_string(),
+            job_type: job
+                .job_type()
+                .map_or("unknown", |job_type| job_type.as_str())
+                .to_string(),
+            status: job.status.clone(),
+            playlist_name,
+            playlist_id: job.playlist_id().map(|id| id.to_string()),
+            created_at: job.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            duration: job.duration().map(|duration| {
+                let seconds = duration.num_seconds().max(0);
+                format!("{}m {:02}s", seconds / 60, seconds % 60)
+            }),
+        }
+    }
+
+    pub fn status_color(&self) -> &'static str {
+        match self.status {
+            domain::JobStatus::Completed => "bg-green-900 text-green-300",
+            domain::JobStatus::Failed => "bg-red-900 text-red-300",
+            domain::JobStatus::Processing => "bg-yellow-900 text-yellow-300",
+            domain::JobStatus::Retrying => "bg-orange-900 text-orange-300",
+            domain::JobStatus::Pending => "bg-neutral-900 text-gray-300",
+            domain::JobStatus::Cancelled => "bg-neutral-800 text-gray-400",
+        }
+    }
+}
+
+/// Template context for the admin jobs dashboard
+#[derive(askama::Template, Debug)]
+#[template(path = "admin_jobs.html")]
+pub struct AdminJobsTemplate {
+    /// Page title
+    pub title: String,
+    pub jobs: Vec<AdminJobVM>,
+    /// Status the jobs are filtered by, empty for all jobs
+    pub status_filter: String,
+    pub page: u32,
+    pub previous_page: Option<u32>,
+    pub next_page: Option<u32>,
+    /// Number of jobs matching the filter
+    pub total: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use askama::Template;
+
+    fn job(status: domain::JobStatus) -> domain::Job {
+        let mut job = domain::Job::new(serde_json::json!({
+            "type": domain::JobType::GeneratePlaylistPdfs,
+            "playlist_id": domain::PlaylistId::new(),
+        }));
+        job.completed_at = status
+            .is_done()
+            .then(|| job.created_at + chrono::Duration::seconds(75));
+        job.status = status;
+        job
+    }
+
+    #[test]
+    fn test_rows_are_colored_by_status() {
+        let html = AdminJobsTemplate {
+            title: "Jobs".to_string(),
+            jobs: vec![
+                AdminJobVM::new(
+                    &job(domain::JobStatus::Completed),
+                    Some("Party".to_string()),
+                ),
+                AdminJobVM::new(&job(domain::JobStatus::Failed), None),
+                AdminJobVM::new(&job(domain::JobStatus::Processing), None),
+            ],
+            status_filter: String::new(),
+            page: 1,
+            previous_page: None,
+            next_page: None,
+            total: 3,
+        }
+        .render()
+        .unwrap();
+
+        assert!(html.contains("bg-green-900"));
+        assert!(html.contains("bg-red-900"));
+        assert!(html.contains("bg-yellow-900"));
+        assert!(html.contains("Party"));
+        assert!(html.contains("1m 15s"));
+    }
+}
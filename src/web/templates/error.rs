@@ -1,11 +1,76 @@
+use crate::web::request_id::current_request_id;
 use axum::http::StatusCode;
 
 /// Template context for error pages
 #[derive(askama::Template, Debug)]
 #[template(path = "error.html")]
 pub struct ErrorTemplate {
-    /// Error message to display
+    /// Error message to display, not shown for server errors
     pub details: String,
     /// HTTP status code
     pub status_code: StatusCode,
+    /// ID of the failed request, for bug reports
+    pub request_id: Option<String>,
+}
+
+impl ErrorTemplate {
+    /// Error page for the request being handled
+    pub fn new(status_code: StatusCode, details: String) -> Self {
+        Self {
+            details,
+            status_code,
+            request_id: current_request_id().map(|id| id.to_string()),
+        }
+    }
+
+    fn is_not_found(&self) -> bool {
+        self.status_code == StatusCode::NOT_FOUND
+    }
+
+    fn is_bad_request(&self) -> bool {
+        self.status_code == StatusCode::BAD_REQUEST
+    }
+
+    fn is_server_error(&self) -> bool {
+        self.status_code.is_server_error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use askama::Template;
+
+    fn render(status_code: StatusCode, details: &str) -> String {
+        ErrorTemplate {
+            details: details.to_string(),
+            status_code,
+            request_id: Some("2f1c6a55-0d0e-4b8e-9a57-5b1f8a4b7c01".to_string()),
+        }
+        .render()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_not_found_links_to_playlists_and_import() {
+        let html = render(StatusCode::NOT_FOUND, "Playlist with id abc not found");
+        assert!(html.contains("Playlist with id abc not found"));
+        assert!(html.contains("Return to playlist list"));
+        assert!(html.contains("Import a new playlist"));
+        assert!(html.contains("2f1c6a55-0d0e-4b8e-9a57-5b1f8a4b7c01"));
+    }
+
+    #[test]
+    fn test_bad_request_shows_validation_message() {
+        let html = render(StatusCode::BAD_REQUEST, "Tag must not be empty");
+        assert!(html.contains("Tag must not be empty"));
+        assert!(!html.contains("Return to playlist list"));
+    }
+
+    #[test]
+    fn test_server_error_hides_details() {
+        let html = render(StatusCode::INTERNAL_SERVER_ERROR, "database is locked");
+        assert!(!html.contains("database is locked"));
+        assert!(html.contains("2f1c6a55-0d0e-4b8e-9a57-5b1f8a4b7c01"));
+    }
 }
@@ -1,7 +1,179 @@
+use crate::application::playlist_service::LibraryStats;
+use crate::domain;
+
+/// Number of playlists in each of the recent playlist lists on the index page
+pub const RECENT_PLAYLISTS_COUNT: u32 = 10;
+
+#[derive(Debug)]
+pub struct PlaylistSummaryVM {
+    pub id: String,
+    pub name: String,
+    pub track_count: usize,
+    /// Creation time for playlists that were never updated
+    pub last_updated: String,
+}
+
+impl From<&domain::Playlist> for PlaylistSummaryVM {
+    fn from(playlist: &domain::Playlist) -> Self {
+        Self {
+            id: playlist.id.to_string(),
+            name: playlist.display_name().to_string(),
+            track_count: playlist.track_count(),
+            last_updated: playlist
+                .updated_at
+                .or(playlist.created_at)
+                .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Size of the playlist library, counted like "1 playlist" or "3,241 tracks"
+#[derive(Debug)]
+pub struct LibraryStatsVM {
+    pub playlists: String,
+    pub tracks: String,
+}
+
+impl From<LibraryStats> for LibraryStatsVM {
+    fn from(stats: LibraryStats) -> Self {
+        Self {
+            playlists: counted(stats.playlists, "playlist"),
+            tracks: counted(stats.tracks, "track"),
+        }
+    }
+}
+
+fn counted(count: u64, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        count => format!("{} {noun}s", separate_thousands(count)),
+    }
+}
+
+fn separate_thousands(count: u64) -> String {
+    let digits = count.to_string();
+    let mut separated = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            separated.push(',');
+        }
+        separated.push(digit);
+    }
+    separated
+}
+
 /// Template context for the index page
 #[derive(askama::Template, Debug)]
 #[template(path = "index.html")]
 pub struct IndexTemplate {
     /// Page title
     pub title: String,
+    pub recently_created: Vec<PlaylistSummaryVM>,
+    pub recently_updated: Vec<PlaylistSummaryVM>,
+    /// Not shown while the library is empty
+    pub library_stats: Option<LibraryStatsVM>,
+}
+
+/// HTMX partial refreshing the recent playlists of the index page
+#[derive(askama::Template, Debug)]
+#[template(path = "partials/recent_playlists.html")]
+pub struct RecentPlaylistsTemplate {
+    pub recently_created: Vec<PlaylistSummaryVM>,
+    pub recently_updated: Vec<PlaylistSummaryVM>,
+}
+
+/// HTMX partial listing the playlists matching the search on the index page
+#[derive(askama::Template, Debug)]
+#[template(path = "partials/playlist_search_results.html")]
+pub struct PlaylistSearchResultsTemplate {
+    /// Trimmed search input, nothing is listed while it is empty
+    pub query: String,
+    pub playlists: Vec<PlaylistSummaryVM>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use askama::Template;
+
+    fn summary(name: &str) -> PlaylistSummaryVM {
+        PlaylistSummaryVM {
+            id: domain::PlaylistId::new().to_string(),
+            name: name.to_string(),
+            track_count: 12,
+            last_updated: "2025-01-07 12:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_recent_playlists_sections() {
+        let html = RecentPlaylistsTemplate {
+            recently_created: vec![summary("Newest")],
+            recently_updated: vec![summary("Touched")],
+        }
+        .render()
+        .unwrap();
+        let (created, updated) = html.split_once("Recently Updated").unwrap();
+        assert!(created.contains("Recently Created") && created.contains("Newest"));
+        assert!(updated.contains("Touched") && updated.contains("2025-01-07 12:00"));
+
+        let empty = RecentPlaylistsTemplate {
+            recently_created: vec![],
+            recently_updated: vec![],
+        }
+        .render()
+        .unwrap();
+        assert!(!empty.contains("Recently Created"));
+    }
+
+    #[test]
+    fn test_playlist_search_results() {
+        let html = PlaylistSearchResultsTemplate {
+            query: "hits".to_string(),
+            playlists: vec![summary("Summer Hits")],
+        }
+        .render()
+        .unwrap();
+        assert!(html.contains("Summer Hits"));
+
+        let none = PlaylistSearchResultsTemplate {
+            query: "jazz".to_string(),
+            playlists: vec![],
+        }
+        .render()
+        .unwrap();
+        assert!(none.contains("No playlists match"));
+
+        let cleared = PlaylistSearchResultsTemplate {
+            query: String::new(),
+            playlists: vec![],
+        }
+        .render()
+        .unwrap();
+        assert!(cleared.trim().is_empty());
+    }
+
+    #[test]
+    fn test_library_stats_separate_thousands() {
+        let stats = LibraryStatsVM::from(LibraryStats {
+            playlists: 47,
+            tracks: 3241,
+        });
+        assert_eq!(stats.playlists, "47 playlists");
+        assert_eq!(stats.tracks, "3,241 tracks");
+        assert_eq!(separate_thousands(0), "0");
+        assert_eq!(separate_thousands(100_000), "100,000");
+        assert_eq!(separate_thousands(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn test_library_stats_of_a_single_playlist_are_singular() {
+        let stats = LibraryStatsVM::from(LibraryStats {
+            playlists: 1,
+            tracks: 1,
+        });
+        assert_eq!(stats.playlists, "1 playlist");
+        assert_eq!(stats.tracks, "1 track");
+    }
 }
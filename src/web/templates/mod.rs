@@ -1,9 +1,28 @@
 //! Template modules
 //!
 //! This module contains the Rust template structs that correspond to the HTML templates.
+pub mod admin;
+pub use admin::AdminJobsTemplate;
 pub mod playlist;
-pub use playlist::PlaylistTemplate;
+pub use playlist::{PlaylistCardsTemplate, PlaylistTemplate};
 pub mod error;
 pub use error::ErrorTemplate;
 pub mod index;
-pub use index::IndexTemplate;
+pub use index::{IndexTemplate, PlaylistSearchResultsTemplate, RecentPlaylistsTemplate};
+pub mod print_preview;
+pub use print_preview::PrintPreviewTemplate;
+
+/// Renders a QR code for `url` as an inline SVG that fills its container
+pub(crate) fn qr_code_svg(url: &str) -> Result<String, qrcode::types::QrError> {
+    let code = qrcode::QrCode::new(url)?;
+    let svg = code
+        .render::<qrcode::render::svg::Color>()
+        .min_dimensions(0, 200)
+        .max_dimensions(200, 200)
+        .build();
+
+    Ok(svg.replace(
+        r#"crispEdges""#,
+        r#"crispEdges" style="height: 100%; width: 100%""#,
+    ))
+}
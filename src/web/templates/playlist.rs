@@ -1,4 +1,9 @@
 use crate::domain;
+use crate::web::templates::qr_code_svg;
+use std::num::NonZeroUsize;
+
+/// Number of tracks rendered per lazily loaded card page
+pub const CARDS_PAGE_SIZE: usize = 24;
 
 #[derive(Debug)]
 pub struct TrackVM {
@@ -7,6 +12,23 @@ pub struct TrackVM {
     pub year: i32,
     pub qr_code: String,
     pub album_cover_url: Option<String>,
+    /// Only known when built from the whole playlist
+    pub difficulty: Option<domain::Difficulty>,
+}
+
+impl TryFrom<&domain::Track> for TrackVM {
+    type Error = qrcode::types::QrError;
+
+    fn try_from(track: &domain::Track) -> Result<Self, Self::Error> {
+        Ok(Self {
+            title: track.title.clone(),
+            artist: track.artist.clone(),
+            year: track.year,
+            qr_code: qr_code_svg(&track.spotify_url)?,
+            album_cover_url: track.album_cover_url.clone(),
+            difficulty: None,
+        })
+    }
 }
 
 impl TrackVM {
@@ -17,6 +39,14 @@ impl TrackVM {
     pub fn album_cover_url_or_empty(&self) -> &str {
         self.album_cover_url.as_deref().unwrap_or("")
     }
+
+    pub fn difficulty_color(&self) -> &'static str {
+        match self.difficulty {
+            Some(domain::Difficulty::Easy) => "bg-green-900 text-green-300",
+            Some(domain::Difficulty::Medium) => "bg-yellow-900 text-yellow-300",
+            Some(domain::Difficulty::Hard) | None => "bg-red-900 text-red-300",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -35,7 +65,7 @@ impl From<domain::Job> for JobVM {
     fn from(job: domain::Job) -> Self {
         Self {
             id: job.id.to_string(),
-            is_in_progress: job.status != domain::JobStatus::Completed,
+            is_in_progress: !job.status.is_done(),
         }
     }
 }
@@ -47,26 +77,181 @@ pub struct PlaylistTemplate {
     /// Page title
     pub title: String,
     pub total_tracks: usize,
-    /// List of tracks to display
+    /// First page of tracks to display, later pages are lazy loaded
     pub tracks: Vec<TrackVM>,
+    /// Page to load when the end of the track list is revealed
+    pub next_page: Option<usize>,
     /// Helper fields for template
     pub playlist_id: String,
-    pub latest_job: Option<JobVM>,
+    pub latest_pdf_job: Option<JobVM>,
+    pub latest_refetch_job: Option<JobVM>,
     pub has_generated_pdfs: bool,
+    /// Subscribe to job status over WebSockets instead of SSE
+    pub prefer_websocket: bool,
+    /// Number of tracks without a usable release year
+    pub missing_year_count: usize,
+    /// Share of album covers and release years present, from 0.0 to 1.0
+    pub completeness_score: f64,
+    /// Tags attached to the playlist
+    pub tags: Vec<String>,
 }
 
 impl PlaylistTemplate {
     pub fn enable_download_buttons(&self) -> bool {
-        match &self.latest_job {
-            Some(job) => !job.is_in_progress && self.has_generated_pdfs,
-            None => false,
+        let generating = self
+            .latest_pdf_job
+            .as_ref()
+            .is_some_and(|job| job.is_in_progress);
+        self.has_generated_pdfs && !generating
+    }
+
+    pub fn completeness_percent(&self) -> u8 {
+        (self.completeness_score * 100.0).round() as u8
+    }
+
+    pub fn completeness_label(&self) -> &'static str {
+        match self.completeness_percent() {
+            90.. => "Excellent",
+            60..90 => "Good",
+            _ => "Poor",
+        }
+    }
+
+    pub fn completeness_color(&self) -> &'static str {
+        match self.completeness_percent() {
+            90.. => "bg-green-500",
+            60..90 => "bg-yellow-500",
+            _ => "bg-red-500",
         }
     }
 
     pub fn has_job_in_progress(&self) -> bool {
-        match &self.latest_job {
-            Some(job) => job.is_in_progress,
-            None => false,
+        [&self.latest_pdf_job, &self.latest_refetch_job]
+            .into_iter()
+            .flatten()
+            .any(|job| job.is_in_progress)
+    }
+}
+
+/// HTMX partial with a single page of track cards
+#[derive(askama::Template, Debug)]
+#[template(path = "partials/playlist_cards.html")]
+pub struct PlaylistCardsTemplate {
+    pub playlist_id: String,
+    pub tracks: Vec<TrackVM>,
+    pub next_page: Option<usize>,
+}
+
+impl PlaylistCardsTemplate {
+    /// Builds the cards for `page` (1-based) of the playlist
+    pub fn for_page(
+        playlist: &domain::Playlist,
+        page: NonZeroUsize,
+    ) -> Result<Self, qrcode::types::QrError> {
+        // A page far beyond the end simply renders no cards
+        let start = (page.get() - 1).saturating_mul(CARDS_PAGE_SIZE);
+
+        let difficulties = domain::PlaylistDomainService::difficulties(playlist);
+        let tracks = playlist
+            .tracks
+            .iter()
+            .zip(difficulties)
+            .skip(start)
+            .take(CARDS_PAGE_SIZE)
+            .map(|(track, difficulty)| {
+                TrackVM::try_from(track).map(|vm| TrackVM { difficulty, ..vm })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let next_page = start
+            .checked_add(CARDS_PAGE_SIZE)
+            .filter(|end| *end < playlist.tracks.len())
+            .and_then(|_| page.get().checked_add(1));
+
+        Ok(Self {
+            playlist_id: playlist.id.to_string(),
+            tracks,
+            next_page,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use askama::Template;
+
+    #[test]
+    fn test_cards_page_renders_one_page_of_tracks() {
+        let playlist = fixtures::playlist("Cards", fixtures::tracks(48));
+
+        let first = PlaylistCardsTemplate::for_page(&playlist, NonZeroUsize::MIN).unwrap();
+        assert_eq!(first.next_page, Some(2));
+
+        let html = PlaylistCardsTemplate::for_page(&playlist, NonZeroUsize::new(2).unwrap())
+            .unwrap()
+            .render()
+            .unwrap();
+        assert_eq!(html.matches(r#"class="track-card"#).count(), 24);
+        assert!(html.contains("Song 24"));
+        // The last page has no sentinel for further pages
+        assert!(!html.contains(r#"hx-trigger="revealed""#));
+    }
+
+    #[test]
+    fn test_cards_page_beyond_the_end_is_empty() {
+        let playlist = fixtures::playlist("Cards", fixtures::tracks(48));
+
+        for page in [NonZeroUsize::new(3).unwrap(), NonZeroUsize::MAX] {
+            let cards = PlaylistCardsTemplate::for_page(&playlist, page).unwrap();
+            assert!(cards.tracks.is_empty());
+            assert_eq!(cards.next_page, None);
         }
     }
+
+    #[test]
+    fn test_cards_show_difficulty_badges() {
+        let tracks = (0..4)
+            .map(|i| domain::Track {
+                popularity: Some(i * 10),
+                ..fixtures::track(i)
+            })
+            .collect();
+        let playlist = fixtures::playlist("Cards", tracks);
+
+        let html = PlaylistCardsTemplate::for_page(&playlist, NonZeroUsize::MIN)
+            .unwrap()
+            .render()
+            .unwrap();
+        assert_eq!(html.matches(">Hard<").count(), 1);
+        assert_eq!(html.matches(">Medium<").count(), 2);
+        assert_eq!(html.matches(">Easy<").count(), 1);
+    }
+
+    #[test]
+    fn test_in_progress_job_reloads_on_done_with_refresh_fallback() {
+        let template = PlaylistTemplate {
+            title: "Cards".to_string(),
+            total_tracks: 0,
+            tracks: vec![],
+            next_page: None,
+            playlist_id: "playlist".to_string(),
+            latest_pdf_job: Some(JobVM {
+                id: "job".to_string(),
+                is_in_progress: true,
+            }),
+            latest_refetch_job: None,
+            has_generated_pdfs: false,
+            prefer_websocket: false,
+            missing_year_count: 0,
+            completeness_score: 1.0,
+            tags: vec![],
+        };
+
+        let html = template.render().unwrap();
+        assert!(html.contains(r#"sse-connect="/api/playlist/playlist/jobs/job/status""#));
+        assert!(html.contains(r#"hx-trigger="sse:done""#));
+        assert!(html.contains(r#"id="job-refresh-job""#));
+    }
 }
@@ -0,0 +1,87 @@
+use crate::application::pdf_generator::{PdfGeneratorConfig, artist_lines, title_lines};
+use crate::domain;
+use crate::web::templates::qr_code_svg;
+
+/// A single card as it is printed by the PDF generator
+#[derive(Debug)]
+pub struct PreviewCardVM {
+    pub artist_lines: Vec<String>,
+    pub title_lines: Vec<String>,
+    pub year: i32,
+    pub qr_code: String,
+}
+
+/// One printed page, holding at most as many cards as the default [`PdfGeneratorConfig`] fits
+#[derive(Debug)]
+pub struct PreviewPageVM {
+    pub cards: Vec<PreviewCardVM>,
+}
+
+/// Template context for the print preview page
+#[derive(askama::Template, Debug)]
+#[template(path = "print_preview.html")]
+pub struct PrintPreviewTemplate {
+    /// Page title
+    pub title: String,
+    pub playlist_id: String,
+    pub total_tracks: usize,
+    /// Text printed below the QR code on the back of each card
+    pub back_text: String,
+    pub pages: Vec<PreviewPageVM>,
+}
+
+impl PrintPreviewTemplate {
+    /// Lays out the playlist tracks using the default grid of the PDF generator, which
+    /// the page styles are written for
+    pub fn new(playlist: &domain::Playlist) -> Result<Self, qrcode::types::QrError> {
+        let config = PdfGeneratorConfig::default();
+        let pages = playlist
+            .tracks
+            .chunks(config.layout.cards_per_page())
+            .map(|tracks_on_page| {
+                let cards = tracks_on_page
+                    .iter()
+                    .map(|track| {
+                        Ok(PreviewCardVM {
+                            artist_lines: artist_lines(&track.artist, &config),
+                            title_lines: title_lines(&track.title, &config),
+                            year: track.year,
+                            qr_code: qr_code_svg(&track.spotify_url)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, qrcode::types::QrError>>()?;
+
+                Ok(PreviewPageVM { cards })
+            })
+            .collect::<Result<Vec<_>, qrcode::types::QrError>>()?;
+
+        Ok(Self {
+            title: playlist.display_name().to_string(),
+            playlist_id: playlist.id.to_string(),
+            total_tracks: playlist.tracks.len(),
+            back_text: playlist.card_back_text().to_string(),
+            pages,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use askama::Template;
+
+    #[test]
+    fn test_renders_one_card_per_track_and_side() {
+        let html = PrintPreviewTemplate::new(&fixtures::playlist("Preview", fixtures::tracks(30)))
+            .unwrap()
+            .render()
+            .unwrap();
+
+        assert_eq!(html.matches(r#"class="card card-front""#).count(), 30);
+        assert_eq!(html.matches(r#"class="card card-back""#).count(), 30);
+        // 30 tracks fill three pages per side
+        assert_eq!(html.matches(r#"class="sheet sheet-front""#).count(), 3);
+        assert_eq!(html.matches(r#"class="sheet sheet-back""#).count(), 3);
+    }
+}
@@ -0,0 +1,346 @@
+use hitster::application::{FetchProgress, ISpotifyClient, SpotifyError};
+use hitster::config::{DatabaseConfig, PdfConfig, ServerConfig, SpotifyConfig};
+use hitster::domain::{JobProgress, SpotifyId};
+use hitster::{Settings, SpotifyClient};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const PLAYLIST_ID: &str = "3cEYpjA9oz9GiPac4AsH4n";
+const ALBUM_ID: &str = "0ETFjACtuP2ADo6LFhL6HN";
+
+fn settings(api_base_url: String, skip_episodes: bool) -> Settings {
+    Settings {
+        spotify: SpotifyConfig {
+            client_id: "client-id".to_string(),
+            client_secret: "client-secret".to_string(),
+            api_base_url: Some(api_base_url),
+            skip_episodes,
+            max_retry_after_seconds: 60,
+        },
+        database: DatabaseConfig {
+            path: ":memory:".to_string(),
+            max_connections: 1,
+            timeout_seconds: 30,
+        },
+        server: ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+        },
+        pdf: PdfConfig::default(),
+        prefer_websocket: false,
+        dedup_on_import: false,
+        sse_idle_timeout_seconds: 60,
+        max_sse_connections: 100,
+        pdf_worker_count: 1,
+        job_queue_capacity: 100,
+        pdf_rate_limit_per_minute: 10,
+        max_playlist_tracks: 1000,
+        job_retention_days: 30,
+        stale_job_minutes: 30,
+        admin_token: None,
+        db_integrity_check_on_startup: true,
+    }
+}
+
+fn spotify_id() -> SpotifyId {
+    SpotifyId::parse(&format!("https://open.spotify.com/playlist/{PLAYLIST_ID}")).unwrap()
+}
+
+/// Starts a fake Spotify API that hands out a token and serves `fixture` as the playlist
+async fn mock_spotify(fixture: &'static str) -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "test-token",
+            "token_type": "Bearer",
+            "expires_in": 3600,
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(format!("/playlists/{PLAYLIST_ID}")))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(fixture, "application/json"))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+#[tokio::test]
+async fn test_get_playlist_with_tracks_parses_fixture() {
+    let server = mock_spotify(include_str!("fixtures/spotify_playlist.json")).await;
+    let client = SpotifyClient::new(&settings(server.uri(), true))
+        .await
+        .unwrap();
+    assert!(client.has_token().await);
+
+    let progress = FetchProgress::default();
+    let fetched = client
+        .get_playlist_with_tracks(&spotify_id(), &progress)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(fetched.skipped_episodes, 0);
+    assert_eq!(progress.get(), Some(JobProgress { done: 2, total: 2 }));
+
+    let playlist = fetched.playlist;
+    assert_eq!(playlist.name, "Hitster Fixture");
+    assert_eq!(playlist.spotify_id, Some(spotify_id()));
+    assert_eq!(playlist.tracks.len(), 2);
+    assert_eq!(playlist.spotify_track_count, Some(2));
+
+    let dancing_queen = &playlist.tracks[0];
+    assert_eq!(dancing_queen.title, "Dancing Queen");
+    assert_eq!(dancing_queen.artist, "ABBA");
+    assert_eq!(dancing_queen.year, 1976);
+    assert_eq!(
+        dancing_queen.spotify_url,
+        "https://open.spotify.com/track/0GjEhVFGZW8afUYGChu3Rr"
+    );
+    assert_eq!(
+        dancing_queen.album_cover_url.as_deref(),
+        Some("https://i.scdn.co/image/cover-one")
+    );
+    assert_eq!(dancing_queen.popularity, Some(80));
+    assert_eq!(dancing_queen.duration_ms, Some(230000));
+
+    let under_pressure = &playlist.tracks[1];
+    assert_eq!(under_pressure.artist, "Queen, David Bowie");
+    assert_eq!(under_pressure.year, 1981);
+    assert_eq!(under_pressure.album_cover_url, None);
+}
+
+#[tokio::test]
+async fn test_get_playlist_with_tracks_skips_episodes() {
+    let server = mock_spotify(include_str!("fixtures/spotify_mixed_playlist.json")).await;
+    let client = SpotifyClient::new(&settings(server.uri(), true))
+        .await
+        .unwrap();
+
+    let fetched = client
+        .get_playlist_with_tracks(&spotify_id(), &FetchProgress::default())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(fetched.skipped_episodes, 1);
+    let titles: Vec<_> = fetched
+        .playlist
+        .tracks
+        .iter()
+        .map(|track| track.title.as_str())
+        .collect();
+    assert_eq!(titles, ["Dancing Queen", "Under Pressure"]);
+}
+
+#[tokio::test]
+async fn test_get_playlist_with_tracks_rejects_episodes_when_not_skipping() {
+    let server = mock_spotify(include_str!("fixtures/spotify_mixed_playlist.json")).await;
+    let client = SpotifyClient::new(&settings(server.uri(), false))
+        .await
+        .unwrap();
+
+    let err = client
+        .get_playlist_with_tracks(&spotify_id(), &FetchProgress::default())
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "Playlist contains podcast episodes");
+}
+
+#[tokio::test]
+async fn test_get_album_tracks_uses_album_details() {
+    let server = mock_spotify(include_str!("fixtures/spotify_playlist.json")).await;
+    Mock::given(method("GET"))
+        .and(path(format!("/albums/{ALBUM_ID}")))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            include_str!("fixtures/spotify_album.json"),
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/albums/{ALBUM_ID}/tracks")))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            include_str!("fixtures/spotify_album_tracks.json"),
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+    let client = SpotifyClient::new(&settings(server.uri(), true))
+        .await
+        .unwrap();
+
+    let album_id = SpotifyId::parse_album(&format!("spotify:album:{ALBUM_ID}")).unwrap();
+    let playlist = client.get_album_tracks(&album_id).await.unwrap().unwrap();
+
+    assert_eq!(playlist.name, "Abbey Road");
+    assert_eq!(playlist.spotify_id, None);
+    let titles: Vec<_> = playlist
+        .tracks
+        .iter()
+        .map(|track| track.title.as_str())
+        .collect();
+    assert_eq!(titles, ["Come Together", "Something"]);
+
+    let come_together = &playlist.tracks[0];
+    assert_eq!(come_together.artist, "The Beatles");
+    assert_eq!(come_together.year, 1969);
+    assert_eq!(
+        come_together.album_cover_url.as_deref(),
+        Some("https://i.scdn.co/image/abbey-road")
+    );
+    assert_eq!(come_together.popularity, None);
+}
+
+#[tokio::test]
+async fn test_get_album_tracks_of_an_unknown_album_is_none() {
+    let server = mock_spotify(include_str!("fixtures/spotify_playlist.json")).await;
+    Mock::given(method("GET"))
+        .and(path(format!("/albums/{ALBUM_ID}")))
+        .respond_with(ResponseTemplate::new(404).set_body_raw(
+            r#"{"error":{"status":404,"message":"Non existing id"}}"#,
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+    let client = SpotifyClient::new(&settings(server.uri(), true))
+        .await
+        .unwrap();
+
+    let album_id = SpotifyId::parse_album(&format!("spotify:album:{ALBUM_ID}")).unwrap();
+    assert!(client.get_album_tracks(&album_id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_get_playlist_metadata_requests_no_tracks() {
+    let server = mock_spotify(include_str!("fixtures/spotify_playlist.json")).await;
+    Mock::given(method("GET"))
+        .and(path(format!("/playlists/{PLAYLIST_ID}")))
+        .and(query_param("fields", "name,tracks.total"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "name": "Just The Name",
+            "tracks": {"total": 3},
+        })))
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = SpotifyClient::new(&settings(server.uri(), true))
+        .await
+        .unwrap();
+
+    let metadata = client
+        .get_playlist_metadata(&spotify_id())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(metadata.name, "Just The Name");
+    assert_eq!(metadata.track_count, 3);
+}
+
+#[tokio::test]
+async fn test_rate_limited_request_waits_for_retry_after() {
+    let server = mock_spotify(include_str!("fixtures/spotify_playlist.json")).await;
+    Mock::given(method("GET"))
+        .and(path(format!("/playlists/{PLAYLIST_ID}")))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "2"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    let client = SpotifyClient::new(&settings(server.uri(), true))
+        .await
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let playlist = client
+        .get_playlist_metadata(&spotify_id())
+        .await
+        .unwrap()
+        .unwrap();
+    let waited = started.elapsed();
+
+    assert_eq!(playlist.name, "Hitster Fixture");
+    assert!(
+        waited >= std::time::Duration::from_secs(2),
+        "retried after {waited:?}"
+    );
+    assert!(
+        waited < std::time::Duration::from_secs(4),
+        "retried after {waited:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_rate_limit_and_auth_failures_are_distinguishable() {
+    let server = mock_spotify(include_str!("fixtures/spotify_playlist.json")).await;
+    Mock::given(method("GET"))
+        .and(path(format!("/playlists/{PLAYLIST_ID}")))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+    let client = SpotifyClient::new(&settings(server.uri(), true))
+        .await
+        .unwrap();
+
+    let rate_limited = client
+        .get_playlist_metadata(&spotify_id())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        rate_limited.downcast_ref::<SpotifyError>(),
+        Some(&SpotifyError::RateLimited {
+            retry_after_seconds: Some(0)
+        })
+    );
+    let unauthorized = client.search_playlists("hits", 3).await.unwrap_err();
+    assert_eq!(
+        unauthorized.downcast_ref::<SpotifyError>(),
+        Some(&SpotifyError::Unauthorized)
+    );
+}
+
+#[tokio::test]
+async fn test_search_playlists_parses_results() {
+    let server = mock_spotify(include_str!("fixtures/spotify_playlist.json")).await;
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .and(query_param("q", "hits"))
+        .and(query_param("type", "playlist"))
+        .and(query_param("limit", "3"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            include_str!("fixtures/spotify_playlist_search.json"),
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+    let client = SpotifyClient::new(&settings(server.uri(), true))
+        .await
+        .unwrap();
+
+    let playlists = client.search_playlists("hits", 3).await.unwrap();
+
+    // Spotify pads search results with nulls, they are left out
+    assert_eq!(playlists.len(), 2);
+    assert_eq!(playlists[0].id.as_str(), "37i9dQZF1DXcBWIGoYBM5M");
+    assert_eq!(playlists[0].name, "Today's Top Hits");
+    assert_eq!(playlists[0].owner, "Spotify");
+    assert_eq!(playlists[0].track_count, 50);
+    assert_eq!(
+        playlists[0].image_url.as_deref(),
+        Some("https://i.scdn.co/image/hits")
+    );
+    assert_eq!(playlists[1].owner, "someone");
+    assert_eq!(playlists[1].image_url, None);
+}